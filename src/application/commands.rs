@@ -1,3 +1,4 @@
 pub mod create_set;
 pub mod create_user;
+pub mod set_banned_col;
 pub mod set_deleted_col;
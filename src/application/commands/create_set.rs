@@ -6,7 +6,8 @@ use crate::application::{
     set::{dto::create::Create, traits::SetRepo as _},
 };
 
-pub async fn create_set<'a, UoW>(uow: &'a mut UoW, set: Create<'a>) -> Result<(), TransactionKind>
+/// Save the set. Return `false`, if the set is already saved.
+pub async fn create_set<'a, UoW>(uow: &'a mut UoW, set: Create<'a>) -> Result<bool, TransactionKind>
 where
     UoW: UoWTrait,
 {
@@ -19,18 +20,61 @@ where
 
     match result {
         Ok(_) => (),
-        Err(RepoKind::Unexpected(_)) => {
+        Err(RepoKind::Unexpected(err)) => {
             uow.rollback()
                 .await
                 .map_err(TransactionKind::rollback_err)?;
+
+            return Err(TransactionKind::repo_err(err));
         }
         // skip if created
         Err(RepoKind::Exception(_)) => {
-            return Ok(());
+            // close transaction, so connection is not held until the next call
+            uow.rollback()
+                .await
+                .map_err(TransactionKind::rollback_err)?;
+
+            return Ok(false);
         }
     };
 
     uow.commit().await.map_err(TransactionKind::commit_err)?;
 
-    Ok(())
+    Ok(true)
+}
+
+#[tokio::test]
+async fn create_set_test() {
+    use crate::{
+        application::common::traits::uow::UoWFactory as _,
+        infrastructure::database::memory::UoWFactory,
+    };
+
+    let uow_factory = UoWFactory::new();
+    let mut uow = uow_factory.create_uow();
+
+    assert!(create_set(&mut uow, Create::new(1, "short_name", "title"))
+        .await
+        .unwrap());
+    // skip if already created
+    assert!(
+        !create_set(&mut uow, Create::new(2, "short_name", "other title"))
+            .await
+            .unwrap()
+    );
+
+    let database = uow_factory.snapshot();
+
+    assert_eq!(database.sets.len(), 1);
+    assert_eq!(database.sets[0].tg_id, 1);
+    assert_eq!(database.sets[0].title, "title");
+
+    let mut uow = uow_factory.create_unavailable_uow();
+
+    assert!(
+        create_set(&mut uow, Create::new(1, "other_short_name", "title"))
+            .await
+            .is_err()
+    );
+    assert_eq!(uow_factory.snapshot().sets.len(), 1);
 }
@@ -3,28 +3,46 @@ use crate::application::{
         exceptions::{RepoKind, TransactionKind},
         traits::uow::UoW as UoWTrait,
     },
-    user::{dto::create::Create, traits::UserRepo as _},
+    user::{
+        dto::{create::Create, set_active_col::SetActiveCol},
+        traits::UserRepo as _,
+    },
 };
 
+/// Create the user or mark already created one as active again
 pub async fn create_user<UoW>(uow: &mut UoW, user: Create) -> Result<(), TransactionKind>
 where
     UoW: UoWTrait,
 {
-    let result = uow
-        .user_repo()
-        .await
-        .map_err(TransactionKind::begin_err)?
-        .create(user)
-        .await;
+    let result = {
+        let mut repo = uow.user_repo().await.map_err(TransactionKind::begin_err)?;
+
+        // the user can be already created, but block the bot before
+        match repo
+            .set_active_col(SetActiveCol::new(user.tg_id(), true))
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(RepoKind::Unexpected(err)) => Err(RepoKind::Unexpected(err)),
+            Err(RepoKind::Exception(_)) => repo.create(user).await,
+        }
+    };
 
     match result {
         Ok(_) => (),
-        Err(RepoKind::Unexpected(_)) => {
+        Err(RepoKind::Unexpected(err)) => {
             uow.rollback()
                 .await
                 .map_err(TransactionKind::rollback_err)?;
+
+            return Err(TransactionKind::repo_err(err));
         }
         Err(RepoKind::Exception(_)) => {
+            // close transaction, so connection is not held until the next call
+            uow.rollback()
+                .await
+                .map_err(TransactionKind::rollback_err)?;
+
             return Ok(());
         }
     }
@@ -33,3 +51,46 @@ where
 
     Ok(())
 }
+
+#[tokio::test]
+async fn create_user_test() {
+    use crate::{
+        application::common::traits::uow::UoWFactory as _,
+        infrastructure::database::memory::UoWFactory,
+    };
+
+    let uow_factory = UoWFactory::new();
+    let mut uow = uow_factory.create_uow();
+
+    create_user(&mut uow, Create::new(1)).await.unwrap();
+    // skip if already created
+    create_user(&mut uow, Create::new(1)).await.unwrap();
+    create_user(&mut uow, Create::new(2)).await.unwrap();
+
+    let users: Vec<i64> = uow_factory
+        .snapshot()
+        .users
+        .iter()
+        .map(|user| user.tg_id)
+        .collect();
+
+    assert_eq!(users, vec![1, 2]);
+
+    // the user blocked the bot and then wrote to it again
+    uow.user_repo()
+        .await
+        .unwrap()
+        .set_active_col(SetActiveCol::new(1, false))
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    create_user(&mut uow, Create::new(1)).await.unwrap();
+
+    assert!(uow_factory.snapshot().users[0].active);
+
+    let mut uow = uow_factory.create_unavailable_uow();
+
+    assert!(create_user(&mut uow, Create::new(3)).await.is_err());
+    assert_eq!(uow_factory.snapshot().users.len(), 2);
+}
@@ -0,0 +1,75 @@
+use crate::application::{
+    common::{
+        exceptions::{RepoKind, TransactionKind},
+        traits::uow::UoW as UoWTrait,
+    },
+    user::{dto::set_banned_col::SetBannedCol, traits::UserRepo as _},
+};
+
+/// Return `false`, if the user isn't updated (user doesn't exist or unexpected error occurred)
+pub async fn set_banned_col<UoW>(uow: &mut UoW, user: SetBannedCol) -> Result<bool, TransactionKind>
+where
+    UoW: UoWTrait,
+{
+    let result = uow
+        .user_repo()
+        .await
+        .map_err(TransactionKind::begin_err)?
+        .set_banned_col(user)
+        .await;
+
+    match result {
+        Ok(_) => (),
+        Err(RepoKind::Unexpected(_) | RepoKind::Exception(_)) => {
+            // close transaction, so connection is not held until the next call
+            uow.rollback()
+                .await
+                .map_err(TransactionKind::rollback_err)?;
+
+            return Ok(false);
+        }
+    }
+
+    uow.commit().await.map_err(TransactionKind::commit_err)?;
+
+    Ok(true)
+}
+
+#[tokio::test]
+async fn set_banned_col_test() {
+    use crate::{
+        application::{
+            commands::create_user::create_user, common::traits::uow::UoWFactory as _,
+            user::dto::create::Create,
+        },
+        infrastructure::database::memory::UoWFactory,
+    };
+
+    let uow_factory = UoWFactory::new();
+    let mut uow = uow_factory.create_uow();
+
+    create_user(&mut uow, Create::new(1)).await.unwrap();
+    create_user(&mut uow, Create::new(2)).await.unwrap();
+
+    assert!(set_banned_col(&mut uow, SetBannedCol::new(1, true))
+        .await
+        .unwrap());
+    // nothing is updated if user doesn't exist
+    assert!(!set_banned_col(&mut uow, SetBannedCol::new(3, true))
+        .await
+        .unwrap());
+
+    let banned: Vec<(i64, bool)> = uow_factory
+        .snapshot()
+        .users
+        .into_iter()
+        .map(|user| (user.tg_id, user.banned))
+        .collect();
+
+    assert_eq!(banned, vec![(1, true), (2, false)]);
+
+    assert!(set_banned_col(&mut uow, SetBannedCol::new(1, false))
+        .await
+        .unwrap());
+    assert!(!uow_factory.snapshot().users[0].banned);
+}
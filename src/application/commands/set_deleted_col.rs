@@ -22,12 +22,19 @@ where
 
     match result {
         Ok(_) => (),
-        Err(RepoKind::Unexpected(_)) => {
+        Err(RepoKind::Unexpected(err)) => {
             uow.rollback()
                 .await
                 .map_err(TransactionKind::rollback_err)?;
+
+            return Err(TransactionKind::repo_err(err));
         }
         Err(RepoKind::Exception(_)) => {
+            // close transaction, so connection is not held until the next call
+            uow.rollback()
+                .await
+                .map_err(TransactionKind::rollback_err)?;
+
             return Ok(());
         }
     }
@@ -36,3 +43,53 @@ where
 
     Ok(())
 }
+
+#[tokio::test]
+async fn set_deleted_col_test() {
+    use crate::{
+        application::{
+            commands::create_set::create_set, common::traits::uow::UoWFactory as _,
+            set::dto::create::Create,
+        },
+        infrastructure::database::memory::UoWFactory,
+    };
+
+    let uow_factory = UoWFactory::new();
+    let mut uow = uow_factory.create_uow();
+
+    create_set(&mut uow, Create::new(1, "first", "title"))
+        .await
+        .unwrap();
+    create_set(&mut uow, Create::new(1, "second", "title"))
+        .await
+        .unwrap();
+
+    set_deleted_col(&mut uow, SetDeletedColByShortName::new("first", true))
+        .await
+        .unwrap();
+    // nothing happens if set doesn't exist
+    set_deleted_col(&mut uow, SetDeletedColByShortName::new("unknown", true))
+        .await
+        .unwrap();
+
+    let deleted: Vec<(String, bool)> = uow_factory
+        .snapshot()
+        .sets
+        .into_iter()
+        .map(|set| (set.short_name, set.deleted))
+        .collect();
+
+    assert_eq!(
+        deleted,
+        vec![("first".to_owned(), true), ("second".to_owned(), false)]
+    );
+
+    let mut uow = uow_factory.create_unavailable_uow();
+
+    assert!(
+        set_deleted_col(&mut uow, SetDeletedColByShortName::new("second", true))
+            .await
+            .is_err()
+    );
+    assert!(!uow_factory.snapshot().sets[1].deleted);
+}
@@ -44,6 +44,15 @@ where
     }
 }
 
+impl<RepoException> From<RepoError> for RepoKind<RepoException>
+where
+    RepoException: ApplicationException,
+{
+    fn from(error: RepoError) -> Self {
+        Self::Unexpected(error)
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("Begin transaction error: {message}")]
 pub struct BeginError {
@@ -102,6 +111,10 @@ pub enum TransactionKind {
 
     #[error(transparent)]
     RollbackError(RollbackError),
+
+    /// Unexpected error of the repository, after which transaction is rolled back
+    #[error(transparent)]
+    RepoError(RepoError),
 }
 
 impl TransactionKind {
@@ -116,6 +129,10 @@ impl TransactionKind {
     pub fn rollback_err(err: impl Into<RollbackError>) -> Self {
         Self::RollbackError(err.into())
     }
+
+    pub fn repo_err(err: impl Into<RepoError>) -> Self {
+        Self::RepoError(err.into())
+    }
 }
 
 impl ApplicationException for TransactionKind {}
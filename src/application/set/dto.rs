@@ -1,3 +1,4 @@
+pub mod count;
 pub mod create;
 pub mod delete_by_short_name;
 pub mod get_by_short_name;
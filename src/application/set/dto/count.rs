@@ -0,0 +1,26 @@
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct Count {
+    /// If: `None` -> count all
+    /// Some(true) -> count only deleted
+    /// Some(false) -> count only NOT deleted
+    get_deleted: Option<bool>,
+    /// If `true`, count only sets created today (UTC)
+    created_today: bool,
+}
+
+impl Count {
+    pub const fn new(get_deleted: Option<bool>, created_today: bool) -> Self {
+        Self {
+            get_deleted,
+            created_today,
+        }
+    }
+    pub const fn get_deleted(&self) -> Option<bool> {
+        self.get_deleted
+    }
+    pub const fn created_today(&self) -> bool {
+        self.created_today
+    }
+}
@@ -1,11 +1,15 @@
 use async_trait::async_trait;
 
-use crate::{application::common::exceptions::RepoKind, domain::entities::set::Set};
+use crate::{
+    application::common::exceptions::{RepoError, RepoKind},
+    domain::entities::set::Set,
+};
 
 use super::{
     dto::{
-        create::Create, delete_by_short_name::DeleteByShortName, get_by_short_name::GetByShortName,
-        get_by_tg_id::GetByTgID, set_deleted_col_by_short_name::SetDeletedColByShortName,
+        count::Count, create::Create, delete_by_short_name::DeleteByShortName,
+        get_by_short_name::GetByShortName, get_by_tg_id::GetByTgID,
+        set_deleted_col_by_short_name::SetDeletedColByShortName,
     },
     exceptions::{SetShortNameAlreadyExist, SetShortNameNotExist, SetTgIdNotExist},
 };
@@ -34,4 +38,6 @@ pub trait SetRepo {
         &'a mut self,
         set: SetDeletedColByShortName<'a>,
     ) -> Result<(), RepoKind<SetShortNameNotExist>>;
+
+    async fn count(&mut self, set: Count) -> Result<i64, RepoError>;
 }
@@ -1,2 +1,4 @@
 pub mod create;
 pub mod get_by_tg_id;
+pub mod set_active_col;
+pub mod set_banned_col;
@@ -0,0 +1,19 @@
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct SetActiveCol {
+    tg_id: i64,
+    active: bool,
+}
+
+impl SetActiveCol {
+    pub const fn new(tg_id: i64, active: bool) -> Self {
+        Self { tg_id, active }
+    }
+    pub const fn tg_id(&self) -> i64 {
+        self.tg_id
+    }
+    pub const fn active(&self) -> bool {
+        self.active
+    }
+}
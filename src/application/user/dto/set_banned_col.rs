@@ -0,0 +1,19 @@
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct SetBannedCol {
+    tg_id: i64,
+    banned: bool,
+}
+
+impl SetBannedCol {
+    pub const fn new(tg_id: i64, banned: bool) -> Self {
+        Self { tg_id, banned }
+    }
+    pub const fn tg_id(&self) -> i64 {
+        self.tg_id
+    }
+    pub const fn banned(&self) -> bool {
+        self.banned
+    }
+}
@@ -1,9 +1,15 @@
 use async_trait::async_trait;
 
-use crate::{application::common::exceptions::RepoKind, domain::entities::user::User};
+use crate::{
+    application::common::exceptions::{RepoError, RepoKind},
+    domain::entities::user::User,
+};
 
 use super::{
-    dto::{create::Create, get_by_tg_id::GetByTgID},
+    dto::{
+        create::Create, get_by_tg_id::GetByTgID, set_active_col::SetActiveCol,
+        set_banned_col::SetBannedCol,
+    },
     exceptions::{UserTgIdAlreadyExists, UserTgIdNotExist},
 };
 
@@ -12,4 +18,16 @@ pub trait UserRepo {
     async fn create(&mut self, user: Create) -> Result<(), RepoKind<UserTgIdAlreadyExists>>;
 
     async fn get_by_tg_id(&mut self, user: GetByTgID) -> Result<User, RepoKind<UserTgIdNotExist>>;
+
+    async fn set_banned_col(
+        &mut self,
+        user: SetBannedCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>>;
+
+    async fn set_active_col(
+        &mut self,
+        user: SetActiveCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>>;
+
+    async fn count(&mut self) -> Result<i64, RepoError>;
 }
@@ -0,0 +1,141 @@
+//! Requests to Bot API, which respect limits of Telegram.
+//! Handlers send methods with [`BotApi::send_limited`] instead of sleeping between requests by hand.
+
+use std::{sync::OnceLock, time::Duration};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use telers::{
+    errors::{session::ErrorKind, TelegramErrorKind},
+    methods::TelegramMethod,
+    Bot,
+};
+use tracing::{field::Empty, info_span, warn, Instrument as _};
+
+use crate::config::RateLimitConfig;
+
+mod rate_limit;
+
+pub use rate_limit::RateLimiter;
+
+static RATE_LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+/// Set limits of requests from config. Limits by default are used, if it isn't called.
+pub fn init_rate_limiter(config: &RateLimitConfig) {
+    if RATE_LIMITER.set(RateLimiter::new(config)).is_err() {
+        warn!("Rate limiter is already initialized");
+    }
+}
+
+fn rate_limiter() -> &'static RateLimiter {
+    RATE_LIMITER.get_or_init(|| RateLimiter::new(&RateLimitConfig::default()))
+}
+
+/// Return name of the method without path, for example `AddStickerToSet`
+fn method_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Chat, which the method is sent into (or user, whose sticker set is changed)
+fn target<T: Serialize>(method: &T) -> Option<i64> {
+    let params = serde_json::to_value(method).ok()?;
+
+    params["chat_id"]
+        .as_i64()
+        .or_else(|| params["user_id"].as_i64())
+}
+
+#[async_trait]
+pub trait BotApi {
+    /// Send method to Bot API, when limits of the chat and the bot allow it.
+    /// If Telegram answers `Too Many Requests`, wait as much as it asks and retry.
+    /// Request is sent inside `bot_api` span, so time of every request is seen in traces.
+    async fn send_limited<T>(&self, method: T) -> Result<T::Return, ErrorKind>
+    where
+        T: TelegramMethod + Serialize + Clone + Send + Sync,
+        T::Method: Send + Sync;
+}
+
+#[async_trait]
+impl BotApi for Bot {
+    async fn send_limited<T>(&self, method: T) -> Result<T::Return, ErrorKind>
+    where
+        T: TelegramMethod + Serialize + Clone + Send + Sync,
+        T::Method: Send + Sync,
+    {
+        let span = info_span!(
+            "bot_api",
+            otel.kind = "client",
+            otel.status_code = Empty,
+            method = method_name::<T>(),
+            retries = Empty,
+            error = Empty,
+        );
+
+        let limiter = rate_limiter();
+        let chat_id = target(&method);
+        let mut retries = 0;
+
+        let result = loop {
+            limiter.acquire(chat_id).instrument(span.clone()).await;
+
+            match self.send(method.clone()).instrument(span.clone()).await {
+                Err(ErrorKind::Telegram(TelegramErrorKind::RetryAfter { retry_after, .. }))
+                    if retries < limiter.max_retries() =>
+                {
+                    retries += 1;
+
+                    let retry_after = Duration::from_secs_f64(retry_after as f64);
+                    limiter.retry_after(chat_id, retry_after);
+
+                    warn!(
+                        parent: &span,
+                        ?retry_after,
+                        retries,
+                        "Telegram asked to wait before the next request"
+                    );
+                }
+                result => break result,
+            }
+        };
+
+        if retries > 0 {
+            span.record("retries", retries);
+        }
+
+        if let Err(err) = &result {
+            span.record("otel.status_code", "ERROR");
+            span.record("error", err.to_string());
+        }
+
+        result
+    }
+}
+
+#[test]
+fn method_name_test() {
+    use telers::methods::{AddStickerToSet, SendMessage};
+
+    assert_eq!(method_name::<AddStickerToSet>(), "AddStickerToSet");
+
+    assert_eq!(target(&SendMessage::new(7, "text")), Some(7));
+    assert_eq!(target(&SendMessage::new("@channel", "text")), None);
+}
+
+#[tokio::test]
+async fn send_limited_test() {
+    use telers::methods::SendMessage;
+
+    use crate::fake_bot_api::FakeBotApi;
+
+    let api = FakeBotApi::start().await;
+    api.flood("sendMessage", 1);
+
+    // the first request is answered with `Too Many Requests`, and the second one is sent after pause
+    let result = api.bot().send_limited(SendMessage::new(1, "text")).await;
+
+    assert!(result.is_ok());
+    assert_eq!(api.sent_texts(), ["text", "text"]);
+}
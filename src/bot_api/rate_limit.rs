@@ -0,0 +1,188 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::config::RateLimitConfig;
+
+/// Number of chats, after which buckets of idle chats are removed
+const MAX_CHAT_BUCKETS: usize = 10_000;
+
+/// Bucket, which is refilled with `rate` tokens per second up to `capacity`.
+/// Token is reserved even if bucket is empty, so requests wait in the order of arrival.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, rate: f64, now: Instant) -> Self {
+        Self {
+            capacity,
+            rate,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Reserve one token and return time, after which it can be used
+    fn reserve(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    /// Don't give tokens during `pause` (in addition to already reserved ones)
+    fn pause(&mut self, now: Instant, pause: Duration) {
+        self.refill(now);
+
+        self.tokens = self.tokens.min(0.0) - pause.as_secs_f64() * self.rate;
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+
+        self.tokens >= self.capacity
+    }
+}
+
+/// Limits of requests to Bot API: the global one and the one of every chat
+#[derive(Debug)]
+pub struct RateLimiter {
+    chat_rate: f64,
+    chat_burst: f64,
+    max_retries: u32,
+    global: Mutex<TokenBucket>,
+    chats: Mutex<HashMap<i64, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            chat_rate: config.chat_rate,
+            chat_burst: config.chat_burst.into(),
+            max_retries: config.max_retries,
+            global: Mutex::new(TokenBucket::new(
+                config.global_rate,
+                config.global_rate,
+                Instant::now(),
+            )),
+            chats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of retries of the request, after which Telegram answered `Too Many Requests`
+    pub const fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Wait until request into the chat is allowed.
+    /// Requests without chat are limited only by the global limit.
+    pub async fn acquire(&self, chat_id: Option<i64>) {
+        let delay = self.reserve(chat_id, Instant::now());
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn reserve(&self, chat_id: Option<i64>, now: Instant) -> Duration {
+        let global = self
+            .global
+            .lock()
+            .expect("rate limiter lock poisoned")
+            .reserve(now);
+
+        let chat = match chat_id {
+            Some(chat_id) => self.chat_bucket(chat_id, now, |bucket| bucket.reserve(now)),
+            None => Duration::ZERO,
+        };
+
+        global.max(chat)
+    }
+
+    /// Telegram asked to wait before the next request into the chat
+    /// (or before any request, if chat isn't known)
+    pub fn retry_after(&self, chat_id: Option<i64>, retry_after: Duration) {
+        let now = Instant::now();
+
+        match chat_id {
+            Some(chat_id) => {
+                self.chat_bucket(chat_id, now, |bucket| bucket.pause(now, retry_after))
+            }
+            None => self
+                .global
+                .lock()
+                .expect("rate limiter lock poisoned")
+                .pause(now, retry_after),
+        }
+    }
+
+    fn chat_bucket<R>(
+        &self,
+        chat_id: i64,
+        now: Instant,
+        f: impl FnOnce(&mut TokenBucket) -> R,
+    ) -> R {
+        let mut chats = self.chats.lock().expect("rate limiter lock poisoned");
+
+        if chats.len() >= MAX_CHAT_BUCKETS {
+            chats.retain(|_, bucket| !bucket.is_full(now));
+        }
+
+        let bucket = chats
+            .entry(chat_id)
+            .or_insert_with(|| TokenBucket::new(self.chat_burst, self.chat_rate, now));
+
+        f(bucket)
+    }
+}
+
+#[test]
+fn rate_limiter_test() {
+    let limiter = RateLimiter::new(&RateLimitConfig {
+        global_rate: 30.0,
+        chat_rate: 1.0,
+        chat_burst: 3,
+        max_retries: 3,
+    });
+    let now = Instant::now();
+
+    // burst of the chat
+    for _ in 0..3 {
+        assert_eq!(limiter.reserve(Some(1), now), Duration::ZERO);
+    }
+    assert_eq!(limiter.reserve(Some(1), now), Duration::from_secs(1));
+    assert_eq!(limiter.reserve(Some(1), now), Duration::from_secs(2));
+
+    // another chat isn't affected
+    assert_eq!(limiter.reserve(Some(2), now), Duration::ZERO);
+
+    // 6 requests are reserved above, so only 24 are left in the global bucket
+    for _ in 0..24 {
+        assert_eq!(limiter.reserve(None, now), Duration::ZERO);
+    }
+    assert!(limiter.reserve(None, now) > Duration::ZERO);
+
+    // buckets are refilled
+    let later = now + Duration::from_secs(10);
+    assert_eq!(limiter.reserve(Some(1), later), Duration::ZERO);
+
+    limiter.retry_after(Some(1), Duration::from_secs(5));
+    assert!(limiter.reserve(Some(1), Instant::now()) >= Duration::from_secs(5));
+}
@@ -3,6 +3,7 @@ mod handlers;
 mod states;
 
 pub use commands::{
-    add_stickers_command, cancel_command, my_stickers, process_non_command, process_non_sticker,
-    source_command, start_command, steal_sticker_set_command,
+    add_stickers_command, admin_commands, cancel_command, download_command, export_command,
+    from_archive_command, import_command, my_stickers, process_non_command, process_non_document,
+    process_non_sticker, protect_command, source_command, start_command, steal_sticker_set_command,
 };
@@ -1,6 +1,10 @@
 use crate::{
     application::{set::traits::SetRepo, user::traits::UserRepo},
-    bot_commands::states::{AddStickerState, MyStickersState, StealStickerSetState},
+    bot_commands::states::{
+        AddStickerState, DownloadState, FromArchiveState, ImportState, MyStickersState,
+        ProtectState, StealStickerSetState,
+    },
+    filters::Admin,
     infrastructure::database::{
         repositories::{set::SetRepoImpl, user::UserRepoImpl},
         uow::UoWFactory,
@@ -11,121 +15,195 @@ use telers::{
     client::Reqwest,
     enums::{ChatType as ChatTypeEnum, ContentType as ContentTypeEnum},
     filters::{ChatType, Command, ContentType, State as StateFilter},
-    fsm::MemoryStorage,
+    fsm::Storage,
     Filter as _, Router,
 };
 
 use super::handlers::{
-    add_stickers_handler, add_stickers_to_user_owned_sticker_set, cancel_handler,
-    create_new_sticker_set, get_sticker_set_name, get_stickers_to_add, get_stolen_sticker_set,
-    my_stickers_handler, process_button, process_non_sticker as process_non_sticker_handler,
-    source_handler, start_handler, steal_sticker_set_handler,
+    add_stickers_handler, add_stickers_to_user_owned_sticker_set, ban_handler, block_handler,
+    broadcast_handler, cancel_handler, create_new_sticker_set, download_handler, export_handler,
+    from_archive_handler, get_archive, get_import_file, get_new_sticker_set_title,
+    get_sticker_set_name, get_sticker_set_to_download, get_sticker_to_protect, get_stickers_to_add,
+    get_stolen_sticker_set, import_handler, my_stickers_handler, process_broadcast_button,
+    process_button, process_non_document as process_non_document_handler,
+    process_non_sticker as process_non_sticker_handler, process_protect_button,
+    protect_my_packs_handler, source_handler, start_handler, stats_handler,
+    steal_sticker_set_handler, unban_handler, unblock_handler, unprotect_handler, user_handler,
 };
 
 /// If the user simply writes to the bot without calling any commands, the bot will call specified function
-pub async fn process_non_command(router: &mut Router<Reqwest>, ignore_commands: &'static [&str]) {
+pub async fn process_non_command<S>(router: &mut Router<Reqwest>, ignore_commands: &'static [&str])
+where
+    S: Storage + Send + Sync + 'static,
+{
     router
         .message
         .filter(ChatType::one(ChatTypeEnum::Private))
-        .register(start_handler::<MemoryStorage>)
+        .register(start_handler::<S>)
         .filter(StateFilter::none())
         .filter(Command::many(ignore_commands.iter().map(ToOwned::to_owned)).invert());
 }
 
 /// Executes Telegram commands `/start` and `/help`
-pub async fn start_command(router: &mut Router<Reqwest>, commands: &'static [&str]) {
+pub async fn start_command<S>(router: &mut Router<Reqwest>, commands: &'static [&str])
+where
+    S: Storage + Send + Sync + 'static,
+{
     router
         .message
-        .register(start_handler::<MemoryStorage>)
+        .register(start_handler::<S>)
         .filter(ChatType::one(ChatTypeEnum::Private))
         .filter(Command::many(commands.iter().map(ToOwned::to_owned)));
 }
 
 /// Executes Telegram commands `/src` and `/source`
-pub async fn source_command(router: &mut Router<Reqwest>, commands: &'static [&str]) {
+pub async fn source_command<S>(router: &mut Router<Reqwest>, commands: &'static [&str])
+where
+    S: Storage + Send + Sync + 'static,
+{
     router
         .message
-        .register(source_handler::<MemoryStorage>)
+        .register(source_handler::<S>)
         .filter(ChatType::one(ChatTypeEnum::Private))
         .filter(Command::many(commands.iter().map(ToOwned::to_owned)));
 }
 
 /// Executes Telegram command `/cancel`
-pub async fn cancel_command(router: &mut Router<Reqwest>, commands: &'static [&str]) {
+pub async fn cancel_command<S>(router: &mut Router<Reqwest>, commands: &'static [&str])
+where
+    S: Storage + Send + Sync + 'static,
+{
     router
         .message
-        .register(cancel_handler::<MemoryStorage>)
+        .register(cancel_handler::<S>)
         .filter(ChatType::one(ChatTypeEnum::Private))
         .filter(Command::many(commands.iter().map(ToOwned::to_owned)));
 }
 
 /// Executes Telegram command `/add_stickers`
-pub async fn add_stickers_command<DB>(
+pub async fn add_stickers_command<S, DB>(
     router: &mut Router<Reqwest>,
     command: &'static str,
     done_command: &'static str,
 ) where
+    S: Storage + Send + Sync + 'static,
     DB: Database,
     for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
     for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
 {
     router
         .message
-        .register(add_stickers_handler::<MemoryStorage>)
+        .register(add_stickers_handler::<S>)
         .filter(ChatType::one(ChatTypeEnum::Private))
         .filter(Command::one(command))
         .filter(ContentType::one(ContentTypeEnum::Text));
 
     router
         .message
-        .register(get_stolen_sticker_set::<MemoryStorage, UoWFactory<DB>>)
+        .register(get_stolen_sticker_set::<S, UoWFactory<DB>>)
         .filter(ContentType::one(ContentTypeEnum::Sticker))
         .filter(StateFilter::one(AddStickerState::GetStolenStickerSet));
 
     router
         .message
-        .register(get_stickers_to_add::<MemoryStorage, UoWFactory<DB>>)
+        .register(get_stickers_to_add::<S, UoWFactory<DB>>)
         .filter(ContentType::one(ContentTypeEnum::Sticker))
         .filter(StateFilter::one(AddStickerState::GetStickersToAdd));
 
     router
         .message
-        .register(add_stickers_to_user_owned_sticker_set::<MemoryStorage>)
+        .register(add_stickers_to_user_owned_sticker_set::<S>)
         .filter(Command::one(done_command))
         .filter(ContentType::one(ContentTypeEnum::Text))
         .filter(StateFilter::one(AddStickerState::GetStickersToAdd));
 }
 
 /// Executes Telegram command `/steal_pack`
-pub async fn steal_sticker_set_command<DB>(router: &mut Router<Reqwest>, command: &'static str)
+pub async fn steal_sticker_set_command<S, DB>(router: &mut Router<Reqwest>, command: &'static str)
 where
+    S: Storage + Send + Sync + 'static,
     DB: Database,
     for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
     for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
 {
     router
         .message
-        .register(steal_sticker_set_handler::<MemoryStorage>)
+        .register(steal_sticker_set_handler::<S>)
         .filter(ChatType::one(ChatTypeEnum::Private))
         .filter(Command::one(command))
         .filter(ContentType::one(ContentTypeEnum::Text));
 
     router
         .message
-        .register(get_sticker_set_name::<MemoryStorage>)
+        .register(get_sticker_set_name::<S>)
         .filter(ContentType::one(ContentTypeEnum::Sticker))
         .filter(StateFilter::one(StealStickerSetState::StealStickerSetName));
 
     router
         .message
-        .register(create_new_sticker_set::<MemoryStorage, UoWFactory<DB>>)
+        .register(create_new_sticker_set::<S, UoWFactory<DB>>)
         .filter(ContentType::one(ContentTypeEnum::Text))
         .filter(StateFilter::one(StealStickerSetState::CreateNewStickerSet));
 }
 
+/// Commands of the admins: `/stats`, `/user`, `/ban`, `/unban`, `/block`, `/unblock` and `/broadcast`.
+/// Updates of other users aren't handled by the router, so they are processed by the next routers.
+pub async fn admin_commands<DB>(router: &mut Router<Reqwest>, admin: Admin)
+where
+    DB: Database,
+    for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
+    for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
+{
+    router
+        .message
+        .filter(admin.clone())
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .filter(ContentType::one(ContentTypeEnum::Text));
+
+    router
+        .message
+        .register(stats_handler::<UoWFactory<DB>>)
+        .filter(Command::one("stats"));
+
+    router
+        .message
+        .register(user_handler::<UoWFactory<DB>>)
+        .filter(Command::one("user"));
+
+    router
+        .message
+        .register(ban_handler::<UoWFactory<DB>>)
+        .filter(Command::one("ban"));
+
+    router
+        .message
+        .register(unban_handler::<UoWFactory<DB>>)
+        .filter(Command::one("unban"));
+
+    router
+        .message
+        .register(block_handler)
+        .filter(Command::one("block"));
+
+    router
+        .message
+        .register(unblock_handler)
+        .filter(Command::one("unblock"));
+
+    router
+        .message
+        .register(broadcast_handler)
+        .filter(Command::one("broadcast"));
+
+    router.callback_query.filter(admin);
+
+    router.callback_query.register(process_broadcast_button);
+}
+
 /// Show all user stolen sticker sets
-pub async fn my_stickers<DB>(router: &mut Router<Reqwest>, command: &'static str)
+pub async fn my_stickers<S, DB>(router: &mut Router<Reqwest>, command: &'static str)
 where
+    S: Storage + Send + Sync + 'static,
     DB: Database,
     for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
     for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
@@ -133,18 +211,137 @@ where
     router
         .message
         .filter(ChatType::one(ChatTypeEnum::Private))
-        .register(my_stickers_handler::<MemoryStorage, UoWFactory<DB>>)
+        .register(my_stickers_handler::<S, UoWFactory<DB>>)
         .filter(Command::one(command))
         .filter(ContentType::one(ContentTypeEnum::Text));
 
     router
         .callback_query
-        .register(process_button::<MemoryStorage, UoWFactory<DB>>)
+        .register(process_button::<S, UoWFactory<DB>>)
         .filter(StateFilter::one(
             MyStickersState::EditStickerSetsListMessage,
         ));
 }
 
+/// Export all user stolen sticker sets into JSON or CSV file
+pub async fn export_command<S, DB>(router: &mut Router<Reqwest>, command: &'static str)
+where
+    S: Storage + Send + Sync + 'static,
+    DB: Database,
+    for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
+    for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
+{
+    router
+        .message
+        .register(export_handler::<S, UoWFactory<DB>>)
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .filter(Command::one(command))
+        .filter(ContentType::one(ContentTypeEnum::Text));
+}
+
+/// Import sticker sets from JSON file, which was exported using `/export` command
+pub async fn import_command<S, DB>(router: &mut Router<Reqwest>, command: &'static str)
+where
+    S: Storage + Send + Sync + 'static,
+    DB: Database,
+    for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
+    for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
+{
+    router
+        .message
+        .register(import_handler::<S>)
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .filter(Command::one(command))
+        .filter(ContentType::one(ContentTypeEnum::Text));
+
+    router
+        .message
+        .register(get_import_file::<S, UoWFactory<DB>>)
+        .filter(ContentType::one(ContentTypeEnum::Document))
+        .filter(StateFilter::one(ImportState::GetImportFile));
+}
+
+/// Download sticker set as zip archive
+pub async fn download_command<S>(router: &mut Router<Reqwest>, command: &'static str)
+where
+    S: Storage + Send + Sync + 'static,
+{
+    router
+        .message
+        .register(download_handler::<S>)
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .filter(Command::one(command))
+        .filter(ContentType::one(ContentTypeEnum::Text));
+
+    router
+        .message
+        .register(get_sticker_set_to_download::<S>)
+        .filter(ContentType::one(ContentTypeEnum::Sticker))
+        .filter(StateFilter::one(DownloadState::GetStickerSetToDownload));
+}
+
+/// Create new sticker set from zip archive with sticker files
+pub async fn from_archive_command<S, DB>(router: &mut Router<Reqwest>, command: &'static str)
+where
+    S: Storage + Send + Sync + 'static,
+    DB: Database,
+    for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
+    for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
+{
+    router
+        .message
+        .register(from_archive_handler::<S>)
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .filter(Command::one(command))
+        .filter(ContentType::one(ContentTypeEnum::Text));
+
+    router
+        .message
+        .register(get_new_sticker_set_title::<S>)
+        .filter(ContentType::one(ContentTypeEnum::Text))
+        .filter(StateFilter::one(FromArchiveState::GetNewStickerSetTitle));
+
+    router
+        .message
+        .register(get_archive::<S, UoWFactory<DB>>)
+        .filter(ContentType::one(ContentTypeEnum::Document))
+        .filter(StateFilter::one(FromArchiveState::GetArchive));
+}
+
+/// Protect sticker packs of the creator from stealing and remove the protection
+pub async fn protect_command<S>(
+    router: &mut Router<Reqwest>,
+    command: &'static str,
+    unprotect_command: &'static str,
+) where
+    S: Storage + Send + Sync + 'static,
+{
+    router
+        .message
+        .register(protect_my_packs_handler::<S>)
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .filter(Command::one(command))
+        .filter(ContentType::one(ContentTypeEnum::Text));
+
+    router
+        .message
+        .register(unprotect_handler::<S>)
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .filter(Command::one(unprotect_command))
+        .filter(ContentType::one(ContentTypeEnum::Text));
+
+    router
+        .message
+        .register(get_sticker_to_protect::<S>)
+        .filter(ContentType::one(ContentTypeEnum::Sticker))
+        .filter(StateFilter::one(ProtectState::GetStickerToProtect));
+
+    router
+        .callback_query
+        .register(process_protect_button::<S>)
+        .filter(StateFilter::one(ProtectState::ChooseProtectedSets));
+}
+
 /// If user enter wrong content type, but the request type is <content_type>, this handler will process it
 pub async fn process_non_sticker(router: &mut Router<Reqwest>, content_type: ContentTypeEnum) {
     router
@@ -153,9 +350,25 @@ pub async fn process_non_sticker(router: &mut Router<Reqwest>, content_type: Con
         .register(process_non_sticker_handler)
         .filter(ContentType::one(content_type).invert())
         .filter(
-            StateFilter::one(StealStickerSetState::StealStickerSetName).or(StateFilter::many([
-                AddStickerState::GetStolenStickerSet,
-                AddStickerState::GetStickersToAdd,
-            ])),
+            StateFilter::one(StealStickerSetState::StealStickerSetName)
+                .or(StateFilter::many([
+                    AddStickerState::GetStolenStickerSet,
+                    AddStickerState::GetStickersToAdd,
+                ]))
+                .or(StateFilter::one(DownloadState::GetStickerSetToDownload))
+                .or(StateFilter::one(ProtectState::GetStickerToProtect)),
+        );
+}
+
+/// If user enter wrong content type, but the request type is document, this handler will process it
+pub async fn process_non_document(router: &mut Router<Reqwest>) {
+    router
+        .message
+        .filter(ChatType::one(ChatTypeEnum::Private))
+        .register(process_non_document_handler)
+        .filter(ContentType::one(ContentTypeEnum::Document).invert())
+        .filter(
+            StateFilter::one(ImportState::GetImportFile)
+                .or(StateFilter::one(FromArchiveState::GetArchive)),
         );
 }
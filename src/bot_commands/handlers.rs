@@ -1,8 +1,14 @@
 // export modules
 pub mod add_stickers;
+pub mod admin;
 pub mod cancel;
 pub mod common;
+pub mod download;
+pub mod export;
+pub mod from_archive;
+pub mod import;
 pub mod my_stickers;
+pub mod protect;
 pub mod source;
 pub mod start;
 pub mod steal_sticker_set;
@@ -12,9 +18,20 @@ pub use add_stickers::{
     add_stickers_handler, add_stickers_to_user_owned_sticker_set, get_stickers_to_add,
     get_stolen_sticker_set,
 };
+pub use admin::{
+    ban_handler, block_handler, broadcast_handler, process_broadcast_button, stats_handler,
+    unban_handler, unblock_handler, user_handler,
+};
 pub use cancel::cancel_handler;
-pub use common::{add_stickers, process_non_sticker};
+pub use common::{add_stickers, process_non_document, process_non_sticker};
+pub use download::{download_handler, get_sticker_set_to_download};
+pub use export::export_handler;
+pub use from_archive::{from_archive_handler, get_archive, get_new_sticker_set_title};
+pub use import::{get_import_file, import_handler};
 pub use my_stickers::{my_stickers_handler, process_button};
+pub use protect::{
+    get_sticker_to_protect, process_protect_button, protect_my_packs_handler, unprotect_handler,
+};
 pub use source::source_handler;
 pub use start::start_handler;
 pub use steal_sticker_set::{
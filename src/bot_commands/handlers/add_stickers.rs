@@ -18,10 +18,20 @@ use crate::{
         commands::create_set::create_set, common::traits::uow::UoWFactory as UoWFactoryTrait,
         set::dto::create::Create as CreateSet,
     },
-    bot_commands::{handlers::add_stickers, states::AddStickerState},
+    bot_api::BotApi as _,
+    bot_commands::{
+        handlers::{
+            add_stickers,
+            common::{
+                check_not_blocked, input_sticker, resolve_owner, start_operation, take_quota,
+                AddedStickers,
+            },
+        },
+        states::AddStickerState,
+    },
     core::{common::set_created_by, stickers::constants::MAX_STICKER_SET_LENGTH},
-    middlewares::Client,
-    telegram_application::get_sticker_set_user_id,
+    logging::handler_called,
+    middlewares::{Blocklist, Client, QuotaKind, Quotas},
 };
 
 pub async fn add_stickers_handler<S: Storage>(
@@ -29,13 +39,15 @@ pub async fn add_stickers_handler<S: Storage>(
     message: MessageText,
     fsm: Context<S>,
 ) -> HandlerResult {
+    handler_called("add_stickers_handler");
+
     fsm.finish().await.map_err(Into::into)?;
 
     fsm.set_state(AddStickerState::GetStolenStickerSet)
         .await
         .map_err(Into::into)?;
 
-    bot.send(SendMessage::new(
+    bot.send_limited(SendMessage::new(
             message.chat.id(),
             format!("Send me {your} sticker pack, in which you want to add stickers. You can see all your \
             stolen stickers, using command /mystickers (if you don't have the sticker packs stolen by this bot, first use the command /stealpack).",
@@ -57,12 +69,14 @@ where
     UoWFactory: UoWFactoryTrait,
     S: Storage,
 {
+    handler_called("get_stolen_sticker_set");
+
     let mut uow = uow_factory.create_uow();
 
     let sticker_set_name = match message.sticker.set_name {
         Some(sticker_set_name) => sticker_set_name,
         None => {
-            bot.send(SendMessage::new(
+            bot.send_limited(SendMessage::new(
                 message.chat.id(),
                 "This sticker is without sticker pack! Try to send another sticker pack.",
             ))
@@ -73,7 +87,7 @@ where
     };
 
     let sticker_set = match bot
-        .send(GetStickerSet::new(sticker_set_name.as_ref()))
+        .send_limited(GetStickerSet::new(sticker_set_name.as_ref()))
         .await
     {
         Ok(set) => set,
@@ -83,7 +97,7 @@ where
                 "error occurded while getting sticker set to add stickers into it:"
             );
 
-            bot.send(SendMessage::new(
+            bot.send_limited(SendMessage::new(
                 message.chat.id(),
                 "Sorry, an erorr occurded. Try send this sticker again :(",
             ))
@@ -96,13 +110,13 @@ where
     let sticker_set_title = sticker_set.title;
 
     let bot_username = bot
-        .send(GetMe::new())
+        .send_limited(GetMe::new())
         .await?
         .username
         .expect("bot without username :/");
 
     if !set_created_by(sticker_set_name.as_ref(), bot_username.as_ref()) {
-        bot.send(SendMessage::new(
+        bot.send_limited(SendMessage::new(
             message.chat.id(),
             "This sticker pack wasnt stolen by this bot, which means i cant add stickers to it according to Telegram rules! \
             You can see your stolen sticker pack using command /mystickers or steal this sticker pack using command /stealpack.",
@@ -112,50 +126,16 @@ where
         return Ok(EventReturn::Finish);
     }
 
-    // if function doesnt execute in 3 second, send error message
-    let steal_set_user_id = match tokio::time::timeout(Duration::from_secs(10), async {
-        let mut error_count: u32 = 0;
-
-        loop {
-            match get_sticker_set_user_id(sticker_set_name.as_ref(), &client).await {
-                Ok(set_id) => return Ok(set_id),
-                Err(err) if error_count >= 5 => return Err(err),
-                Err(err) => {
-                    error!(
-                        ?err,
-                        "error occurded while trying to get sticker set user id:"
-                    );
-
-                    error_count += 1;
-                }
-            }
-        }
-    })
-    .await
-    {
-        Ok(Ok(set_id)) => set_id,
-        Ok(Err(err)) => {
-            error!(%err, "failed to get sticker set user id:");
-
-            bot.send(
-                SendMessage::new(message.chat.id(), "Sorry, an error occurded. Try again :(")
-                    .reply_parameters(ReplyParameters::new(message.id).chat_id(message.chat.id())),
-            )
-            .await?;
-
-            return Ok(EventReturn::Finish);
-        }
-        Err(err) => {
-            error!(%err, "too long time to get sticker set user id:");
-
-            bot.send(
-                SendMessage::new(message.chat.id(), "Sorry, an error occurded. Try again :(")
-                    .reply_parameters(ReplyParameters::new(message.id).chat_id(message.chat.id())),
-            )
-            .await?;
-
-            return Ok(EventReturn::Finish);
-        }
+    let Some(steal_set_user_id) = resolve_owner(
+        &bot,
+        &client,
+        message.chat.id(),
+        message.id,
+        sticker_set_name.as_ref(),
+    )
+    .await?
+    else {
+        return Ok(EventReturn::Finish);
     };
 
     create_set(
@@ -173,7 +153,7 @@ where
     let user_id = message.from.expect("user not specified").id;
 
     if user_id != steal_set_user_id {
-        bot.send(
+        bot.send_limited(
             SendMessage::new(
                 message.chat.id(),
                 format!(
@@ -190,13 +170,13 @@ where
     }
 
     let set_length = bot
-        .send(GetStickerSet::new(sticker_set_name.as_ref()))
+        .send_limited(GetStickerSet::new(sticker_set_name.as_ref()))
         .await?
         .stickers
         .len();
 
     let message_delete = if MAX_STICKER_SET_LENGTH - set_length > 0 {
-        bot.send(SendMessage::new(
+        bot.send_limited(SendMessage::new(
                 message.chat.id(),
                 format!("Total length of this sticker pack = {set_length}. This means you can add a maximum of {} stickers, \
                 otherwise you will get error because the maximum size of a sticker pack in current time = {MAX_STICKER_SET_LENGTH} stickers.",
@@ -204,7 +184,7 @@ where
             ).reply_parameters(ReplyParameters::new(message.id).chat_id(message.chat.id())))
             .await?
     } else {
-        bot.send(SendMessage::new(
+        bot.send_limited(SendMessage::new(
                 message.chat.id(),
                 format!("Sorry, but this sticker pack contains {MAX_STICKER_SET_LENGTH} stickers! :(\n\
                 You cant add more stickers, because the maximum size of a sticker pack in current time = {MAX_STICKER_SET_LENGTH} \
@@ -226,7 +206,7 @@ where
         .await
         .map_err(Into::into)?;
 
-    bot.send(SendMessage::new(
+    bot.send_limited(SendMessage::new(
         message.chat.id(),
         "Now send me stickers you want to add in stolen sticker pack. \
         When youre ready, use /done command (or /cancel, if you want to cancel the last command).",
@@ -235,7 +215,7 @@ where
 
     // delete unnecessary message after 15 sec
     tokio::time::sleep(Duration::from_secs(15)).await;
-    bot.send(DeleteMessage::new(
+    bot.send_limited(DeleteMessage::new(
         message_delete.chat().id(),
         message_delete.id(),
     ))
@@ -250,11 +230,14 @@ pub async fn get_stickers_to_add<S, UoWFactory>(
     Client(client): Client,
     uow_factory: UoWFactory,
     fsm: Context<S>,
+    blocklist: Blocklist,
 ) -> HandlerResult
 where
     UoWFactory: UoWFactoryTrait,
     S: Storage,
 {
+    handler_called("get_stickers_to_add");
+
     let mut uow = uow_factory.create_uow();
 
     let (_, _, sticker_set_length): (Box<str>, Box<str>, usize) = fsm
@@ -274,7 +257,7 @@ where
     };
 
     if sticker_to_add.emoji.is_none() {
-        bot.send(
+        bot.send_limited(
             SendMessage::new(
                 message.chat.id(),
                 "Sorry, but this sticker is without emoji. Try send another sticker.",
@@ -287,62 +270,37 @@ where
     }
 
     if set_can_created {
-        // if function doesnt execute in 3 second, send error message
-        let sticker_set_owner_id = match tokio::time::timeout(Duration::from_secs(10), async {
-            let mut error_count: u32 = 0;
-
-            loop {
-                match get_sticker_set_user_id(sticker_to_add_set_name, &client).await {
-                    Ok(set_id) => return Ok(set_id),
-                    Err(err) if error_count >= 5 => return Err(err),
-                    Err(err) => {
-                        error!(
-                            ?err,
-                            "error occurded while trying to get sticker set user id:"
-                        );
-
-                        error_count += 1;
-                    }
-                }
-            }
-        })
-        .await
-        {
-            Ok(Ok(set_id)) => set_id,
-            Ok(Err(err)) => {
-                error!(%err, "failed to get sticker set user id:");
-
-                bot.send(
-                    SendMessage::new(message.chat.id(), "Sorry, an error occurded. Try again :(")
-                        .reply_parameters(
-                            ReplyParameters::new(message.id).chat_id(message.chat.id()),
-                        ),
-                )
-                .await?;
+        let Some(sticker_set_owner_id) = resolve_owner(
+            &bot,
+            &client,
+            message.chat.id(),
+            message.id,
+            sticker_to_add_set_name,
+        )
+        .await?
+        else {
+            return Ok(EventReturn::Finish);
+        };
 
-                return Ok(EventReturn::Finish);
-            }
-            Err(err) => {
-                error!(%err, "too long time to get sticker set user id:");
-
-                bot.send(
-                    SendMessage::new(message.chat.id(), "Sorry, an error occurded. Try again :(")
-                        .reply_parameters(
-                            ReplyParameters::new(message.id).chat_id(message.chat.id()),
-                        ),
-                )
-                .await?;
+        if !check_not_blocked(
+            &bot,
+            &blocklist,
+            message.chat.id(),
+            sticker_to_add_set_name,
+            Some(sticker_set_owner_id),
+        )
+        .await?
+        {
+            return Ok(EventReturn::Finish);
+        }
 
-                return Ok(EventReturn::Finish);
-            }
-        };
         let sticker_to_add_title = &bot
-            .send(GetStickerSet::new(sticker_to_add_set_name))
+            .send_limited(GetStickerSet::new(sticker_to_add_set_name))
             .await?
             .title;
 
         let bot_username = bot
-            .send(GetMe::new())
+            .send_limited(GetMe::new())
             .await?
             .username
             .expect("bot without username :/");
@@ -372,7 +330,7 @@ where
             let sticker_vec_len = sticker_vec.len();
 
             if sticker_set_length + sticker_vec_len >= MAX_STICKER_SET_LENGTH {
-                bot.send(SendMessage::new(
+                bot.send_limited(SendMessage::new(
                     message.chat.id(),
                     format!("Please, use command /done to add stickers (or /cancel if for some reason you change your \
                     mind about adding them), because the sum of the current stickers in the sticker pack \
@@ -395,7 +353,7 @@ where
         .await
         .map_err(Into::into)?;
 
-    bot.send(
+    bot.send_limited(
         SendMessage::new(
             message.chat.id(),
             "Sticker processed! Send the next one, or use the /done command if you're ready.",
@@ -413,7 +371,10 @@ pub async fn add_stickers_to_user_owned_sticker_set<S: Storage>(
     bot: Bot,
     message: MessageText,
     fsm: Context<S>,
+    quotas: Quotas,
 ) -> HandlerResult {
+    handler_called("add_stickers_to_user_owned_sticker_set");
+
     let (sticker_set_name, sticker_set_title, _): (Box<str>, Box<str>, usize) = fsm
         .get_value("get_stolen_sticker_set")
         .await
@@ -428,7 +389,7 @@ pub async fn add_stickers_to_user_owned_sticker_set<S: Storage>(
     {
         Some(sticker_vec) => sticker_vec,
         None => {
-            bot.send(SendMessage::new(
+            bot.send_limited(SendMessage::new(
                 message.chat.id(),
                 "You haven't sent a single sticker! Send the stickers, and only then use the /done command.",
             ))
@@ -443,25 +404,48 @@ pub async fn add_stickers_to_user_owned_sticker_set<S: Storage>(
     // only panic if messages uses in channels, but i'm using private filter in main function
     let user_id = message.from.expect("error while parsing user").id;
 
+    let usage = [(QuotaKind::Sticker, stickers_to_add_vec.len() as u32)];
+
+    if !take_quota(&bot, &quotas, message.chat.id(), user_id, &usage).await? {
+        return Ok(EventReturn::Finish);
+    }
+
+    let Some(_operation) =
+        start_operation(&bot, &quotas, message.chat.id(), user_id, &usage).await?
+    else {
+        return Ok(EventReturn::Finish);
+    };
+
     let message_delete = bot
-        .send(SendMessage::new(
+        .send_limited(SendMessage::new(
             message.chat.id(),
             "Done! Trying to add that sticker(s) to your sticker pack..\n\
         (if you have sent a lot of stickers, it may take up to a few minutes to add them)",
         ))
         .await?;
 
-    let all_stickers_was_added = add_stickers(
+    let added = add_stickers(
         &bot,
         user_id,
         sticker_set_name.as_ref(),
-        stickers_to_add_vec.as_ref(),
+        stickers_to_add_vec.iter().map(input_sticker).collect(),
     )
     .await
     .expect("empty stickers list");
 
-    if !all_stickers_was_added {
-        bot.send(
+    // the user is told about interrupted operation by shutdown
+    if added == AddedStickers::Cancelled {
+        bot.send_limited(DeleteMessage::new(
+            message_delete.chat().id(),
+            message_delete.id(),
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    if added == AddedStickers::Partially {
+        bot.send_limited(
             SendMessage::new(
                 message.chat.id(),
                 format!(
@@ -474,7 +458,7 @@ pub async fn add_stickers_to_user_owned_sticker_set<S: Storage>(
         .await?;
     }
 
-    bot.send(
+    bot.send_limited(
         SendMessage::new(
             message.chat.id(),
             format!(
@@ -490,7 +474,7 @@ pub async fn add_stickers_to_user_owned_sticker_set<S: Storage>(
     .await?;
 
     // delete unnecessary message
-    bot.send(DeleteMessage::new(
+    bot.send_limited(DeleteMessage::new(
         message_delete.chat().id(),
         message_delete.id(),
     ))
@@ -498,3 +482,61 @@ pub async fn add_stickers_to_user_owned_sticker_set<S: Storage>(
 
     Ok(EventReturn::Finish)
 }
+
+#[tokio::test]
+async fn add_stickers_to_user_owned_sticker_set_test() {
+    use serde_json::json;
+    use telers::fsm::StorageKey;
+
+    use crate::fake_bot_api::{message_update, sticker, sticker_set, TestBot, TEST_BOT_ID};
+
+    let test_bot = TestBot::new().await;
+
+    let set_name = "my_set_by_steal_stickers_test_bot";
+
+    test_bot.api.add_sticker_set(sticker_set(
+        set_name,
+        "My set",
+        vec![sticker(set_name, "file_id_0", "😀")],
+    ));
+
+    // user has already chosen sticker set and sent stickers to add
+    let key = StorageKey::new(TEST_BOT_ID, 1, 1);
+    let stickers_to_add: Vec<Sticker> =
+        vec![serde_json::from_value(sticker("other", "file_id_1", "😎")).unwrap()];
+
+    test_bot
+        .storage
+        .set_state(&key, AddStickerState::GetStickersToAdd)
+        .await
+        .unwrap();
+    test_bot
+        .storage
+        .set_value(&key, "get_stolen_sticker_set", (set_name, "My set", 1usize))
+        .await
+        .unwrap();
+    test_bot
+        .storage
+        .set_value(&key, "get_stickers_to_add", stickers_to_add)
+        .await
+        .unwrap();
+
+    test_bot
+        .feed_update(message_update(1, 1, json!({ "text": "/done" })))
+        .await;
+
+    let added = test_bot.api.calls_of("addStickerToSet");
+    assert_eq!(added.len(), 1);
+    assert_eq!(added[0]["user_id"], 1);
+    assert_eq!(added[0]["name"], set_name);
+
+    let stickers = &test_bot.api.sticker_set(set_name).unwrap()["stickers"];
+    assert_eq!(stickers.as_array().unwrap().len(), 2);
+    assert_eq!(stickers[1]["file_id"], "file_id_1");
+
+    assert!(test_bot
+        .api
+        .sent_texts()
+        .iter()
+        .any(|text| text.contains("was added into")));
+}
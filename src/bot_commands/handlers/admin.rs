@@ -0,0 +1,398 @@
+use telers::{
+    enums::ParseMode,
+    errors::HandlerError,
+    event::{telegram::HandlerResult, EventReturn},
+    methods::{AnswerCallbackQuery, CopyMessage, SendMessage},
+    types::{CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, MessageText, ReplyMarkup},
+    Bot,
+};
+
+use crate::{
+    application::{
+        commands::set_banned_col::set_banned_col,
+        common::{
+            exceptions::RepoKind,
+            traits::uow::{UoW as _, UoWFactory as UoWFactoryTrait},
+        },
+        set::{
+            dto::{count::Count, get_by_tg_id::GetByTgID as GetSetByTgID},
+            traits::SetRepo as _,
+        },
+        user::{
+            dto::{get_by_tg_id::GetByTgID as GetUserByTgID, set_banned_col::SetBannedCol},
+            traits::UserRepo as _,
+        },
+    },
+    bot_api::BotApi as _,
+    broadcast::Broadcasts,
+    infrastructure::database::blocked_sets::BlockedSet,
+    logging::handler_called,
+    middlewares::Blocklist,
+    texts::{stats_message, user_message},
+};
+
+/// Prefix of the data of the buttons, which confirm broadcast (`broadcast:{message_id}` or `broadcast:cancel`)
+const BROADCAST_DATA_PREFIX: &str = "broadcast:";
+
+/// ID of the user from the first argument of the command, like `/ban 123`
+fn user_id_arg(text: &str) -> Option<i64> {
+    text.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Sticker pack from the first argument of the command: ID of the owner, short name or link to the sticker pack
+fn blocked_set_arg(text: &str) -> Option<BlockedSet> {
+    let arg = text.split_whitespace().nth(1)?;
+
+    if let Ok(owner_id) = arg.parse() {
+        return Some(BlockedSet::OwnerId(owner_id));
+    }
+
+    let short_name = arg
+        .trim_start_matches("https://")
+        .trim_start_matches("t.me/addstickers/");
+
+    if short_name.is_empty()
+        || !short_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+
+    Some(BlockedSet::short_name(short_name))
+}
+
+pub async fn stats_handler<UoWFactory>(
+    bot: Bot,
+    message: MessageText,
+    uow_factory: UoWFactory,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+{
+    handler_called("stats_handler");
+
+    let mut uow = uow_factory.create_uow();
+
+    let users = uow
+        .user_repo()
+        .await
+        .map_err(HandlerError::new)?
+        .count()
+        .await
+        .map_err(HandlerError::new)?;
+
+    let mut set_repo = uow.set_repo().await.map_err(HandlerError::new)?;
+
+    let sets = set_repo
+        .count(Count::new(None, false))
+        .await
+        .map_err(HandlerError::new)?;
+    let deleted_sets = set_repo
+        .count(Count::new(Some(true), false))
+        .await
+        .map_err(HandlerError::new)?;
+    let stolen_today = set_repo
+        .count(Count::new(None, true))
+        .await
+        .map_err(HandlerError::new)?;
+
+    bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        stats_message(users, sets, deleted_sets, stolen_today),
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+pub async fn user_handler<UoWFactory>(
+    bot: Bot,
+    message: MessageText,
+    uow_factory: UoWFactory,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+{
+    handler_called("user_handler");
+
+    let Some(user_id) = user_id_arg(&message.text) else {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            "Specify ID of the user, for example: /user 123456789",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    };
+
+    let mut uow = uow_factory.create_uow();
+
+    let user = match uow
+        .user_repo()
+        .await
+        .map_err(HandlerError::new)?
+        .get_by_tg_id(GetUserByTgID::new(user_id))
+        .await
+    {
+        Ok(user) => user,
+        Err(RepoKind::Exception(_)) => {
+            bot.send_limited(SendMessage::new(
+                message.chat.id(),
+                "This user has never used the bot.",
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+        Err(err) => return Err(HandlerError::new(err)),
+    };
+
+    let sticker_sets = uow
+        .set_repo()
+        .await
+        .map_err(HandlerError::new)?
+        .get_by_tg_id(GetSetByTgID::new(user_id, None))
+        .await
+        .map_err(HandlerError::new)?;
+
+    bot.send_limited(
+        SendMessage::new(
+            message.chat.id(),
+            user_message(user.tg_id, user.banned, &sticker_sets),
+        )
+        .parse_mode(ParseMode::HTML),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+pub async fn ban_handler<UoWFactory>(
+    bot: Bot,
+    message: MessageText,
+    uow_factory: UoWFactory,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+{
+    handler_called("ban_handler");
+
+    set_banned(bot, message, uow_factory, true).await
+}
+
+pub async fn unban_handler<UoWFactory>(
+    bot: Bot,
+    message: MessageText,
+    uow_factory: UoWFactory,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+{
+    handler_called("unban_handler");
+
+    set_banned(bot, message, uow_factory, false).await
+}
+
+async fn set_banned<UoWFactory>(
+    bot: Bot,
+    message: MessageText,
+    uow_factory: UoWFactory,
+    banned: bool,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+{
+    let command = if banned { "ban" } else { "unban" };
+
+    let Some(user_id) = user_id_arg(&message.text) else {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            format!("Specify ID of the user, for example: /{command} 123456789"),
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    };
+
+    let mut uow = uow_factory.create_uow();
+
+    let text = if set_banned_col(&mut uow, SetBannedCol::new(user_id, banned))
+        .await
+        .map_err(HandlerError::new)?
+    {
+        format!("User {user_id} is {command}ned.")
+    } else {
+        "This user has never used the bot.".to_owned()
+    };
+
+    bot.send_limited(SendMessage::new(message.chat.id(), text))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+pub async fn block_handler(bot: Bot, message: MessageText, blocklist: Blocklist) -> HandlerResult {
+    handler_called("block_handler");
+
+    set_blocked(bot, message, blocklist, true).await
+}
+
+pub async fn unblock_handler(
+    bot: Bot,
+    message: MessageText,
+    blocklist: Blocklist,
+) -> HandlerResult {
+    handler_called("unblock_handler");
+
+    set_blocked(bot, message, blocklist, false).await
+}
+
+async fn set_blocked(
+    bot: Bot,
+    message: MessageText,
+    blocklist: Blocklist,
+    blocked: bool,
+) -> HandlerResult {
+    let command = if blocked { "block" } else { "unblock" };
+
+    let Some(set) = blocked_set_arg(&message.text) else {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            format!(
+                "Specify short name of the sticker pack, link to it or ID of its owner, \
+                for example: /{command} t.me/addstickers/name"
+            ),
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    };
+
+    let changed = if blocked {
+        blocklist.block(&set).await
+    } else {
+        blocklist.unblock(&set).await
+    }
+    .map_err(HandlerError::new)?;
+
+    let set = match set {
+        BlockedSet::ShortName(short_name) => format!("Sticker pack {short_name}"),
+        BlockedSet::OwnerId(owner_id) => format!("Every sticker pack of the user {owner_id}"),
+    };
+
+    let text = if changed {
+        format!("{set} is {command}ed.")
+    } else {
+        format!("{set} is already {command}ed.")
+    };
+
+    bot.send_limited(SendMessage::new(message.chat.id(), text))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+/// Show preview of the message, which the admin replied to, and ask to confirm its broadcast
+pub async fn broadcast_handler(bot: Bot, message: MessageText) -> HandlerResult {
+    handler_called("broadcast_handler");
+
+    let chat_id = message.chat.id();
+
+    let Some(reply) = message.reply_to_message else {
+        bot.send_limited(SendMessage::new(
+            chat_id,
+            "Reply with /broadcast to the message, which should be sent to every user.",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    };
+
+    let message_id = reply.id();
+
+    bot.send_limited(CopyMessage::new(chat_id, chat_id, message_id))
+        .await?;
+
+    let buttons = vec![vec![
+        InlineKeyboardButton::new("Send")
+            .callback_data(format!("{BROADCAST_DATA_PREFIX}{message_id}")),
+        InlineKeyboardButton::new("Cancel").callback_data(format!("{BROADCAST_DATA_PREFIX}cancel")),
+    ]];
+
+    bot.send_limited(
+        SendMessage::new(chat_id, "Send the message above to every user of the bot?").reply_markup(
+            ReplyMarkup::InlineKeyboard(InlineKeyboardMarkup::new(buttons)),
+        ),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+/// Start broadcast, if the admin confirmed it. Other buttons are skipped for the next routers.
+pub async fn process_broadcast_button(
+    bot: Bot,
+    callback_query: CallbackQuery,
+    broadcasts: Broadcasts,
+) -> HandlerResult {
+    let Some(data) = callback_query
+        .data
+        .as_deref()
+        .and_then(|data| data.strip_prefix(BROADCAST_DATA_PREFIX))
+    else {
+        return Ok(EventReturn::Skip);
+    };
+
+    handler_called("process_broadcast_button");
+
+    bot.send_limited(AnswerCallbackQuery::new(callback_query.id.clone()))
+        .await?;
+
+    let chat_id = callback_query.chat_id().expect("chat not found");
+
+    let text = match data.parse::<i64>() {
+        Ok(message_id) => {
+            if broadcasts
+                .start(bot.clone(), chat_id, message_id)
+                .await
+                .map_err(HandlerError::new)?
+            {
+                "Broadcast is started! You will get a report, when it is finished."
+            } else {
+                "This message is already broadcast."
+            }
+        }
+        Err(_) => "Broadcast is cancelled.",
+    };
+
+    bot.send_limited(SendMessage::new(chat_id, text)).await?;
+
+    Ok(EventReturn::Finish)
+}
+
+#[test]
+fn user_id_arg_test() {
+    assert_eq!(user_id_arg("/ban 123"), Some(123));
+    assert_eq!(user_id_arg("/ban   -100123 reason"), Some(-100123));
+    assert_eq!(user_id_arg("/ban"), None);
+    assert_eq!(user_id_arg("/ban @username"), None);
+}
+
+#[test]
+fn blocked_set_arg_test() {
+    assert_eq!(
+        blocked_set_arg("/block 123"),
+        Some(BlockedSet::OwnerId(123))
+    );
+    assert_eq!(
+        blocked_set_arg("/block Some_Pack"),
+        Some(BlockedSet::ShortName("some_pack".to_owned()))
+    );
+    assert_eq!(
+        blocked_set_arg("/block https://t.me/addstickers/Some_Pack"),
+        Some(BlockedSet::ShortName("some_pack".to_owned()))
+    );
+    assert_eq!(blocked_set_arg("/block t.me/addstickers/"), None);
+    assert_eq!(blocked_set_arg("/block"), None);
+}
@@ -6,14 +6,19 @@ use telers::{
     Bot,
 };
 
+use crate::bot_api::BotApi as _;
+use crate::logging::handler_called;
+
 pub async fn cancel_handler<S: Storage>(
     bot: Bot,
     message: MessageText,
     fsm: Context<S>,
 ) -> HandlerResult {
+    handler_called("cancel_handler");
+
     fsm.finish().await.map_err(Into::into)?;
 
-    bot.send(SendMessage::new(
+    bot.send_limited(SendMessage::new(
         message.chat.id(),
         "Last command was canceled.",
     ))
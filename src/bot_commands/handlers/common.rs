@@ -1,14 +1,64 @@
-use std::{borrow::Cow, time::Duration};
+use std::{borrow::Cow, sync::LazyLock};
 
+use grammers_client::Client as ClientGrammers;
 use telers::{
+    errors::{session::ErrorKind, HandlerError, TelegramErrorKind},
     event::{telegram::HandlerResult, EventReturn},
-    methods::{AddStickerToSet, SendMessage},
-    types::{InputFile, InputSticker, Message, Sticker},
+    methods::{AddStickerToSet, CreateNewStickerSet, GetFile, GetMe, SendMessage},
+    types::{InputFile, InputSticker, Message, ReplyParameters, Sticker},
     Bot,
 };
-use tracing::error;
+use tracing::{error, field::Empty, info, instrument, Span};
 
-use crate::core::common::sticker_format;
+use crate::{
+    application::{
+        commands::create_set::create_set,
+        common::{exceptions::TransactionKind, traits::uow::UoW as UoWTrait},
+        set::dto::create::Create as CreateSet,
+    },
+    bot_api::BotApi as _,
+    core::{
+        common::{generate_sticker_set_name_and_link, sticker_format},
+        stickers::constants::{CREATE_SET_IN_ONE_GO_LENGTH_LIMIT, TELEGRAM_BOT_API_FILE_URL},
+    },
+    logging::handler_called,
+    metrics::METRICS,
+    middlewares::{Blocklist, BlocklistError, QuotaKind, Quotas},
+    shutdown::{OperationGuard, OPERATIONS},
+    telegram_application::{errors::OwnerError, resolve_sticker_set_owner},
+    texts::{restarting_message, BLOCKED_SET_MESSAGE},
+};
+
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+/// Errors of `AddStickerToSet` method, which are counted by their own labels (others are counted as `bad_request`)
+const KNOWN_ADD_STICKER_ERRORS: [&str; 6] = [
+    "STICKERS_TOO_MUCH",
+    "STICKERSET_INVALID",
+    "STICKER_PNG_DIMENSIONS",
+    "STICKER_TGS_NOTGS",
+    "STICKER_VIDEO_LONG",
+    "STICKER_EMOJI_INVALID",
+];
+
+/// Label of `AddStickerToSet` error for [`METRICS`]. Labels don't contain variable parts
+/// of the error message (like seconds to retry after), so their number is limited.
+fn add_sticker_error_label(err: &ErrorKind) -> &'static str {
+    match err {
+        ErrorKind::Telegram(TelegramErrorKind::BadRequest { message }) => message
+            .strip_prefix("Bad Request: ")
+            .and_then(|code| {
+                KNOWN_ADD_STICKER_ERRORS
+                    .into_iter()
+                    .find(|known| *known == code)
+            })
+            .unwrap_or("bad_request"),
+        ErrorKind::Telegram(TelegramErrorKind::RetryAfter { .. }) => "retry_after",
+        ErrorKind::Telegram(TelegramErrorKind::Forbidden { .. }) => "forbidden",
+        ErrorKind::Telegram(_) => "other",
+        _ => "session",
+    }
+}
 
 #[derive(Debug, Clone, thiserror::Error)]
 #[error("Error occurded while adding stickers: {message}")]
@@ -24,8 +74,48 @@ impl AddStickersError {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadFileError {
+    #[error(transparent)]
+    Session(#[from] ErrorKind),
+    #[error("Telegram did not return path to the file")]
+    FilePathNotSpecified,
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateStickerSetError {
+    #[error(transparent)]
+    Telegram(#[from] ErrorKind),
+    #[error(transparent)]
+    Transaction(#[from] TransactionKind),
+    #[error(transparent)]
+    AddStickers(#[from] AddStickersError),
+}
+
+/// Sticker set created by [`create_sticker_set`]
+#[derive(Debug, Clone)]
+pub struct CreatedStickerSet {
+    pub name: String,
+    pub link: String,
+    pub added: AddedStickers,
+}
+
+/// Result of [`add_stickers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddedStickers {
+    All,
+    /// Some stickers weren't added because of errors
+    Partially,
+    /// Adding is stopped by shutdown, the user is told about it by [`crate::shutdown::finish_operations`]
+    Cancelled,
+}
+
 pub async fn process_non_sticker(bot: Bot, message: Message) -> HandlerResult {
-    bot.send(SendMessage::new(
+    handler_called("process_non_sticker");
+
+    bot.send_limited(SendMessage::new(
         message.chat().id(),
         "Please, send me a sticker.",
     ))
@@ -34,41 +124,326 @@ pub async fn process_non_sticker(bot: Bot, message: Message) -> HandlerResult {
     Ok(EventReturn::Finish)
 }
 
+pub async fn process_non_document(bot: Bot, message: Message) -> HandlerResult {
+    handler_called("process_non_document");
+
+    bot.send_limited(SendMessage::new(
+        message.chat().id(),
+        "Please, send me a file.",
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+/// Download file sent to the bot. Note, that Telegram Bot API allows to download files up to 20 MB.
+pub async fn download_file(bot: &Bot, file_id: &str) -> Result<Vec<u8>, DownloadFileError> {
+    let file_path = bot
+        .send_limited(GetFile::new(file_id))
+        .await?
+        .file_path
+        .ok_or(DownloadFileError::FilePathNotSpecified)?;
+
+    let file = HTTP_CLIENT
+        .get(format!(
+            "{TELEGRAM_BOT_API_FILE_URL}{token}/{file_path}",
+            token = bot.token
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    Ok(file.to_vec())
+}
+
+/// Take quota of the operation from limits of the user.
+/// If some limit is reached, tell the user when to retry and return `false`.
+pub async fn take_quota(
+    bot: &Bot,
+    quotas: &Quotas,
+    chat_id: i64,
+    user_id: i64,
+    usage: &[(QuotaKind, u32)],
+) -> Result<bool, HandlerError> {
+    let Some(exceeded) = quotas
+        .take(user_id, usage)
+        .await
+        .map_err(HandlerError::new)?
+    else {
+        return Ok(true);
+    };
+
+    info!(
+        user_id,
+        limit = exceeded.description,
+        "Operation of the user is not allowed:"
+    );
+
+    bot.send_limited(SendMessage::new(chat_id, exceeded.message()))
+        .await?;
+
+    Ok(false)
+}
+
+/// Give back quota of the operation, which is failed before anything is created
+pub async fn refund_quota(quotas: &Quotas, user_id: i64, usage: &[(QuotaKind, u32)]) {
+    if let Err(err) = quotas.refund(user_id, usage).await {
+        error!(?err, "An error occurded while refund quota:");
+    }
+}
+
+/// Register long operation of the user, so shutdown waits for it.
+/// If shutdown is already started, give back quota of the operation, tell the user to retry later and return `None`.
+pub async fn start_operation(
+    bot: &Bot,
+    quotas: &Quotas,
+    chat_id: i64,
+    user_id: i64,
+    usage: &[(QuotaKind, u32)],
+) -> Result<Option<OperationGuard<'static>>, HandlerError> {
+    if let Some(operation) = OPERATIONS.start(chat_id) {
+        return Ok(Some(operation));
+    }
+
+    info!(
+        user_id,
+        "Operation of the user is not started due to shutdown"
+    );
+
+    refund_quota(quotas, user_id, usage).await;
+
+    bot.send_limited(SendMessage::new(chat_id, restarting_message()))
+        .await?;
+
+    Ok(None)
+}
+
+/// Check the sticker pack of the user in the blocklist.
+/// If it's blocked (or can't be checked), tell the user and return `false`.
+pub async fn check_not_blocked(
+    bot: &Bot,
+    blocklist: &Blocklist,
+    chat_id: i64,
+    set_name: &str,
+    owner_id: Option<i64>,
+) -> Result<bool, HandlerError> {
+    let text = match blocklist.is_blocked(set_name, owner_id).await {
+        Ok(false) => return Ok(true),
+        Ok(true) => {
+            info!(set_name, "Sticker set is blocked:");
+
+            Cow::Borrowed(BLOCKED_SET_MESSAGE)
+        }
+        Err(BlocklistError::Owner(err)) => {
+            error!(%err, set_name, "failed to get sticker set user id:");
+
+            owner_error_message(&err)
+        }
+        Err(err) => {
+            error!(
+                ?err,
+                "An error occurded while check sticker set in blocklist:"
+            );
+
+            Cow::Borrowed("Sorry, an error occurded. Try again :(")
+        }
+    };
+
+    bot.send_limited(SendMessage::new(chat_id, text)).await?;
+
+    Ok(false)
+}
+
+/// Get owner of the sticker set.
+/// If it can't be got, tell the user why (in reply to the message) and return `None`.
+pub async fn resolve_owner(
+    bot: &Bot,
+    client: &ClientGrammers,
+    chat_id: i64,
+    message_id: i64,
+    set_name: &str,
+) -> Result<Option<i64>, HandlerError> {
+    match resolve_sticker_set_owner(set_name, client).await {
+        Ok(owner_id) => Ok(Some(owner_id)),
+        Err(err) => {
+            error!(%err, set_name, "failed to get sticker set user id:");
+
+            bot.send_limited(
+                SendMessage::new(chat_id, owner_error_message(&err))
+                    .reply_parameters(ReplyParameters::new(message_id).chat_id(chat_id)),
+            )
+            .await?;
+
+            Ok(None)
+        }
+    }
+}
+
+fn owner_error_message(err: &OwnerError) -> Cow<'static, str> {
+    match err {
+        OwnerError::NotFound => {
+            "This sticker pack doesn't exist anymore! Try to send another sticker.".into()
+        }
+        OwnerError::FloodWait(wait) => format!(
+            "Too many requests to Telegram :( Try again in {} seconds.",
+            wait.as_secs().max(1)
+        )
+        .into(),
+        _ => "Sorry, an error occurded. Try again :(".into(),
+    }
+}
+
+/// Convert sticker into [`InputSticker`] to add it into another sticker set
+pub fn input_sticker(sticker: &Sticker) -> InputSticker {
+    InputSticker::new(
+        InputFile::id(sticker.file_id.as_ref()),
+        sticker_format(sticker),
+    )
+    .emoji_list(sticker.emoji.clone())
+}
+
+/// Create new sticker set owned by user with random name and save it into database.
+/// If there are more than [`CREATE_SET_IN_ONE_GO_LENGTH_LIMIT`] stickers, the rest of them are added one by one
+/// (it can take a long time).
+#[instrument(skip_all, fields(user_id = user_id, stickers = stickers.len(), set_name = Empty))]
+pub async fn create_sticker_set<UoW>(
+    bot: &Bot,
+    uow: &mut UoW,
+    user_id: i64,
+    title: &str,
+    mut stickers: Vec<InputSticker>,
+) -> Result<CreatedStickerSet, CreateStickerSetError>
+where
+    UoW: UoWTrait,
+{
+    // cant panic because bot cant be without username
+    let bot_username = bot
+        .send_limited(GetMe::new())
+        .await?
+        .username
+        .expect("bot without username :/");
+
+    // prepare name for new sticker set and link to use it in message later
+    let (mut set_name, mut set_link) = generate_sticker_set_name_and_link(11, &bot_username);
+    Span::current().record("set_name", set_name.as_str());
+
+    let other_stickers = if stickers.len() > CREATE_SET_IN_ONE_GO_LENGTH_LIMIT {
+        stickers.split_off(CREATE_SET_IN_ONE_GO_LENGTH_LIMIT)
+    } else {
+        Vec::new()
+    };
+
+    while let Err(err) = bot
+        .send_limited(CreateNewStickerSet::new(
+            user_id,
+            set_name.as_str(),
+            title,
+            stickers.clone(),
+        ))
+        .await
+    {
+        if matches!(&err, ErrorKind::Telegram(TelegramErrorKind::BadRequest { message }) if message.as_ref()
+            == "Bad Request: SHORTNAME_OCCUPY_FAILED")
+        {
+            error!(
+                ?err,
+                "file to create new sticker set; trying to generate sticker set name again:"
+            );
+            error!(set_name, "sticker set name:");
+
+            (set_name, set_link) = generate_sticker_set_name_and_link(11, &bot_username);
+            Span::current().record("set_name", set_name.as_str());
+        } else {
+            error!(?err, "error occureded while creating new sticker set:");
+            error!(set_name, "sticker set name:");
+
+            return Err(err.into());
+        }
+    }
+
+    create_set(uow, CreateSet::new(user_id, set_name.as_str(), title)).await?;
+
+    let added = if other_stickers.is_empty() {
+        AddedStickers::All
+    } else {
+        add_stickers(bot, user_id, set_name.as_str(), other_stickers).await?
+    };
+
+    Ok(CreatedStickerSet {
+        name: set_name,
+        link: set_link,
+        added,
+    })
+}
+
+/// Add stickers one by one.
+/// If operations are cancelled by shutdown, the rest of stickers isn't added.
+#[instrument(
+    skip(bot, sticker_list),
+    fields(stickers = sticker_list.len(), added = Empty, failed = Empty, cancelled = Empty)
+)]
 pub async fn add_stickers(
     bot: &Bot,
     user_id: i64,
     set_name: &str,
-    sticker_list: &[Sticker],
-) -> Result<bool, AddStickersError> {
+    sticker_list: Vec<InputSticker>,
+) -> Result<AddedStickers, AddStickersError> {
     if sticker_list.is_empty() {
         return Err(AddStickersError::new("list is empty"));
     }
 
-    let mut all_stickers_was_stolen = true;
+    let stickers_number = sticker_list.len();
+    let (mut added, mut failed, mut cancelled) = (0, 0, false);
+
+    for (i, sticker) in sticker_list.into_iter().enumerate() {
+        if OPERATIONS.is_cancelled() {
+            let stopped = stickers_number - i;
+
+            Span::current().record("cancelled", stopped);
+            info!(
+                cancelled = stopped,
+                "Adding of stickers is stopped because of shutdown"
+            );
 
-    let mut sticker_formats = sticker_list.iter().map(|sticker| sticker_format(sticker));
+            cancelled = true;
 
-    for sticker in sticker_list {
-        if let Err(err) = bot
-            .send(AddStickerToSet::new(user_id, set_name, {
-                let sticker_is = InputSticker::new(
-                    InputFile::id(sticker.file_id.as_ref()),
-                    sticker_formats.next().unwrap(),
-                );
+            break;
+        }
 
-                sticker_is.emoji_list(sticker.clone().emoji)
-            }))
+        match bot
+            .send_limited(AddStickerToSet::new(user_id, set_name, sticker))
             .await
         {
-            error!(?err, "error occureded while adding sticker to sticker set:");
-            error!(set_name, "sticker set name:");
+            Ok(_) => {
+                METRICS.stickers_added.inc();
 
-            all_stickers_was_stolen = false;
-        }
+                added += 1;
+            }
+            Err(err) => {
+                error!(?err, "error occureded while adding sticker to sticker set:");
+                error!(set_name, "sticker set name:");
 
-        // sleep because you can’t send telegram api requests more often than per second
-        tokio::time::sleep(Duration::from_millis(1001)).await;
+                METRICS
+                    .add_sticker_to_set_errors
+                    .with_label_values(&[add_sticker_error_label(&err)])
+                    .inc();
+
+                failed += 1;
+            }
+        }
     }
 
-    Ok(all_stickers_was_stolen)
+    Span::current()
+        .record("added", added)
+        .record("failed", failed);
+
+    Ok(if cancelled {
+        AddedStickers::Cancelled
+    } else if failed > 0 {
+        AddedStickers::Partially
+    } else {
+        AddedStickers::All
+    })
 }
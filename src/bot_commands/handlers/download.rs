@@ -0,0 +1,181 @@
+use std::fs::File;
+
+use grammers_client::Client as ClientGrammers;
+use telers::{
+    errors::HandlerError,
+    event::{telegram::HandlerResult, EventReturn},
+    fsm::{Context, Storage},
+    methods::{DeleteMessage, GetStickerSet, SendDocument, SendMessage},
+    types::{InputFile, MessageSticker, MessageText, StickerSet},
+    Bot,
+};
+use tracing::error;
+
+use crate::{
+    bot_api::BotApi as _,
+    bot_commands::{handlers::common::download_file, states::DownloadState},
+    core::{
+        common::sticker_format,
+        stickers::{
+            archive::{sticker_file_extension, ArchiveSticker, ArchivesWriter},
+            constants::TELEGRAM_UPLOAD_FILE_SIZE_LIMIT,
+        },
+    },
+    logging::handler_called,
+    middlewares::Client,
+    telegram_application::get_sticker_set_keywords,
+};
+
+pub async fn download_handler<S: Storage>(
+    bot: Bot,
+    message: MessageText,
+    fsm: Context<S>,
+) -> HandlerResult {
+    handler_called("download_handler");
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    fsm.set_state(DownloadState::GetStickerSetToDownload)
+        .await
+        .map_err(Into::into)?;
+
+    bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        "Send me a sticker and i will send you zip archive with all stickers from this sticker pack!",
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+pub async fn get_sticker_set_to_download<S: Storage>(
+    bot: Bot,
+    message: MessageSticker,
+    fsm: Context<S>,
+    Client(client): Client,
+) -> HandlerResult {
+    handler_called("get_sticker_set_to_download");
+
+    let set_name = match message.sticker.set_name {
+        Some(sticker_set_name) => sticker_set_name,
+        None => {
+            bot.send_limited(SendMessage::new(
+                message.chat.id(),
+                "This sticker is without sticker pack! Try to send another sticker pack.",
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    };
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    let sticker_set = bot
+        .send_limited(GetStickerSet::new(set_name.as_ref()))
+        .await?;
+
+    let message_delete = bot
+        .send_limited(SendMessage::new(
+            message.chat.id(),
+            "Downloading sticker pack..\n(downloading big sticker packs can take up to a few minutes)",
+        ))
+        .await?;
+
+    let result = send_sticker_set_archives(&bot, &message, &client, &set_name, &sticker_set).await;
+
+    // delete unnecessary message
+    bot.send_limited(DeleteMessage::new(
+        message_delete.chat().id(),
+        message_delete.id(),
+    ))
+    .await?;
+
+    result
+}
+
+/// Download stickers into archives in temporary directory and send them.
+/// Only one sticker is kept in memory, so big sticker packs don't take a lot of memory.
+async fn send_sticker_set_archives(
+    bot: &Bot,
+    message: &MessageSticker,
+    client: &ClientGrammers,
+    set_name: &str,
+    sticker_set: &StickerSet,
+) -> HandlerResult {
+    // keywords are optional, so if we cant get them, just send archive without keywords
+    let mut keywords = match get_sticker_set_keywords(set_name, client).await {
+        Ok(keywords) => keywords.into_iter(),
+        Err(err) => {
+            error!(?err, "error occurded while getting sticker set keywords:");
+
+            Vec::new().into_iter()
+        }
+    };
+
+    // directory is removed with the archives, when it's dropped
+    let dir = tempfile::tempdir().map_err(HandlerError::new)?;
+    let part_path = |number: usize| dir.path().join(format!("part{number}.zip"));
+
+    let mut parts_number = 0;
+    let mut archives = ArchivesWriter::new(
+        set_name,
+        sticker_set.title.as_ref(),
+        TELEGRAM_UPLOAD_FILE_SIZE_LIMIT,
+        || {
+            parts_number += 1;
+
+            File::create(part_path(parts_number))
+        },
+    );
+
+    for (index, sticker) in sticker_set.stickers.iter().enumerate() {
+        let data = match download_file(bot, sticker.file_id.as_ref()).await {
+            Ok(data) => data,
+            Err(err) => {
+                error!(?err, "error occurded while downloading sticker:");
+                error!(%set_name, "sticker set name:");
+
+                bot.send_limited(SendMessage::new(
+                    message.chat.id(),
+                    "Sorry, an error occurded while downloading sticker pack. Try again :(",
+                ))
+                .await?;
+
+                return Ok(EventReturn::Finish);
+            }
+        };
+
+        let format = sticker_format(sticker);
+
+        archives
+            .add(&ArchiveSticker {
+                position: index + 1,
+                emoji: sticker.emoji.as_deref().map(ToOwned::to_owned),
+                keywords: keywords.next().unwrap_or_default(),
+                extension: sticker_file_extension(&format),
+                format,
+                data,
+            })
+            .map_err(HandlerError::new)?;
+    }
+
+    // files are closed, so they can be read to send
+    let parts_number = archives.finish().map_err(HandlerError::new)?.len();
+
+    for number in 1..=parts_number {
+        let file_name = if parts_number == 1 {
+            format!("{set_name}.zip")
+        } else {
+            format!("{set_name}.part{number}.zip")
+        };
+
+        bot.send_limited(SendDocument::new(
+            message.chat.id(),
+            InputFile::fs_with_name(part_path(number), file_name),
+        ))
+        .await?;
+    }
+
+    Ok(EventReturn::Finish)
+}
@@ -0,0 +1,85 @@
+use telers::{
+    errors::HandlerError,
+    event::{telegram::HandlerResult, EventReturn},
+    fsm::{Context, Storage},
+    methods::{SendDocument, SendMessage},
+    types::{InputFile, MessageText},
+    Bot,
+};
+
+use crate::{
+    application::{
+        common::traits::uow::{UoW as _, UoWFactory as UoWFactoryTrait},
+        set::{dto::get_by_tg_id::GetByTgID as GetSetByTgID, traits::SetRepo as _},
+    },
+    bot_api::BotApi as _,
+    core::stickers::export::{sets_to_csv, sets_to_json, ExportFormat},
+    logging::handler_called,
+};
+
+/// ### Panics
+/// - Panics if user is unknown (only if message sent in channel)
+pub async fn export_handler<S, UoWFactory>(
+    bot: Bot,
+    message: MessageText,
+    fsm: Context<S>,
+    uow_factory: UoWFactory,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+    S: Storage,
+{
+    handler_called("export_handler");
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    let format = match ExportFormat::from_arg(message.text.split_whitespace().nth(1)) {
+        Some(format) => format,
+        None => {
+            bot.send_limited(SendMessage::new(
+                message.chat.id(),
+                "Unknown format! Use /export json or /export csv.",
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    };
+
+    let mut uow = uow_factory.create_uow();
+
+    // only panic if messages uses in channels, but i'm using private filter in main function
+    let user_id = message.from.expect("user not specified").id;
+
+    let sticker_sets = uow
+        .set_repo()
+        .await
+        .map_err(HandlerError::new)?
+        .get_by_tg_id(GetSetByTgID::new(user_id, None))
+        .await
+        .map_err(HandlerError::new)?;
+
+    if sticker_sets.is_empty() {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            "You don't have a single stolen sticker pack. \
+            Steal any sticker pack using the /stealpack command and you will be able to export it.",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    let file = match format {
+        ExportFormat::Json => sets_to_json(&sticker_sets).map_err(HandlerError::new)?,
+        ExportFormat::Csv => sets_to_csv(&sticker_sets),
+    };
+
+    bot.send_limited(SendDocument::new(
+        message.chat.id(),
+        InputFile::buffered_with_name(file.into_bytes(), format.file_name()),
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
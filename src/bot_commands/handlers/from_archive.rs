@@ -0,0 +1,344 @@
+use telers::{
+    enums::ParseMode,
+    errors::HandlerError,
+    event::{telegram::HandlerResult, EventReturn},
+    fsm::{Context, Storage},
+    methods::{DeleteMessage, SendMessage, UploadStickerFile},
+    types::{InputFile, InputSticker, MessageDocument, MessageText},
+    utils::text::{html_bold, html_code, html_text_link},
+    Bot,
+};
+use tracing::error;
+
+use crate::{
+    application::common::traits::uow::UoWFactory as UoWFactoryTrait,
+    bot_api::BotApi as _,
+    bot_commands::{
+        handlers::common::{
+            create_sticker_set, download_file, refund_quota, start_operation, take_quota,
+            AddedStickers, CreateStickerSetError,
+        },
+        states::FromArchiveState,
+    },
+    core::stickers::{
+        archive::{read_archive, ArchiveContent},
+        constants::{CREATE_SET_IN_ONE_GO_LENGTH_LIMIT, DEFAULT_STICKER_EMOJI},
+    },
+    logging::handler_called,
+    middlewares::{QuotaKind, Quotas},
+    shutdown::OPERATIONS,
+    texts::{archive_error_message, created_sticker_set_message},
+};
+
+pub async fn from_archive_handler<S: Storage>(
+    bot: Bot,
+    message: MessageText,
+    fsm: Context<S>,
+) -> HandlerResult {
+    handler_called("from_archive_handler");
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    fsm.set_state(FromArchiveState::GetNewStickerSetTitle)
+        .await
+        .map_err(Into::into)?;
+
+    bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        "Enter name for your new sticker pack (1-64 characters).",
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+pub async fn get_new_sticker_set_title<S: Storage>(
+    bot: Bot,
+    message: MessageText,
+    fsm: Context<S>,
+) -> HandlerResult {
+    handler_called("get_new_sticker_set_title");
+
+    // if user enter wrong sticker set title, process it
+    if message.text.len() > 64 {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            "Too long name for sticker pack! Try enter a name up to 64 characters long.",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    } else if message.text.is_empty() {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            "Too short name! Try enter a name between 1 and 64 characters long.",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    fsm.set_value("get_new_sticker_set_title", message.text.as_ref())
+        .await
+        .map_err(Into::into)?;
+
+    fsm.set_state(FromArchiveState::GetArchive)
+        .await
+        .map_err(Into::into)?;
+
+    bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        "Now send me zip archive with sticker files (.webp, .png, .tgs or .webm). \
+        You can also send archive, which you got using command /download, \
+        WhatsApp sticker pack (.wastickers) or Signal sticker pack bundle.",
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+/// ### Panics
+/// - Panics if user is unknown (only if message sent in channel)
+pub async fn get_archive<S, UoWFactory>(
+    bot: Bot,
+    message: MessageDocument,
+    fsm: Context<S>,
+    uow_factory: UoWFactory,
+    quotas: Quotas,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+    S: Storage,
+{
+    handler_called("get_archive");
+
+    let is_zip = message
+        .document
+        .file_name
+        .as_deref()
+        .map(str::to_lowercase)
+        .is_some_and(|file_name| file_name.ends_with(".zip") || file_name.ends_with(".wastickers"));
+
+    if !is_zip {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            "Please, send me zip archive (or .wastickers file).",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    let archive = match download_file(&bot, message.document.file_id.as_ref()).await {
+        Ok(archive) => archive,
+        Err(err) => {
+            error!(?err, "error occurded while downloading archive:");
+
+            bot.send_limited(SendMessage::new(
+                message.chat.id(),
+                "Sorry, an error occurded. Try send this archive again :(",
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    };
+
+    let archive = match read_archive(&archive) {
+        Ok(archive) => archive,
+        Err(err) => {
+            bot.send_limited(SendMessage::new(
+                message.chat.id(),
+                archive_error_message(&err),
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    };
+
+    // only panic if i'm forget call fsm.set_value() in function get_new_sticker_set_title()
+    let new_set_title: Box<str> = fsm
+        .get_value("get_new_sticker_set_title")
+        .await
+        .map_err(Into::into)?
+        .expect("Title for new sticker set should be set");
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    // only panic if messages uses in channels, but i'm using private filter in main function
+    let user_id = message.from.expect("user not specified").id;
+
+    let usage = [
+        (QuotaKind::Steal, 1),
+        (QuotaKind::Sticker, archive.stickers.len() as u32),
+    ];
+
+    if !take_quota(&bot, &quotas, message.chat.id(), user_id, &usage).await? {
+        return Ok(EventReturn::Finish);
+    }
+
+    let Some(_operation) =
+        start_operation(&bot, &quotas, message.chat.id(), user_id, &usage).await?
+    else {
+        return Ok(EventReturn::Finish);
+    };
+
+    let message_delete = bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        format!(
+            "Creating sticker pack with name `{new_set_title}` for you..\n(creating sticker packs \
+            containing more than {CREATE_SET_IN_ONE_GO_LENGTH_LIMIT} stickers can take up to a several minutes due to some internal limitations)",
+        ),
+    ))
+    .await?;
+
+    let result = create_sticker_set_from_archive(
+        &bot,
+        &uow_factory,
+        &quotas,
+        message.chat.id(),
+        user_id,
+        &usage,
+        &archive,
+        &new_set_title,
+    )
+    .await;
+
+    // delete unnecessary message
+    bot.send_limited(DeleteMessage::new(
+        message_delete.chat().id(),
+        message_delete.id(),
+    ))
+    .await?;
+
+    result
+}
+
+/// Upload stickers of the archive and create sticker pack from them.
+/// Quota of the user is given back, if sticker pack isn't created.
+#[allow(clippy::too_many_arguments)]
+async fn create_sticker_set_from_archive<UoWFactory>(
+    bot: &Bot,
+    uow_factory: &UoWFactory,
+    quotas: &Quotas,
+    chat_id: i64,
+    user_id: i64,
+    usage: &[(QuotaKind, u32)],
+    archive: &ArchiveContent,
+    new_set_title: &str,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+{
+    let mut uploaded_files = Vec::with_capacity(archive.stickers.len());
+
+    for sticker in &archive.stickers {
+        // sticker pack isn't created yet, so there is nothing to save
+        if OPERATIONS.is_cancelled() {
+            refund_quota(quotas, user_id, usage).await;
+
+            return Ok(EventReturn::Finish);
+        }
+
+        match bot
+            .send_limited(UploadStickerFile::new(
+                user_id,
+                InputFile::buffered_with_name(sticker.data.clone(), sticker.file_name()),
+                sticker.format.as_str(),
+            ))
+            .await
+        {
+            Ok(file) => uploaded_files.push(file),
+            Err(err) => {
+                error!(?err, "error occurded while uploading sticker file:");
+
+                refund_quota(quotas, user_id, usage).await;
+
+                bot.send_limited(
+                    SendMessage::new(
+                        chat_id,
+                        format!(
+                            "Telegram didn't accept sticker file {file} :( Fix it and try send archive again.",
+                            file = html_code(sticker.file_name())
+                        ),
+                    )
+                    .parse_mode(ParseMode::HTML),
+                )
+                .await?;
+
+                return Ok(EventReturn::Finish);
+            }
+        }
+    }
+
+    let stickers: Vec<InputSticker> = archive
+        .stickers
+        .iter()
+        .zip(uploaded_files.iter())
+        .map(|(sticker, file)| {
+            InputSticker::new(
+                InputFile::id(file.file_id.as_ref()),
+                sticker.format.as_str(),
+            )
+            .emoji_list([sticker.emoji.as_deref().unwrap_or(DEFAULT_STICKER_EMOJI)])
+            .keywords(sticker.keywords.iter().map(String::as_str))
+        })
+        .collect();
+
+    let mut uow = uow_factory.create_uow();
+
+    let new_set = match create_sticker_set(bot, &mut uow, user_id, new_set_title, stickers).await {
+        Ok(new_set) => new_set,
+        Err(CreateStickerSetError::Telegram(_)) => {
+            refund_quota(quotas, user_id, usage).await;
+
+            bot.send_limited(SendMessage::new(
+                chat_id,
+                "Error occurded while creating new sticker pack :(",
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+        Err(err) => {
+            refund_quota(quotas, user_id, usage).await;
+
+            return Err(HandlerError::new(err));
+        }
+    };
+
+    // the user is told about interrupted operation by shutdown
+    if new_set.added == AddedStickers::Cancelled {
+        return Ok(EventReturn::Finish);
+    }
+
+    if new_set.added == AddedStickers::Partially {
+        bot.send_limited(SendMessage::new(
+            chat_id,
+            format!(
+                "Error occurded while creating new sticker pack {created_pack}, {but_created}! \n\
+                Due to an error, not all stickers have been added :( \
+                (you can delete this sticker pack if you want using the /delpack command in official Telegram bot @Stickers. \
+                Name of this sticker pack: {set_name})",
+                created_pack = html_text_link(new_set_title, new_set.link),
+                but_created = html_bold("but sticker pack was created"),
+                set_name = html_code(new_set.name.as_str())
+            ),
+        ).parse_mode(ParseMode::HTML))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    bot.send_limited(
+        SendMessage::new(
+            chat_id,
+            created_sticker_set_message(new_set_title, &new_set.name, &new_set.link),
+        )
+        .parse_mode(ParseMode::HTML),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
@@ -0,0 +1,172 @@
+use telers::{
+    event::{telegram::HandlerResult, EventReturn},
+    fsm::{Context, Storage},
+    methods::{GetMe, GetStickerSet, SendMessage},
+    types::{MessageDocument, MessageText},
+    Bot,
+};
+use tracing::error;
+
+use crate::{
+    application::{
+        commands::create_set::create_set, common::traits::uow::UoWFactory as UoWFactoryTrait,
+        set::dto::create::Create as CreateSet,
+    },
+    bot_api::BotApi as _,
+    bot_commands::{handlers::common::download_file, states::ImportState},
+    core::{common::set_created_by, stickers::export::sets_from_json},
+    logging::handler_called,
+    middlewares::Client,
+    telegram_application::resolve_sticker_set_owner,
+    texts::import_finished_message,
+};
+
+pub async fn import_handler<S: Storage>(
+    bot: Bot,
+    message: MessageText,
+    fsm: Context<S>,
+) -> HandlerResult {
+    handler_called("import_handler");
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    fsm.set_state(ImportState::GetImportFile)
+        .await
+        .map_err(Into::into)?;
+
+    bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        "Send me JSON file, which you got using command /export json.",
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+/// Save sticker packs from the exported file, which exist, were created by this bot and belong to the user.
+/// Sticker packs, which can't be saved due to an error, are reported to the user and don't stop the import.
+/// ### Panics
+/// - Panics if user is unknown (only if message sent in channel)
+pub async fn get_import_file<S, UoWFactory>(
+    bot: Bot,
+    message: MessageDocument,
+    fsm: Context<S>,
+    Client(client): Client,
+    uow_factory: UoWFactory,
+) -> HandlerResult
+where
+    UoWFactory: UoWFactoryTrait,
+    S: Storage,
+{
+    handler_called("get_import_file");
+
+    let file = match download_file(&bot, message.document.file_id.as_ref()).await {
+        Ok(file) => file,
+        Err(err) => {
+            error!(?err, "error occurded while downloading file to import:");
+
+            bot.send_limited(SendMessage::new(
+                message.chat.id(),
+                "Sorry, an error occurded. Try send this file again :(",
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    };
+
+    let sticker_sets = match std::str::from_utf8(&file)
+        .ok()
+        .and_then(|file| sets_from_json(file).ok())
+    {
+        Some(sticker_sets) => sticker_sets,
+        None => {
+            bot.send_limited(SendMessage::new(
+                message.chat.id(),
+                "This file is not a file exported by this bot! Try send another file.",
+            ))
+            .await?;
+
+            return Ok(EventReturn::Finish);
+        }
+    };
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    // only panic if messages uses in channels, but i'm using private filter in main function
+    let user_id = message.from.expect("user not specified").id;
+
+    let bot_username = bot
+        .send_limited(GetMe::new())
+        .await?
+        .username
+        .expect("bot without username :/");
+
+    let mut uow = uow_factory.create_uow();
+
+    let (mut imported, mut existing, mut skipped, mut failed) = (0, 0, 0, 0);
+
+    for sticker_set in sticker_sets {
+        let set_name = sticker_set.short_name.as_str();
+
+        // we can register only sticker packs created by this bot
+        if !set_created_by(set_name, bot_username.as_ref()) {
+            skipped += 1;
+
+            continue;
+        }
+
+        let set_title = match bot.send_limited(GetStickerSet::new(set_name)).await {
+            Ok(set) => set.title,
+            Err(err) => {
+                error!(?err, set_name, "sticker set to import not found:");
+
+                skipped += 1;
+
+                continue;
+            }
+        };
+
+        match resolve_sticker_set_owner(set_name, &client).await {
+            Ok(owner_id) if owner_id == user_id => {}
+            Ok(_) => {
+                skipped += 1;
+
+                continue;
+            }
+            Err(err) => {
+                error!(?err, set_name, "failed to get sticker set user id:");
+
+                skipped += 1;
+
+                continue;
+            }
+        }
+
+        match create_set(
+            &mut uow,
+            CreateSet::new(user_id, set_name, set_title.as_ref()),
+        )
+        .await
+        {
+            Ok(true) => imported += 1,
+            Ok(false) => existing += 1,
+            Err(err) => {
+                error!(
+                    ?err,
+                    set_name, "An error occurded while import sticker set:"
+                );
+
+                failed += 1;
+            }
+        }
+    }
+
+    bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        import_finished_message(imported, existing, skipped, failed),
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
@@ -22,9 +22,11 @@ use crate::{
         },
         set::{dto::get_by_tg_id::GetByTgID as GetSetByTgID, traits::SetRepo as _},
     },
+    bot_api::BotApi as _,
     bot_commands::states::MyStickersState,
     core::stickers::constants::STICKER_SETS_NUMBER_PER_PAGE,
     domain::entities::set::Set,
+    logging::handler_called,
     texts::current_page_message,
 };
 
@@ -58,6 +60,8 @@ where
     UoWFactory: UoWFactoryTrait,
     S: Storage,
 {
+    handler_called("my_stickers_handler");
+
     fsm.finish().await.map_err(Into::into)?;
 
     let mut uow = uow_factory.create_uow();
@@ -82,7 +86,7 @@ where
     ) {
         Ok(pages) => pages,
         Err(err) => {
-            bot.send(SendMessage::new(message.chat.id(), err.message.to_string()))
+            bot.send_limited(SendMessage::new(message.chat.id(), err.message.to_string()))
                 .await?;
 
             return Ok(EventReturn::Finish);
@@ -93,7 +97,7 @@ where
     let inline_keyboard = ReplyMarkup::InlineKeyboard(inline_keyboard_markup.clone());
 
     let sticker_sets_list_message = bot
-        .send(
+        .send_limited(
             SendMessage::new(
                 message.chat.id(),
                 current_page_message(
@@ -140,6 +144,8 @@ where
     UoWFactory: UoWFactoryTrait,
     S: Storage,
 {
+    handler_called("process_button");
+
     let mut uow = uow_factory.create_uow();
 
     let message_data = match callback_query.data {
@@ -149,7 +155,7 @@ where
                 "None value occurded while processed callback query from inline keyboard button!"
             );
 
-            bot.send(SendMessage::new(
+            bot.send_limited(SendMessage::new(
                 callback_query.chat_id().expect("chat not found"),
                 "Sorry, an error occurded. Try again :(",
             ))
@@ -233,7 +239,8 @@ where
         .message_id(message_to_edit_id)
         .reply_markup(message_to_edit_reply_markup);
 
-    bot.send(edit_message.parse_mode(ParseMode::HTML)).await?;
+    bot.send_limited(edit_message.parse_mode(ParseMode::HTML))
+        .await?;
 
     Ok(EventReturn::Finish)
 }
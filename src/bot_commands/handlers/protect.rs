@@ -0,0 +1,187 @@
+use telers::{
+    errors::HandlerError,
+    event::{telegram::HandlerResult, EventReturn},
+    fsm::{Context, Storage},
+    methods::{AnswerCallbackQuery, SendMessage},
+    types::{
+        CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, MessageSticker, MessageText,
+        ReplyMarkup,
+    },
+    Bot,
+};
+
+use crate::{
+    bot_api::BotApi as _,
+    bot_commands::states::ProtectState,
+    infrastructure::database::blocked_sets::BlockedSet,
+    logging::handler_called,
+    middlewares::{Blocklist, Client},
+};
+
+use super::common::resolve_owner;
+
+/// Data of the button, which protects only the sticker pack of the sent sticker
+const PROTECT_SET_DATA: &str = "protect:set";
+/// Data of the button, which protects every sticker pack of the creator
+const PROTECT_ALL_DATA: &str = "protect:all";
+
+pub async fn protect_my_packs_handler<S: Storage>(
+    bot: Bot,
+    message: MessageText,
+    fsm: Context<S>,
+) -> HandlerResult {
+    handler_called("protect_my_packs_handler");
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    fsm.set_state(ProtectState::GetStickerToProtect)
+        .await
+        .map_err(Into::into)?;
+
+    bot.send_limited(SendMessage::new(
+        message.chat.id(),
+        "Send me a sticker from the sticker pack you created, and i will not let anyone steal it!",
+    ))
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+/// Check, that the user is the creator of the sticker pack, and ask what to protect
+pub async fn get_sticker_to_protect<S: Storage>(
+    bot: Bot,
+    message: MessageSticker,
+    fsm: Context<S>,
+    Client(client): Client,
+) -> HandlerResult {
+    handler_called("get_sticker_to_protect");
+
+    let Some(set_name) = message.sticker.set_name else {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            "This sticker is without sticker pack! Try to send another sticker.",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    };
+
+    // only panic if bot using in channels, but i'm using private filter in main function
+    let user_id = message.from.expect("user without id").id;
+
+    let Some(owner_id) =
+        resolve_owner(&bot, &client, message.chat.id(), message.id, &set_name).await?
+    else {
+        return Ok(EventReturn::Finish);
+    };
+
+    if owner_id != user_id {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            "You are not the creator of this sticker pack! Send a sticker from the sticker pack you created.",
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    fsm.set_value("protect_sticker_set_name", set_name.as_ref())
+        .await
+        .map_err(Into::into)?;
+
+    fsm.set_state(ProtectState::ChooseProtectedSets)
+        .await
+        .map_err(Into::into)?;
+
+    let buttons = vec![vec![
+        InlineKeyboardButton::new("Only this pack").callback_data(PROTECT_SET_DATA),
+        InlineKeyboardButton::new("All my packs").callback_data(PROTECT_ALL_DATA),
+    ]];
+
+    bot.send_limited(
+        SendMessage::new(
+            message.chat.id(),
+            "Protect only this sticker pack or all sticker packs you created?",
+        )
+        .reply_markup(ReplyMarkup::InlineKeyboard(InlineKeyboardMarkup::new(
+            buttons,
+        ))),
+    )
+    .await?;
+
+    Ok(EventReturn::Finish)
+}
+
+pub async fn process_protect_button<S: Storage>(
+    bot: Bot,
+    callback_query: CallbackQuery,
+    fsm: Context<S>,
+    blocklist: Blocklist,
+) -> HandlerResult {
+    handler_called("process_protect_button");
+
+    bot.send_limited(AnswerCallbackQuery::new(callback_query.id.clone()))
+        .await?;
+
+    let chat_id = callback_query.chat_id().expect("chat not found");
+    let user_id = callback_query.from.id;
+
+    // only panic if i'm forget call fsm.set_value() in function get_sticker_to_protect()
+    let set_name: Box<str> = fsm
+        .get_value("protect_sticker_set_name")
+        .await
+        .map_err(Into::into)?
+        .expect("sticker set name to protect should be set");
+
+    let set = match callback_query.data.as_deref() {
+        Some(PROTECT_SET_DATA) => BlockedSet::short_name(&set_name),
+        Some(PROTECT_ALL_DATA) => BlockedSet::OwnerId(user_id),
+        _ => return Ok(EventReturn::Finish),
+    };
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    let text = if blocklist
+        .protect(&set, user_id)
+        .await
+        .map_err(HandlerError::new)?
+    {
+        "Done! Your sticker packs can't be stolen anymore. Use /unprotect to allow it again."
+    } else {
+        "This is already protected!"
+    };
+
+    bot.send_limited(SendMessage::new(chat_id, text)).await?;
+
+    Ok(EventReturn::Finish)
+}
+
+pub async fn unprotect_handler<S: Storage>(
+    bot: Bot,
+    message: MessageText,
+    fsm: Context<S>,
+    blocklist: Blocklist,
+) -> HandlerResult {
+    handler_called("unprotect_handler");
+
+    fsm.finish().await.map_err(Into::into)?;
+
+    // only panic if bot using in channels, but i'm using private filter in main function
+    let user_id = message.from.expect("user without id").id;
+
+    let text = if blocklist
+        .unprotect(user_id)
+        .await
+        .map_err(HandlerError::new)?
+        > 0
+    {
+        "Done! Your sticker packs can be stolen again."
+    } else {
+        "You don't have protected sticker packs. Use /protectmypacks to protect them."
+    };
+
+    bot.send_limited(SendMessage::new(message.chat.id(), text))
+        .await?;
+
+    Ok(EventReturn::Finish)
+}
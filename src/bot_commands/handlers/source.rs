@@ -8,14 +8,19 @@ use telers::{
     Bot,
 };
 
+use crate::bot_api::BotApi as _;
+use crate::logging::handler_called;
+
 pub async fn source_handler<S: Storage>(
     bot: Bot,
     message: MessageText,
     fsm: Context<S>,
 ) -> HandlerResult {
+    handler_called("source_handler");
+
     fsm.finish().await.map_err(Into::into)?;
 
-    bot.send(
+    bot.send_limited(
         SendMessage::new(
             message.chat.id(),
             format!(
@@ -6,19 +6,21 @@ use telers::{
     Bot,
 };
 
-use crate::texts::start_message;
+use crate::{bot_api::BotApi as _, logging::handler_called, texts::start_message};
 
 pub async fn start_handler<S: Storage>(
     bot: Bot,
     message: Message,
     fsm: Context<S>,
 ) -> HandlerResult {
+    handler_called("start_handler");
+
     fsm.finish().await.map_err(Into::into)?;
 
     // only can panic if messages uses in channels, but i'm using private filter in main function
     let user_first_name = &message.from().expect("error while parsing user").first_name;
 
-    bot.send(SendMessage::new(
+    bot.send_limited(SendMessage::new(
         message.chat().id(),
         start_message(user_first_name),
     ))
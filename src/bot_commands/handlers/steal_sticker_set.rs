@@ -1,42 +1,45 @@
 use telers::{
     enums::ParseMode,
-    errors::{session::ErrorKind, HandlerError, TelegramErrorKind},
+    errors::HandlerError,
     event::{telegram::HandlerResult, EventReturn},
     fsm::{Context, Storage},
-    methods::{CreateNewStickerSet, DeleteMessage, GetMe, GetStickerSet, SendMessage},
-    types::{InputFile, InputSticker, MessageSticker, MessageText},
+    methods::{DeleteMessage, GetStickerSet, SendMessage},
+    types::{MessageSticker, MessageText},
     utils::text::{html_bold, html_code, html_text_link},
     Bot,
 };
-use tracing::error;
+use tracing::{error, field::Empty, info, info_span, Instrument as _};
 
+use crate::texts::sticker_set_message;
 use crate::{
-    application::{
-        commands::create_set::create_set, common::traits::uow::UoWFactory as UoWFactoryTrait,
-        set::dto::create::Create as CreateSet,
-    },
+    application::common::traits::uow::UoWFactory as UoWFactoryTrait,
+    bot_api::BotApi as _,
     bot_commands::states::StealStickerSetState,
     core::stickers::constants::CREATE_SET_IN_ONE_GO_LENGTH_LIMIT,
-};
-use crate::{
-    common::{generate_sticker_set_name_and_link, sticker_format},
-    texts::sticker_set_message,
+    logging::handler_called,
+    metrics::METRICS,
+    middlewares::{Blocklist, QuotaKind, Quotas},
 };
 
-use super::common::add_stickers;
+use super::common::{
+    check_not_blocked, create_sticker_set, input_sticker, refund_quota, start_operation,
+    take_quota, AddedStickers, CreateStickerSetError,
+};
 
 pub async fn steal_sticker_set_handler<S: Storage>(
     bot: Bot,
     message: MessageText,
     fsm: Context<S>,
 ) -> HandlerResult {
+    handler_called("steal_sticker_set_handler");
+
     fsm.finish().await.map_err(Into::into)?;
 
     fsm.set_state(StealStickerSetState::StealStickerSetName)
         .await
         .map_err(Into::into)?;
 
-    bot.send(SendMessage::new(
+    bot.send_limited(SendMessage::new(
         message.chat.id(),
         "Send me a sticker and i will steal this sticker pack for you!",
     ))
@@ -49,11 +52,14 @@ pub async fn get_sticker_set_name<S: Storage>(
     bot: Bot,
     message: MessageSticker,
     fsm: Context<S>,
+    blocklist: Blocklist,
 ) -> HandlerResult {
+    handler_called("get_sticker_set_name");
+
     let set_name = match message.sticker.set_name {
         Some(sticker_set_name) => sticker_set_name,
         None => {
-            bot.send(SendMessage::new(
+            bot.send_limited(SendMessage::new(
                 message.chat.id(),
                 "This sticker is without sticker pack! Try to send another sticker pack.",
             ))
@@ -63,6 +69,10 @@ pub async fn get_sticker_set_name<S: Storage>(
         }
     };
 
+    if !check_not_blocked(&bot, &blocklist, message.chat.id(), &set_name, None).await? {
+        return Ok(EventReturn::Finish);
+    }
+
     fsm.set_value("steal_sticker_set_name", set_name.as_ref())
         .await
         .map_err(Into::into)?;
@@ -71,7 +81,7 @@ pub async fn get_sticker_set_name<S: Storage>(
         .await
         .map_err(Into::into)?;
 
-    bot.send(SendMessage::new(
+    bot.send_limited(SendMessage::new(
         message.chat.id(),
         "Now enter name for your new sticker pack (1-64 characters).",
     ))
@@ -87,14 +97,18 @@ pub async fn create_new_sticker_set<S, UoWFactory>(
     message: MessageText,
     fsm: Context<S>,
     uow_factory: UoWFactory,
+    quotas: Quotas,
+    blocklist: Blocklist,
 ) -> HandlerResult
 where
     UoWFactory: UoWFactoryTrait,
     S: Storage,
 {
+    handler_called("create_new_sticker_set");
+
     // if user enter wrong sticker set title, process it
     let new_set_title = if message.text.len() > 64 {
-        bot.send(SendMessage::new(
+        bot.send_limited(SendMessage::new(
             message.chat.id(),
             "Too long name for sticker pack! Try enter a name up to 64 characters long.",
         ))
@@ -102,7 +116,7 @@ where
 
         return Ok(EventReturn::Finish);
     } else if message.text.len() < 1 {
-        bot.send(SendMessage::new(
+        bot.send_limited(SendMessage::new(
             message.chat.id(),
             "Too short name! Try enter a name between 1 and 64 characters long.",
         ))
@@ -123,29 +137,50 @@ where
 
     fsm.finish().await.map_err(Into::into)?;
 
+    // sticker pack can be blocked after the user sent it
+    if !check_not_blocked(
+        &bot,
+        &blocklist,
+        message.chat.id(),
+        &steal_sticker_set_name,
+        None,
+    )
+    .await?
+    {
+        return Ok(EventReturn::Finish);
+    }
+
     let steal_sticker_set = bot
-        .send(GetStickerSet::new(steal_sticker_set_name.as_ref()))
+        .send_limited(GetStickerSet::new(steal_sticker_set_name.as_ref()))
         .await?;
 
     let steal_sticker_set_title = steal_sticker_set.title;
 
     let steal_stickers_from_sticker_set = steal_sticker_set.stickers;
 
-    // cant panic because bot cant be without username
-    let bot_username = bot
-        .send(GetMe::new())
-        .await?
-        .username
-        .expect("bot without username :/");
-
     // only panic if bot using in channels, but i'm using private filter in main function
     let user_id = message.from.expect("user without id").id;
 
-    // prepare name for new sticker set and link to use it in message later
-    let (mut new_set_name, mut new_set_link) =
-        generate_sticker_set_name_and_link(11, &bot_username);
+    let usage = [
+        (QuotaKind::Steal, 1),
+        (
+            QuotaKind::Sticker,
+            steal_stickers_from_sticker_set.len() as u32,
+        ),
+    ];
 
-    let message_delete = bot.send(SendMessage::new(
+    if !take_quota(&bot, &quotas, message.chat.id(), user_id, &usage).await? {
+        return Ok(EventReturn::Finish);
+    }
+
+    // shutdown waits for the steal, so the half-copied sticker pack isn't left
+    let Some(_operation) =
+        start_operation(&bot, &quotas, message.chat.id(), user_id, &usage).await?
+    else {
+        return Ok(EventReturn::Finish);
+    };
+
+    let message_delete = bot.send_limited(SendMessage::new(
         message.chat.id(),
         format!(
             "Stealing sticker pack with name `{new_set_title}` for you..\n(stealing sticker packs \
@@ -154,122 +189,106 @@ where
     ))
     .await?;
 
-    let (limit_sticker_set_length, more_than_limit) =
-        if steal_stickers_from_sticker_set.len() > CREATE_SET_IN_ONE_GO_LENGTH_LIMIT {
-            (CREATE_SET_IN_ONE_GO_LENGTH_LIMIT, true)
-        } else {
-            (steal_stickers_from_sticker_set.len(), false)
-        };
-
-    let mut sticker_formats = steal_stickers_from_sticker_set
-        .iter()
-        .map(|sticker| sticker_format(sticker));
-
-    while let Err(err) = bot
-        .send(CreateNewStickerSet::new(
-            user_id,
-            new_set_name.as_str(),
-            new_set_title.as_ref(),
-            steal_stickers_from_sticker_set
-                .iter()
-                .take(limit_sticker_set_length)
-                .map(|sticker| {
-                    let sticker_is: InputSticker = InputSticker::new(
-                        InputFile::id(sticker.file_id.as_ref()),
-                        sticker_formats.next().unwrap(),
-                    );
-
-                    sticker_is.emoji_list(sticker.emoji.clone())
-                }),
-        ))
-        .await
-    {
-        match err {
-            ErrorKind::Telegram(err) => {
-                if matches!(&err, TelegramErrorKind::BadRequest { message } if message.as_ref()
-                    == "Bad Request: SHORTNAME_OCCUPY_FAILED")
-                {
-                    error!(
-                        ?err,
-                        "file to create new sticker set; trying to generate sticker set name again:"
-                    );
-                    error!(new_set_name, "sticker set name:");
-
-                    (new_set_name, new_set_link) =
-                        generate_sticker_set_name_and_link(11, &bot_username);
-                } else {
-                    error!(?err, "error occureded while creating new sticker set:");
-                    error!(new_set_name, "sticker set name:");
-
-                    bot.send(SendMessage::new(
-                        message.chat.id(),
-                        "Error occurded while creating new sticker pack :(",
-                    ))
-                    .await?;
-
-                    return Ok(EventReturn::Finish);
-                }
-            }
-            err => {
-                error!(?err, "error occureded while creating new sticker set:");
-
-                bot.send(SendMessage::new(
-                    message.chat.id(),
-                    "Error occurded while creating new sticker pack :(",
-                ))
-                .await?;
-
-                return Ok(EventReturn::Finish);
-            }
-        }
-    }
-
     let mut uow = uow_factory.create_uow();
 
-    create_set(
+    let steal_span = info_span!(
+        "steal",
+        original_set_name = steal_sticker_set_name.as_ref(),
+        stickers = steal_stickers_from_sticker_set.len(),
+        set_name = Empty,
+    );
+
+    METRICS.steals_started.inc();
+    let _steal_timer = METRICS.steal_duration.start_timer();
+
+    let new_set = match create_sticker_set(
+        &bot,
         &mut uow,
-        CreateSet::new(user_id, new_set_name.as_str(), new_set_title.as_ref()),
+        user_id,
+        new_set_title.as_ref(),
+        steal_stickers_from_sticker_set
+            .iter()
+            .map(input_sticker)
+            .collect(),
     )
+    .instrument(steal_span.clone())
     .await
-    .map_err(HandlerError::new)?;
-
-    if more_than_limit {
-        let all_stickers_was_added = add_stickers(
-            &bot,
-            user_id,
-            new_set_name.as_ref(),
-            steal_stickers_from_sticker_set[limit_sticker_set_length..].as_ref(),
-        )
-        .await
-        .expect("empty stickers list");
+    {
+        Ok(new_set) => {
+            METRICS.steals_completed.inc();
+
+            steal_span.record("set_name", new_set.name.as_str());
+            info!(
+                parent: &steal_span,
+                added = ?new_set.added,
+                "Sticker set stolen"
+            );
+
+            new_set
+        }
+        Err(CreateStickerSetError::Telegram(err)) => {
+            METRICS.steals_failed.inc();
+
+            error!(parent: &steal_span, ?err, "An error occurded while steal sticker set:");
 
-        if !all_stickers_was_added {
-            bot.send(SendMessage::new(
+            refund_quota(&quotas, user_id, &usage).await;
+
+            bot.send_limited(SendMessage::new(
                 message.chat.id(),
-                format!(
-                    "Error occurded while creating new sticker pack {created_pack} (original {original_set}), {but_created}! \n\
-                    Due to an error, not all stickers have been stolen :( \
-                    (you can delete this sticker pack if you want using the /delpack command in official Telegram bot @Stickers. \
-                    Name of this sticker pack: {copy_set_name})",
-                    created_pack = html_text_link(new_set_title, new_set_link),
-                    original_set = html_text_link(steal_sticker_set_title, steal_sticker_set_link),
-                    but_created = html_bold("but sticker pack was created"),
-                    copy_set_name = html_code(new_set_name.as_str())
-                ),
-            ).parse_mode(ParseMode::HTML))
+                "Error occurded while creating new sticker pack :(",
+            ))
             .await?;
 
             return Ok(EventReturn::Finish);
         }
+        Err(err) => {
+            METRICS.steals_failed.inc();
+
+            error!(parent: &steal_span, ?err, "An error occurded while steal sticker set:");
+
+            refund_quota(&quotas, user_id, &usage).await;
+
+            return Err(HandlerError::new(err));
+        }
+    };
+
+    // the user is told about interrupted operation by shutdown
+    if new_set.added == AddedStickers::Cancelled {
+        bot.send_limited(DeleteMessage::new(
+            message_delete.chat().id(),
+            message_delete.id(),
+        ))
+        .await?;
+
+        return Ok(EventReturn::Finish);
     }
 
-    bot.send(
+    if new_set.added == AddedStickers::Partially {
+        bot.send_limited(SendMessage::new(
+            message.chat.id(),
+            format!(
+                "Error occurded while creating new sticker pack {created_pack} (original {original_set}), {but_created}! \n\
+                Due to an error, not all stickers have been stolen :( \
+                (you can delete this sticker pack if you want using the /delpack command in official Telegram bot @Stickers. \
+                Name of this sticker pack: {copy_set_name})",
+                created_pack = html_text_link(new_set_title, new_set.link),
+                original_set = html_text_link(steal_sticker_set_title, steal_sticker_set_link),
+                but_created = html_bold("but sticker pack was created"),
+                copy_set_name = html_code(new_set.name.as_str())
+            ),
+        ).parse_mode(ParseMode::HTML))
+        .await?;
+
+        return Ok(EventReturn::Finish);
+    }
+
+    bot.send_limited(
         SendMessage::new(
             message.chat.id(),
             sticker_set_message(
                 &new_set_title,
-                &new_set_name,
-                &new_set_link,
+                &new_set.name,
+                &new_set.link,
                 &steal_sticker_set_title,
                 &steal_sticker_set_link,
             ),
@@ -279,7 +298,7 @@ where
     .await?;
 
     // delete unnecessary message
-    bot.send(DeleteMessage::new(
+    bot.send_limited(DeleteMessage::new(
         message_delete.chat().id(),
         message_delete.id(),
     ))
@@ -287,3 +306,61 @@ where
 
     Ok(EventReturn::Finish)
 }
+
+#[tokio::test]
+async fn create_new_sticker_set_test() {
+    use serde_json::json;
+
+    use crate::{
+        application::set::{dto::get_by_tg_id::GetByTgID, traits::SetRepo as _},
+        fake_bot_api::{message_update, sticker, sticker_set, TestBot, TEST_BOT_USERNAME},
+        infrastructure::database::repositories::set::SetRepoImpl,
+    };
+
+    let test_bot = TestBot::new().await;
+
+    test_bot.api.add_sticker_set(sticker_set(
+        "original",
+        "Original",
+        (0..3)
+            .map(|i| sticker("original", &format!("file_id_{i}"), "😀"))
+            .collect(),
+    ));
+
+    test_bot
+        .feed_update(message_update(1, 1, json!({ "text": "/stealpack" })))
+        .await;
+    test_bot
+        .feed_update(message_update(
+            2,
+            1,
+            json!({ "sticker": sticker("original", "file_id_0", "😀") }),
+        ))
+        .await;
+    test_bot
+        .feed_update(message_update(3, 1, json!({ "text": "My pack" })))
+        .await;
+
+    let created = test_bot.api.calls_of("createNewStickerSet");
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0]["user_id"], 1);
+    assert_eq!(created[0]["title"], "My pack");
+    assert_eq!(created[0]["stickers"].as_array().unwrap().len(), 3);
+
+    let new_set_name = created[0]["name"].as_str().unwrap();
+    assert!(new_set_name.ends_with(&format!("_by_{TEST_BOT_USERNAME}")));
+    assert!(test_bot.api.sticker_set(new_set_name).is_some());
+
+    let mut conn = test_bot.pool.acquire().await.unwrap();
+    let sets = SetRepoImpl::new(&mut *conn)
+        .get_by_tg_id(GetByTgID::new(1, None))
+        .await
+        .unwrap();
+
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].short_name, new_set_name);
+    assert_eq!(sets[0].title, "My pack");
+
+    // message about stealing is deleted after sticker set is created
+    assert_eq!(test_bot.api.calls_of("deleteMessage").len(), 1);
+}
@@ -1,7 +1,15 @@
 pub mod add_stickers;
+pub mod download;
+pub mod from_archive;
+pub mod import;
 pub mod my_stickers;
+pub mod protect;
 pub mod steal_sticker_set;
 
 pub use add_stickers::AddStickerState;
+pub use download::DownloadState;
+pub use from_archive::FromArchiveState;
+pub use import::ImportState;
 pub use my_stickers::MyStickersState;
+pub use protect::ProtectState;
 pub use steal_sticker_set::StealStickerSetState;
@@ -0,0 +1,26 @@
+use std::borrow::Cow;
+
+#[derive(Clone)]
+pub enum DownloadState {
+    GetStickerSetToDownload,
+}
+
+impl DownloadState {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            DownloadState::GetStickerSetToDownload => "get_sticker_set_to_download",
+        }
+    }
+}
+
+impl From<DownloadState> for Cow<'static, str> {
+    fn from(state: DownloadState) -> Self {
+        Cow::Borrowed(state.as_str())
+    }
+}
+
+impl PartialEq<&str> for DownloadState {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
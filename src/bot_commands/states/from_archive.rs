@@ -0,0 +1,28 @@
+use std::borrow::Cow;
+
+#[derive(Clone)]
+pub enum FromArchiveState {
+    GetNewStickerSetTitle,
+    GetArchive,
+}
+
+impl FromArchiveState {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            FromArchiveState::GetNewStickerSetTitle => "get_new_sticker_set_title",
+            FromArchiveState::GetArchive => "get_archive",
+        }
+    }
+}
+
+impl From<FromArchiveState> for Cow<'static, str> {
+    fn from(state: FromArchiveState) -> Self {
+        Cow::Borrowed(state.as_str())
+    }
+}
+
+impl PartialEq<&str> for FromArchiveState {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
@@ -0,0 +1,26 @@
+use std::borrow::Cow;
+
+#[derive(Clone)]
+pub enum ImportState {
+    GetImportFile,
+}
+
+impl ImportState {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            ImportState::GetImportFile => "get_import_file",
+        }
+    }
+}
+
+impl From<ImportState> for Cow<'static, str> {
+    fn from(state: ImportState) -> Self {
+        Cow::Borrowed(state.as_str())
+    }
+}
+
+impl PartialEq<&str> for ImportState {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
@@ -0,0 +1,28 @@
+use std::borrow::Cow;
+
+#[derive(Clone)]
+pub enum ProtectState {
+    GetStickerToProtect,
+    ChooseProtectedSets,
+}
+
+impl ProtectState {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            ProtectState::GetStickerToProtect => "get_sticker_to_protect",
+            ProtectState::ChooseProtectedSets => "choose_protected_sets",
+        }
+    }
+}
+
+impl From<ProtectState> for Cow<'static, str> {
+    fn from(state: ProtectState) -> Self {
+        Cow::Borrowed(state.as_str())
+    }
+}
+
+impl PartialEq<&str> for ProtectState {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
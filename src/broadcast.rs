@@ -0,0 +1,196 @@
+//! Messages of the admins, which are sent to every user of the bot.
+//! Progress of the broadcast is kept in the database, so it continues after restart of the bot.
+
+use std::sync::Arc;
+
+use telers::{
+    errors::{session::ErrorKind, TelegramErrorKind},
+    methods::{CopyMessage, SendMessage},
+    Bot, FromContext,
+};
+use tracing::{debug, error, info, info_span, Instrument as _};
+
+use crate::{
+    bot_api::BotApi as _,
+    infrastructure::database::broadcasts::{Broadcast, BroadcastsPool},
+    texts::broadcast_finished_message,
+};
+
+/// Number of users, which are read from the database at once
+const USERS_BATCH_SIZE: u64 = 100;
+
+/// Broadcasts of the bot, admin handlers get it from context to start them
+#[derive(Clone, FromContext)]
+#[context(key = "broadcasts")]
+pub struct Broadcasts {
+    pool: Arc<dyn BroadcastsPool>,
+}
+
+impl Broadcasts {
+    pub fn new(pool: impl BroadcastsPool) -> Self {
+        Self {
+            pool: Arc::new(pool),
+        }
+    }
+
+    /// Create broadcast of the message from the chat of the admin and send it in background.
+    /// Return `false`, if the message is already broadcast.
+    pub async fn start(
+        &self,
+        bot: Bot,
+        chat_id: i64,
+        message_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let Some(broadcast) = self.pool.create(chat_id, message_id).await? else {
+            return Ok(false);
+        };
+
+        self.spawn(bot, broadcast);
+
+        Ok(true)
+    }
+
+    /// Continue broadcasts, which were interrupted by restart of the bot
+    pub async fn resume(&self, bot: &Bot) -> Result<(), sqlx::Error> {
+        for broadcast in self.pool.unfinished().await? {
+            info!(
+                id = broadcast.id,
+                last_tg_id = broadcast.last_tg_id,
+                "Broadcast is resumed"
+            );
+
+            self.spawn(bot.clone(), broadcast);
+        }
+
+        Ok(())
+    }
+
+    fn spawn(&self, bot: Bot, broadcast: Broadcast) {
+        let pool = self.pool.clone();
+        let span = info_span!("broadcast", id = broadcast.id);
+
+        tokio::spawn(
+            async move {
+                if let Err(err) = run_broadcast(&bot, pool.as_ref(), broadcast).await {
+                    error!(?err, "An error occurded while broadcast message:");
+                }
+            }
+            .instrument(span),
+        );
+    }
+}
+
+/// Copy the message to every user after the cursor, saving progress after every user,
+/// and report counters to the admin at the end
+async fn run_broadcast(
+    bot: &Bot,
+    pool: &dyn BroadcastsPool,
+    mut broadcast: Broadcast,
+) -> Result<(), sqlx::Error> {
+    loop {
+        let users = pool
+            .users_after(broadcast.last_tg_id, USERS_BATCH_SIZE)
+            .await?;
+
+        if users.is_empty() {
+            break;
+        }
+
+        for user_id in users {
+            match bot
+                .send_limited(CopyMessage::new(
+                    user_id,
+                    broadcast.chat_id,
+                    broadcast.message_id,
+                ))
+                .await
+            {
+                Ok(_) => broadcast.delivered += 1,
+                Err(ErrorKind::Telegram(TelegramErrorKind::Forbidden { .. })) => {
+                    broadcast.blocked += 1;
+
+                    pool.set_inactive(user_id).await?;
+                }
+                Err(err) => {
+                    broadcast.failed += 1;
+
+                    debug!(?err, user_id, "Message is not delivered to the user:");
+                }
+            }
+
+            broadcast.last_tg_id = user_id;
+
+            pool.save(&broadcast, false).await?;
+        }
+    }
+
+    pool.save(&broadcast, true).await?;
+
+    info!(
+        delivered = broadcast.delivered,
+        failed = broadcast.failed,
+        blocked = broadcast.blocked,
+        "Broadcast is finished"
+    );
+
+    if let Err(err) = bot
+        .send_limited(SendMessage::new(
+            broadcast.chat_id,
+            broadcast_finished_message(broadcast.delivered, broadcast.failed, broadcast.blocked),
+        ))
+        .await
+    {
+        error!(
+            ?err,
+            "An error occurded while send report of the broadcast:"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn run_broadcast_test() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::{fake_bot_api::FakeBotApi, infrastructure::database::SQLITE_MIGRATOR};
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    SQLITE_MIGRATOR.run(&pool).await.unwrap();
+
+    sqlx::query("INSERT INTO users (tg_id) VALUES (1), (2), (3), (4)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let api = FakeBotApi::start().await;
+    api.block(3);
+
+    // broadcast was interrupted by restart after the first user
+    let mut broadcast = pool.create(1, 10).await.unwrap().unwrap();
+    broadcast.last_tg_id = 1;
+    broadcast.delivered = 1;
+    pool.save(&broadcast, false).await.unwrap();
+
+    run_broadcast(&api.bot(), &pool, broadcast).await.unwrap();
+
+    let copied: Vec<i64> = api
+        .calls_of("copyMessage")
+        .iter()
+        .map(|params| params["chat_id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(copied, [2, 3, 4]);
+
+    assert_eq!(api.sent_texts(), [broadcast_finished_message(3, 0, 1)]);
+    assert_eq!(pool.unfinished().await.unwrap(), []);
+
+    let active: Vec<bool> = sqlx::query_scalar("SELECT active FROM users ORDER BY tg_id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(active, [true, true, false, true]);
+}
@@ -1,25 +1,84 @@
 use serde::Deserialize;
 
+mod load;
+
+pub use load::{load_config, ConfigError, ConfigProblem, DEFAULT_CONFIG_PATH, ENV_PREFIX};
+
 #[derive(Deserialize, Clone)]
 pub struct ConfigToml {
+    /// Users, who can use admin commands (`/stats`, `/user`, `/ban`, `/unban`, `/broadcast`,
+    /// `/block` and `/unblock`)
+    #[serde(default)]
+    pub admin_ids: Vec<i64>,
     pub bot: BotConfig,
     pub tg_app: Application,
     pub auth: AuthCredentials,
     pub tracing: Tracing,
-    pub postgres: DatabaseConfig,
+    /// Required only for `postgres` database backend
+    pub postgres: Option<DatabaseConfig>,
+    #[serde(default)]
+    pub database: Database,
+    #[serde(default)]
+    pub fsm: FsmConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub quotas: QuotasConfig,
+    /// If specified, bot receives updates using webhook instead of long polling
+    pub webhook: Option<WebhookConfig>,
+    /// If specified, Prometheus metrics are served on `/metrics` path
+    pub metrics: Option<MetricsConfig>,
+    /// If specified, liveness and readiness probes are served on `/healthz` and `/readyz` paths
+    pub health: Option<HealthConfig>,
+    /// If specified, traces are exported to OpenTelemetry collector using OTLP
+    pub telemetry: Option<TelemetryConfig>,
 }
 
 impl ConfigToml {
-    pub fn get_postgres_url(&self) -> String {
-        let postgres = &self.postgres;
+    /// Return `None`, if `[postgres]` section is not specified
+    pub fn get_postgres_url(&self) -> Option<String> {
+        let postgres = self.postgres.as_ref()?;
 
-        format!(
+        Some(format!(
             "postgres://{}:{}@{}:{}/{}",
             postgres.username, postgres.password, postgres.host, postgres.port, postgres.db
-        )
+        ))
     }
 }
 
+#[derive(Deserialize, Clone)]
+pub struct Database {
+    #[serde(default)]
+    pub backend: DatabaseBackend,
+    /// Path to the database file (only for `sqlite` backend)
+    #[serde(default = "default_sqlite_path")]
+    pub sqlite_path: String,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            backend: DatabaseBackend::default(),
+            sqlite_path: default_sqlite_path(),
+        }
+    }
+}
+
+fn default_sqlite_path() -> String {
+    "configs/steal_stickers_bot.sqlite".to_owned()
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseBackend {
+    #[default]
+    Postgres,
+    /// Database in the single file, which doesn't require running database server
+    Sqlite,
+}
+
 #[derive(Deserialize, Clone)]
 pub struct DatabaseConfig {
     pub username: String,
@@ -49,4 +108,242 @@ pub struct AuthCredentials {
 #[derive(Deserialize, Clone)]
 pub struct Tracing {
     pub log_level: String,
+    #[serde(default)]
+    pub format: LogFormat,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Plain,
+    /// One JSON object per line with fields of the current span
+    Json,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct FsmConfig {
+    #[serde(default)]
+    pub storage: FsmStorageKind,
+    /// URL of Redis server (only for `redis` storage)
+    pub redis_url: Option<String>,
+    /// Prefix of the keys in Redis (only for `redis` storage)
+    #[serde(default = "default_fsm_prefix")]
+    pub prefix: String,
+    /// Time in seconds, after which unchanged states and data are removed (only for `redis` storage)
+    pub ttl: Option<u64>,
+}
+
+impl Default for FsmConfig {
+    fn default() -> Self {
+        Self {
+            storage: FsmStorageKind::default(),
+            redis_url: None,
+            prefix: default_fsm_prefix(),
+            ttl: None,
+        }
+    }
+}
+
+fn default_fsm_prefix() -> String {
+    "steal_stickers_bot".to_owned()
+}
+
+/// Where states of users and their data are kept
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum FsmStorageKind {
+    /// States are lost after restart of the bot
+    Memory,
+    /// States are kept in the `fsm_states` table of the bot database
+    #[default]
+    #[serde(alias = "postgres")]
+    Database,
+    /// States are kept in Redis and can be shared between several replicas of the bot
+    /// (requires `redis-storage` feature)
+    Redis,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ShutdownConfig {
+    /// Time in seconds, during which running steals and adding of stickers can finish after SIGTERM or SIGINT
+    #[serde(default = "default_shutdown_deadline")]
+    pub deadline: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            deadline: default_shutdown_deadline(),
+        }
+    }
+}
+
+fn default_shutdown_deadline() -> u64 {
+    25
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RateLimitConfig {
+    /// Requests per second to Bot API from the whole bot
+    #[serde(default = "default_global_rate")]
+    pub global_rate: f64,
+    /// Requests per second into one chat (or with sticker sets of one user)
+    #[serde(default = "default_chat_rate")]
+    pub chat_rate: f64,
+    /// Requests into one chat, which can be sent at once before `chat_rate` is applied
+    #[serde(default = "default_chat_burst")]
+    pub chat_burst: u32,
+    /// Number of retries of the request, after which Telegram answered `Too Many Requests`
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            global_rate: default_global_rate(),
+            chat_rate: default_chat_rate(),
+            chat_burst: default_chat_burst(),
+            max_retries: default_max_retries(),
+        }
+    }
+}
+
+fn default_global_rate() -> f64 {
+    30.0
+}
+
+fn default_chat_rate() -> f64 {
+    1.0
+}
+
+fn default_chat_burst() -> u32 {
+    3
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+/// Limits of one user, `0` disables the limit
+#[derive(Deserialize, Clone)]
+pub struct QuotasConfig {
+    /// Messages (and other updates) per minute
+    #[serde(default = "default_messages_per_minute")]
+    pub messages_per_minute: u32,
+    /// Stolen sticker packs (including ones created from archive) per hour
+    #[serde(default = "default_steals_per_hour")]
+    pub steals_per_hour: u32,
+    /// Stolen sticker packs (including ones created from archive) per day
+    #[serde(default = "default_steals_per_day")]
+    pub steals_per_day: u32,
+    /// Stickers added to sticker packs (including stolen ones) per day
+    #[serde(default = "default_stickers_per_day")]
+    pub stickers_per_day: u32,
+}
+
+impl Default for QuotasConfig {
+    fn default() -> Self {
+        Self {
+            messages_per_minute: default_messages_per_minute(),
+            steals_per_hour: default_steals_per_hour(),
+            steals_per_day: default_steals_per_day(),
+            stickers_per_day: default_stickers_per_day(),
+        }
+    }
+}
+
+fn default_messages_per_minute() -> u32 {
+    30
+}
+
+fn default_steals_per_hour() -> u32 {
+    10
+}
+
+fn default_steals_per_day() -> u32 {
+    50
+}
+
+fn default_stickers_per_day() -> u32 {
+    3000
+}
+
+#[derive(Deserialize, Clone)]
+pub struct WebhookConfig {
+    /// Public HTTPS URL, to which Telegram sends updates (TLS can be terminated by reverse proxy)
+    pub url: String,
+    /// Address, on which server listens for updates
+    #[serde(default = "default_webhook_address")]
+    pub address: String,
+    /// Path of the URL, on which server accepts updates
+    #[serde(default = "default_webhook_path")]
+    pub path: String,
+    /// Telegram sends this token in `X-Telegram-Bot-Api-Secret-Token` header of every request
+    /// (1-256 characters, only `A-Z`, `a-z`, `0-9`, `_` and `-` are allowed)
+    pub secret_token: String,
+}
+
+fn default_webhook_address() -> String {
+    "0.0.0.0:8080".to_owned()
+}
+
+fn default_webhook_path() -> String {
+    "/webhook".to_owned()
+}
+
+#[derive(Deserialize, Clone)]
+pub struct MetricsConfig {
+    /// Address, on which server listens for scrape requests
+    #[serde(default = "default_metrics_address")]
+    pub address: String,
+}
+
+fn default_metrics_address() -> String {
+    "0.0.0.0:9090".to_owned()
+}
+
+#[derive(Deserialize, Clone)]
+pub struct HealthConfig {
+    /// Address, on which server listens for probes
+    #[serde(default = "default_health_address")]
+    pub address: String,
+    /// Time in seconds, after which bot isn't ready, if `getUpdates` didn't succeed (only for long polling)
+    #[serde(default = "default_max_poll_age")]
+    pub max_poll_age: u64,
+}
+
+fn default_health_address() -> String {
+    "0.0.0.0:8081".to_owned()
+}
+
+fn default_max_poll_age() -> u64 {
+    60
+}
+
+#[derive(Deserialize, Clone)]
+pub struct TelemetryConfig {
+    /// gRPC endpoint of OpenTelemetry collector
+    #[serde(default = "default_telemetry_endpoint")]
+    pub endpoint: String,
+    /// Value of `service.name` resource attribute of the exported spans
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    /// Part of the traces, which are exported (from 0.0 to 1.0)
+    #[serde(default = "default_sample_ratio")]
+    pub sample_ratio: f64,
+}
+
+fn default_telemetry_endpoint() -> String {
+    "http://localhost:4317".to_owned()
+}
+
+fn default_service_name() -> String {
+    "steal_stickers_bot".to_owned()
+}
+
+fn default_sample_ratio() -> f64 {
+    1.0
 }
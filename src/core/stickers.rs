@@ -1,3 +1,8 @@
+pub mod archive;
+pub mod bundles;
 pub mod common;
 pub mod constants;
+pub mod export;
+pub mod normalize;
 pub mod texts;
+pub mod validation;
@@ -0,0 +1,718 @@
+use std::{
+    fmt,
+    io::{self, Cursor, Read as _, Seek, Write},
+};
+
+use serde::{Deserialize, Serialize};
+use zip::{result::ZipError, write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{
+    bundles::{is_wastickers, read_signal_bundle, read_wastickers, SignalManifest},
+    constants::MAX_STICKER_SET_LENGTH,
+    validation::{validate_sticker, ValidationError},
+};
+
+/// Name of the file with information about stickers in the archive
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Approximate size of zip headers for one file (local header, central directory header and data descriptor)
+const ZIP_ENTRY_OVERHEAD: usize = 128;
+
+/// Approximate size of zip end of central directory and other service data
+const ZIP_ARCHIVE_OVERHEAD: usize = 1024;
+
+/// Maximum size of one unpacked file from the archive sent by user (protects from zip bombs)
+const ARCHIVE_FILE_SIZE_LIMIT: u64 = 5 * 1024 * 1024;
+
+/// Maximum size of all unpacked files from the archive sent by user
+const ARCHIVE_SIZE_LIMIT: u64 = 100 * 1024 * 1024;
+
+/// Maximum number of files in the archive sent by user (stickers and service files like `manifest.json`)
+const ARCHIVE_FILES_LIMIT: usize = MAX_STICKER_SET_LENGTH + 10;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    Zip(#[from] ZipError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("sticker at position {position} is too large to fit into archive part")]
+    StickerTooLarge { position: usize },
+    #[error("archive doesn't contain stickers")]
+    NoStickers,
+    #[error("archive contains {number} stickers, but sticker pack can contain only {MAX_STICKER_SET_LENGTH}")]
+    TooManyStickers { number: usize },
+    #[error("archive contains more than {ARCHIVE_FILES_LIMIT} files")]
+    TooManyFiles,
+    #[error("unpacked archive is larger than {} MB", ARCHIVE_SIZE_LIMIT / 1024 / 1024)]
+    TooLarge,
+    #[error("file {file} is larger than {} MB", ARCHIVE_FILE_SIZE_LIMIT / 1024 / 1024)]
+    FileTooLarge { file: String },
+    #[error("archive contains invalid sticker files")]
+    InvalidFiles(Vec<InvalidFile>),
+}
+
+/// Sticker file from the archive, which can't be uploaded to Telegram
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidFile {
+    pub file: String,
+    pub reason: InvalidFileReason,
+}
+
+impl fmt::Display for InvalidFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.reason)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InvalidFileReason {
+    #[error("unsupported file type (only .webp, .png, .tgs and .webm are supported)")]
+    UnsupportedExtension,
+    #[error(transparent)]
+    Invalid(#[from] ValidationError),
+    #[error("file is specified in manifest.json, but not found in the archive")]
+    NotFound,
+    #[error("unknown sticker format \"{0}\" in manifest.json (only static, animated and video are supported)")]
+    UnknownFormat(String),
+    #[error("can't convert image into sticker ({0})")]
+    Image(String),
+}
+
+/// Sticker set read from the archive sent by user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveContent {
+    pub title: Option<String>,
+    pub stickers: Vec<ArchiveSticker>,
+}
+
+/// Sticker file, which will be placed into archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveSticker {
+    /// Position of the sticker in the sticker set (starting from 1)
+    pub position: usize,
+    pub emoji: Option<String>,
+    pub keywords: Vec<String>,
+    /// `static`, `animated` or `video` (same as returned by [`super::common::sticker_format`])
+    pub format: String,
+    /// Extension of the file, which matches content of `data` (static sticker can be `webp` or `png`)
+    pub extension: &'static str,
+    pub data: Vec<u8>,
+}
+
+impl ArchiveSticker {
+    pub fn file_name(&self) -> String {
+        sticker_file_name(self.position, self.emoji.as_deref(), self.extension)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub title: String,
+    pub stickers: Vec<ManifestSticker>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestSticker {
+    pub file: String,
+    pub emoji: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    pub format: String,
+}
+
+impl From<&ArchiveSticker> for ManifestSticker {
+    fn from(sticker: &ArchiveSticker) -> Self {
+        Self {
+            file: sticker.file_name(),
+            emoji: sticker.emoji.clone(),
+            keywords: sticker.keywords.clone(),
+            format: sticker.format.clone(),
+        }
+    }
+}
+
+/// Return file extension, which Telegram uses for stickers of the specified format.
+pub fn sticker_file_extension(format: &str) -> &'static str {
+    match format {
+        "animated" => "tgs",
+        "video" => "webm",
+        _ => "webp",
+    }
+}
+
+/// Return name of the sticker file in the archive. Example: `007_😎.webp`.
+pub fn sticker_file_name(position: usize, emoji: Option<&str>, extension: &str) -> String {
+    match emoji {
+        Some(emoji) => format!("{position:03}_{emoji}.{extension}"),
+        None => format!("{position:03}.{extension}"),
+    }
+}
+
+/// Pack stickers into one or more zip archives, each of which is not larger than `part_size_limit` bytes.
+/// Every archive contains its own `manifest.json` with the stickers placed in it.
+pub fn build_archives(
+    name: &str,
+    title: &str,
+    stickers: &[ArchiveSticker],
+    part_size_limit: usize,
+) -> Result<Vec<Vec<u8>>, ArchiveError> {
+    let mut writer =
+        ArchivesWriter::new(name, title, part_size_limit, || Ok(Cursor::new(Vec::new())));
+
+    for sticker in stickers {
+        writer.add(sticker)?;
+    }
+
+    Ok(writer
+        .finish()?
+        .into_iter()
+        .map(Cursor::into_inner)
+        .collect())
+}
+
+/// Same as [`build_archives`], but stickers are written one by one into archives created by `new_part`
+/// (e.g. temporary files), so the stickers and archives don't have to be kept in memory.
+pub struct ArchivesWriter<W: Write + Seek, F> {
+    name: String,
+    title: String,
+    part_size_limit: usize,
+    new_part: F,
+    part: Option<ArchivePart<W>>,
+    parts: Vec<W>,
+}
+
+struct ArchivePart<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    stickers: Vec<ManifestSticker>,
+    size: usize,
+}
+
+impl<W, F> ArchivesWriter<W, F>
+where
+    W: Write + Seek,
+    F: FnMut() -> io::Result<W>,
+{
+    pub fn new(name: &str, title: &str, part_size_limit: usize, new_part: F) -> Self {
+        Self {
+            name: name.to_owned(),
+            title: title.to_owned(),
+            part_size_limit,
+            new_part,
+            part: None,
+            parts: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, sticker: &ArchiveSticker) -> Result<(), ArchiveError> {
+        let sticker_size = estimated_entry_size(sticker);
+
+        if sticker_size + ZIP_ARCHIVE_OVERHEAD > self.part_size_limit {
+            return Err(ArchiveError::StickerTooLarge {
+                position: sticker.position,
+            });
+        }
+
+        if let Some(part) = &self.part {
+            if part.size + sticker_size > self.part_size_limit {
+                self.finish_part()?;
+            }
+        }
+
+        let mut part = match self.part.take() {
+            Some(part) => part,
+            None => self.new_part()?,
+        };
+
+        // sticker files are already compressed, so there is no point to compress them again
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        part.zip.start_file(sticker.file_name(), stored)?;
+        part.zip.write_all(&sticker.data)?;
+        part.stickers.push(sticker.into());
+        part.size += sticker_size;
+
+        self.part = Some(part);
+
+        Ok(())
+    }
+
+    /// Write manifests and return archives. Without stickers one archive with empty manifest is returned.
+    pub fn finish(mut self) -> Result<Vec<W>, ArchiveError> {
+        if self.part.is_none() && self.parts.is_empty() {
+            self.part = Some(self.new_part()?);
+        }
+
+        self.finish_part()?;
+
+        Ok(self.parts)
+    }
+
+    fn new_part(&mut self) -> Result<ArchivePart<W>, ArchiveError> {
+        Ok(ArchivePart {
+            zip: ZipWriter::new((self.new_part)()?),
+            stickers: Vec::new(),
+            size: ZIP_ARCHIVE_OVERHEAD,
+        })
+    }
+
+    fn finish_part(&mut self) -> Result<(), ArchiveError> {
+        let Some(mut part) = self.part.take() else {
+            return Ok(());
+        };
+
+        let manifest = Manifest {
+            name: self.name.clone(),
+            title: self.title.clone(),
+            stickers: part.stickers,
+        };
+
+        part.zip.start_file(
+            MANIFEST_FILE_NAME,
+            SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+        )?;
+        part.zip
+            .write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+        self.parts.push(part.zip.finish()?);
+
+        Ok(())
+    }
+}
+
+/// Estimated size of the sticker file in the archive, including its headers and record in manifest
+fn estimated_entry_size(sticker: &ArchiveSticker) -> usize {
+    let file_name_len = sticker.file_name().len();
+    let keywords_len: usize = sticker
+        .keywords
+        .iter()
+        .map(|keyword| keyword.len() + 4)
+        .sum();
+
+    sticker.data.len() + ZIP_ENTRY_OVERHEAD + file_name_len * 3 + keywords_len
+}
+
+/// Return extension of the sticker file, if it's supported.
+pub fn sticker_file_extension_by_name(file_name: &str) -> Option<&'static str> {
+    let (_, extension) = file_name.rsplit_once('.')?;
+
+    match extension.to_lowercase().as_str() {
+        "webp" => Some("webp"),
+        "png" => Some("png"),
+        "tgs" => Some("tgs"),
+        "webm" => Some("webm"),
+        _ => None,
+    }
+}
+
+/// Return format of the sticker from `manifest.json`, if it's supported by Telegram
+fn parse_sticker_format(format: &str) -> Option<&'static str> {
+    match format {
+        "static" => Some("static"),
+        "animated" => Some("animated"),
+        "video" => Some("video"),
+        _ => None,
+    }
+}
+
+/// Return sticker format (`static`, `animated` or `video`) by the file extension.
+pub fn sticker_format_by_file_name(file_name: &str) -> Option<&'static str> {
+    match sticker_file_extension_by_name(file_name)? {
+        "tgs" => Some("animated"),
+        "webm" => Some("video"),
+        _ => Some("static"),
+    }
+}
+
+/// Read all files from the zip archive. Directories are skipped and names of the files
+/// are returned without directories, so archives with root folder are also supported.
+/// Archives with too many or too large files are rejected.
+pub fn read_zip_files(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>, ArchiveError> {
+    let mut zip = ZipArchive::new(Cursor::new(data))?;
+
+    let mut files = Vec::with_capacity(zip.len().min(ARCHIVE_FILES_LIMIT));
+    let mut total_size = 0;
+
+    for index in 0..zip.len() {
+        let file = zip.by_index(index)?;
+
+        if file.is_dir() {
+            continue;
+        }
+
+        let name = match file.name().rsplit_once('/') {
+            Some((_, name)) => name.to_owned(),
+            None => file.name().to_owned(),
+        };
+
+        // skip service files of macOS and other hidden files
+        if name.is_empty() || name.starts_with('.') {
+            continue;
+        }
+
+        if files.len() == ARCHIVE_FILES_LIMIT {
+            return Err(ArchiveError::TooManyFiles);
+        }
+
+        // size in headers can be faked, so only read bytes are counted
+        let mut content = Vec::new();
+        file.take(ARCHIVE_FILE_SIZE_LIMIT + 1)
+            .read_to_end(&mut content)?;
+
+        if content.len() as u64 > ARCHIVE_FILE_SIZE_LIMIT {
+            return Err(ArchiveError::FileTooLarge { file: name });
+        }
+
+        total_size += content.len() as u64;
+        if total_size > ARCHIVE_SIZE_LIMIT {
+            return Err(ArchiveError::TooLarge);
+        }
+
+        files.push((name, content));
+    }
+
+    Ok(files)
+}
+
+/// Read stickers from the archive sent by user. Supported archives:
+/// - archive created using `/download` command (or archive with the same structure). If archive contains
+///   `manifest.json`, order, emoji and keywords of stickers are taken from it, otherwise stickers are sorted
+///   by file name, and emoji is taken from file name (`<position>_<emoji>.<extension>`);
+/// - WhatsApp `.wastickers` archive (more about it in [`super::bundles::read_wastickers`]);
+/// - Signal-style sticker bundle (more about it in [`super::bundles::read_signal_bundle`]).
+pub fn read_archive(data: &[u8]) -> Result<ArchiveContent, ArchiveError> {
+    let mut files = read_zip_files(data)?;
+
+    let (content, mut invalid_files) = match take_file(&mut files, MANIFEST_FILE_NAME) {
+        Some(manifest) => match serde_json::from_slice::<Manifest>(&manifest) {
+            Ok(manifest) => read_telegram_archive(files, Some(manifest)),
+            Err(err) => match serde_json::from_slice::<SignalManifest>(&manifest) {
+                Ok(manifest) => read_signal_bundle(files, manifest),
+                Err(_) => return Err(err.into()),
+            },
+        },
+        None if is_wastickers(&files) => read_wastickers(files),
+        None => read_telegram_archive(files, None),
+    };
+
+    for sticker in &content.stickers {
+        if let Err(err) = validate_sticker(&sticker.data, &sticker.format) {
+            invalid_files.push(InvalidFile {
+                file: sticker.file_name(),
+                reason: err.into(),
+            });
+        }
+    }
+
+    if !invalid_files.is_empty() {
+        return Err(ArchiveError::InvalidFiles(invalid_files));
+    }
+    if content.stickers.is_empty() {
+        return Err(ArchiveError::NoStickers);
+    }
+    if content.stickers.len() > MAX_STICKER_SET_LENGTH {
+        return Err(ArchiveError::TooManyStickers {
+            number: content.stickers.len(),
+        });
+    }
+
+    Ok(content)
+}
+
+/// Remove file with the specified name from the list of files and return its content
+pub(super) fn take_file(files: &mut Vec<(String, Vec<u8>)>, file_name: &str) -> Option<Vec<u8>> {
+    files
+        .iter()
+        .position(|(name, _)| name == file_name)
+        .map(|index| files.remove(index).1)
+}
+
+fn read_telegram_archive(
+    mut files: Vec<(String, Vec<u8>)>,
+    manifest: Option<Manifest>,
+) -> (ArchiveContent, Vec<InvalidFile>) {
+    let mut invalid_files = Vec::new();
+
+    let stickers = match manifest {
+        Some(ref manifest) => manifest
+            .stickers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, manifest_sticker)| {
+                let Some(format) = parse_sticker_format(&manifest_sticker.format) else {
+                    invalid_files.push(InvalidFile {
+                        file: manifest_sticker.file.clone(),
+                        reason: InvalidFileReason::UnknownFormat(manifest_sticker.format.clone()),
+                    });
+
+                    return None;
+                };
+
+                let data = match take_file(&mut files, &manifest_sticker.file) {
+                    Some(data) => data,
+                    None => {
+                        invalid_files.push(InvalidFile {
+                            file: manifest_sticker.file.clone(),
+                            reason: InvalidFileReason::NotFound,
+                        });
+
+                        return None;
+                    }
+                };
+
+                Some(ArchiveSticker {
+                    position: index + 1,
+                    emoji: manifest_sticker.emoji.clone(),
+                    keywords: manifest_sticker.keywords.clone(),
+                    format: format.to_owned(),
+                    extension: sticker_file_extension_by_name(&manifest_sticker.file)
+                        .unwrap_or_else(|| sticker_file_extension(format)),
+                    data,
+                })
+            })
+            .collect(),
+        None => {
+            files.sort_by(|(first, _), (second, _)| first.cmp(second));
+
+            files
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, (name, data))| {
+                    let format = match sticker_format_by_file_name(&name) {
+                        Some(format) => format,
+                        None => {
+                            invalid_files.push(InvalidFile {
+                                file: name,
+                                reason: InvalidFileReason::UnsupportedExtension,
+                            });
+
+                            return None;
+                        }
+                    };
+
+                    Some(ArchiveSticker {
+                        position: index + 1,
+                        emoji: emoji_from_file_name(&name),
+                        keywords: Vec::new(),
+                        format: format.to_owned(),
+                        extension: sticker_file_extension_by_name(&name)
+                            .unwrap_or_else(|| sticker_file_extension(format)),
+                        data,
+                    })
+                })
+                .collect()
+        }
+    };
+
+    (
+        ArchiveContent {
+            title: manifest.map(|manifest| manifest.title),
+            stickers,
+        },
+        invalid_files,
+    )
+}
+
+/// Return emoji from the file name like `007_😎.webp`
+fn emoji_from_file_name(file_name: &str) -> Option<String> {
+    let (stem, _) = file_name.rsplit_once('.')?;
+    let (_, emoji) = stem.split_once('_')?;
+
+    if emoji.is_empty() || emoji.is_ascii() {
+        return None;
+    }
+
+    Some(emoji.to_owned())
+}
+
+#[cfg(test)]
+fn test_sticker(position: usize, size: usize) -> ArchiveSticker {
+    ArchiveSticker {
+        position,
+        emoji: Some("😎".to_owned()),
+        keywords: vec!["cool".to_owned()],
+        format: "static".to_owned(),
+        extension: "webp",
+        data: vec![position as u8; size],
+    }
+}
+
+#[test]
+fn sticker_file_name_test() {
+    assert_eq!(sticker_file_name(7, Some("😎"), "webp"), "007_😎.webp");
+    assert_eq!(
+        sticker_file_name(12, None, sticker_file_extension("animated")),
+        "012.tgs"
+    );
+    assert_eq!(sticker_file_name(120, Some("🔥"), "png"), "120_🔥.png");
+    assert_eq!(sticker_file_extension_by_name("001_😎.PNG"), Some("png"));
+}
+
+#[test]
+fn build_archives_test() {
+    let stickers: Vec<ArchiveSticker> = (1..=10).map(|i| test_sticker(i, 1000)).collect();
+
+    let archives = build_archives("name", "title", &stickers, 50 * 1024 * 1024).unwrap();
+
+    assert_eq!(archives.len(), 1);
+
+    let archive = zip::ZipArchive::new(Cursor::new(&archives[0])).unwrap();
+
+    assert_eq!(archive.len(), 11);
+    assert!(archive.file_names().any(|name| name == MANIFEST_FILE_NAME));
+}
+
+#[test]
+fn build_archives_split_test() {
+    let stickers: Vec<ArchiveSticker> = (1..=10).map(|i| test_sticker(i, 1000)).collect();
+
+    let archives = build_archives("name", "title", &stickers, 4000).unwrap();
+
+    assert!(archives.len() > 1);
+    assert!(archives.iter().all(|archive| archive.len() <= 4000));
+
+    let stickers_number: usize = archives
+        .iter()
+        .map(|archive| zip::ZipArchive::new(Cursor::new(archive)).unwrap().len() - 1)
+        .sum();
+
+    assert_eq!(stickers_number, 10);
+
+    assert!(matches!(
+        build_archives("name", "title", &[test_sticker(1, 5000)], 4000),
+        Err(ArchiveError::StickerTooLarge { position: 1 })
+    ));
+}
+
+#[test]
+fn read_archive_test() {
+    use super::validation::test_png;
+
+    let stickers: Vec<ArchiveSticker> = (1..=3)
+        .map(|i| ArchiveSticker {
+            data: test_png(512, 512),
+            ..test_sticker(i, 0)
+        })
+        .collect();
+
+    let archives = build_archives("name", "title", &stickers, 50 * 1024 * 1024).unwrap();
+
+    let content = read_archive(&archives[0]).unwrap();
+
+    assert_eq!(content.title.as_deref(), Some("title"));
+    assert_eq!(content.stickers, stickers);
+
+    let unknown_format = ArchiveSticker {
+        format: "gif".to_owned(),
+        ..stickers[0].clone()
+    };
+    let archives = build_archives("name", "title", &[unknown_format], 50 * 1024 * 1024).unwrap();
+
+    match read_archive(&archives[0]) {
+        Err(ArchiveError::InvalidFiles(files)) => assert_eq!(
+            files,
+            vec![InvalidFile {
+                file: "001_😎.webp".to_owned(),
+                reason: InvalidFileReason::UnknownFormat("gif".to_owned())
+            }]
+        ),
+        result => panic!("unexpected result: {result:?}"),
+    }
+}
+
+#[test]
+fn read_archive_without_manifest_test() {
+    use super::validation::{test_png, test_tgs};
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (name, data) in [
+        ("pack/002.tgs", test_tgs(60, 180)),
+        ("pack/001_😎.webp", test_png(512, 512)),
+        ("pack/readme.txt", b"data".to_vec()),
+    ] {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(&data).unwrap();
+    }
+
+    let archive = zip.finish().unwrap().into_inner();
+
+    match read_archive(&archive) {
+        Err(ArchiveError::InvalidFiles(files)) => assert_eq!(
+            files,
+            vec![InvalidFile {
+                file: "readme.txt".to_owned(),
+                reason: InvalidFileReason::UnsupportedExtension
+            }]
+        ),
+        result => panic!("unexpected result: {result:?}"),
+    }
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (name, data) in [
+        ("pack/002.tgs", test_tgs(60, 180)),
+        ("pack/001_😎.webp", test_png(512, 512)),
+    ] {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(&data).unwrap();
+    }
+
+    let content = read_archive(&zip.finish().unwrap().into_inner()).unwrap();
+
+    assert_eq!(content.title, None);
+    assert_eq!(content.stickers[0].emoji.as_deref(), Some("😎"));
+    assert_eq!(content.stickers[0].format, "static");
+    assert_eq!(content.stickers[0].file_name(), "001_😎.webp");
+    assert_eq!(content.stickers[1].emoji, None);
+    assert_eq!(content.stickers[1].format, "animated");
+}
+
+#[test]
+fn read_zip_files_limits_test() {
+    let zip_with = |files: &[(&str, usize)]| {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        for (name, size) in files {
+            zip.start_file(*name, stored).unwrap();
+            zip.write_all(&vec![0; *size]).unwrap();
+        }
+
+        zip.finish().unwrap().into_inner()
+    };
+
+    let names: Vec<String> = (0..=ARCHIVE_FILES_LIMIT)
+        .map(|i| format!("{i:03}.webp"))
+        .collect();
+    let files: Vec<(&str, usize)> = names.iter().map(|name| (name.as_str(), 1)).collect();
+
+    assert_eq!(
+        read_zip_files(&zip_with(&files[1..])).unwrap().len(),
+        ARCHIVE_FILES_LIMIT
+    );
+    assert!(matches!(
+        read_zip_files(&zip_with(&files)),
+        Err(ArchiveError::TooManyFiles)
+    ));
+
+    match read_zip_files(&zip_with(&[
+        ("001.webp", 1),
+        ("002.webp", ARCHIVE_FILE_SIZE_LIMIT as usize + 1),
+    ])) {
+        Err(ArchiveError::FileTooLarge { file }) => assert_eq!(file, "002.webp"),
+        result => panic!("unexpected result: {result:?}"),
+    }
+
+    let files: Vec<(&str, usize)> = names[..25]
+        .iter()
+        .map(|name| (name.as_str(), ARCHIVE_FILE_SIZE_LIMIT as usize))
+        .collect();
+    assert!(matches!(
+        read_zip_files(&zip_with(&files)),
+        Err(ArchiveError::TooLarge)
+    ));
+}
@@ -0,0 +1,221 @@
+use serde::Deserialize;
+
+use super::{
+    archive::{take_file, ArchiveContent, ArchiveSticker, InvalidFile, InvalidFileReason},
+    normalize::normalize_static_sticker,
+};
+
+/// Name of the file with sticker pack title in `.wastickers` archive
+pub const WASTICKERS_TITLE_FILE_NAME: &str = "title.txt";
+
+/// Name of the file with sticker pack author in `.wastickers` archive
+pub const WASTICKERS_AUTHOR_FILE_NAME: &str = "author.txt";
+
+/// Manifest of Signal-style sticker bundle
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SignalManifest {
+    pub title: String,
+    pub author: Option<String>,
+    pub stickers: Vec<SignalSticker>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SignalSticker {
+    #[serde(alias = "id")]
+    pub file: String,
+    pub emoji: Option<String>,
+}
+
+/// Check if files from the archive looks like WhatsApp `.wastickers` archive
+pub fn is_wastickers(files: &[(String, Vec<u8>)]) -> bool {
+    files
+        .iter()
+        .any(|(name, _)| name == WASTICKERS_TITLE_FILE_NAME || name == WASTICKERS_AUTHOR_FILE_NAME)
+}
+
+/// Read stickers from WhatsApp `.wastickers` archive. Archive contains `title.txt`, `author.txt`,
+/// tray icon (`.png`) and `.webp` stickers, which are sorted by number in file name. Emoji of the sticker
+/// can be placed into `.txt` file with the same name as sticker. All stickers are converted into 512 px
+/// static stickers.
+pub fn read_wastickers(mut files: Vec<(String, Vec<u8>)>) -> (ArchiveContent, Vec<InvalidFile>) {
+    let title = take_file(&mut files, WASTICKERS_TITLE_FILE_NAME)
+        .map(|title| String::from_utf8_lossy(&title).trim().to_owned())
+        .filter(|title| !title.is_empty());
+
+    take_file(&mut files, WASTICKERS_AUTHOR_FILE_NAME);
+
+    // stickers are numbered, so `10.webp` goes after `2.webp`
+    files.sort_by(|(first, _), (second, _)| {
+        (file_number(first), first).cmp(&(file_number(second), second))
+    });
+
+    let (stickers, emojis): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .filter(|(name, _)| name.to_lowercase().ends_with(".webp") || name.ends_with(".txt"))
+        .partition(|(name, _)| !name.ends_with(".txt"));
+
+    let mut invalid_files = Vec::new();
+
+    let stickers = stickers
+        .into_iter()
+        .filter_map(|(name, data)| {
+            let stem = name
+                .rsplit_once('.')
+                .map_or(name.as_str(), |(stem, _)| stem);
+
+            let emoji = emojis
+                .iter()
+                .find(|(emoji_file, _)| emoji_file.strip_suffix(".txt") == Some(stem))
+                .map(|(_, emoji)| String::from_utf8_lossy(emoji).trim().to_owned())
+                .filter(|emoji| !emoji.is_empty());
+
+            normalized_sticker(name, &data, emoji, &mut invalid_files)
+        })
+        .collect::<Vec<_>>();
+
+    (
+        ArchiveContent {
+            title,
+            stickers: with_positions(stickers),
+        },
+        invalid_files,
+    )
+}
+
+/// Number from the file name like `12.webp`
+fn file_number(file_name: &str) -> Option<u32> {
+    let (stem, _) = file_name.rsplit_once('.')?;
+
+    stem.parse().ok()
+}
+
+/// Read stickers from Signal-style sticker bundle: archive with `manifest.json` (see [`SignalManifest`])
+/// and `.webp`, `.png` or `.apng` stickers. All stickers are converted into 512 px static stickers.
+pub fn read_signal_bundle(
+    mut files: Vec<(String, Vec<u8>)>,
+    manifest: SignalManifest,
+) -> (ArchiveContent, Vec<InvalidFile>) {
+    let mut invalid_files = Vec::new();
+
+    let stickers = manifest
+        .stickers
+        .into_iter()
+        .filter_map(|sticker| {
+            let data = match take_file(&mut files, &sticker.file) {
+                Some(data) => data,
+                None => {
+                    invalid_files.push(InvalidFile {
+                        file: sticker.file,
+                        reason: InvalidFileReason::NotFound,
+                    });
+
+                    return None;
+                }
+            };
+
+            normalized_sticker(sticker.file, &data, sticker.emoji, &mut invalid_files)
+        })
+        .collect::<Vec<_>>();
+
+    (
+        ArchiveContent {
+            title: Some(manifest.title).filter(|title| !title.is_empty()),
+            stickers: with_positions(stickers),
+        },
+        invalid_files,
+    )
+}
+
+/// Convert image into static sticker. If image can't be converted, add it to `invalid_files`.
+fn normalized_sticker(
+    file: String,
+    data: &[u8],
+    emoji: Option<String>,
+    invalid_files: &mut Vec<InvalidFile>,
+) -> Option<ArchiveSticker> {
+    match normalize_static_sticker(data) {
+        Ok((data, extension)) => Some(ArchiveSticker {
+            position: 0,
+            emoji,
+            keywords: Vec::new(),
+            format: "static".to_owned(),
+            extension,
+            data,
+        }),
+        Err(err) => {
+            invalid_files.push(InvalidFile {
+                file,
+                reason: InvalidFileReason::Image(err.to_string()),
+            });
+
+            None
+        }
+    }
+}
+
+fn with_positions(stickers: Vec<ArchiveSticker>) -> Vec<ArchiveSticker> {
+    stickers
+        .into_iter()
+        .enumerate()
+        .map(|(index, sticker)| ArchiveSticker {
+            position: index + 1,
+            ..sticker
+        })
+        .collect()
+}
+
+#[test]
+fn read_wastickers_test() {
+    use super::validation::test_png;
+
+    let files = vec![
+        ("title.txt".to_owned(), b"My pack\n".to_vec()),
+        ("author.txt".to_owned(), b"Author".to_vec()),
+        ("tray.png".to_owned(), test_png(96, 96)),
+        ("2.webp".to_owned(), test_png(100, 200)),
+        ("1.webp".to_owned(), test_png(1024, 1024)),
+        ("1.txt".to_owned(), "😎".as_bytes().to_vec()),
+        ("3.webp".to_owned(), b"not an image".to_vec()),
+        ("10.webp".to_owned(), test_png(512, 512)),
+        ("10.txt".to_owned(), "🔥".as_bytes().to_vec()),
+    ];
+
+    assert!(is_wastickers(&files));
+
+    let (content, invalid_files) = read_wastickers(files);
+
+    assert_eq!(content.title.as_deref(), Some("My pack"));
+    assert_eq!(content.stickers.len(), 3);
+    assert_eq!(content.stickers[0].emoji.as_deref(), Some("😎"));
+    assert_eq!(content.stickers[0].file_name(), "001_😎.png");
+    assert_eq!(content.stickers[1].emoji, None);
+    assert_eq!(content.stickers[1].position, 2);
+    assert_eq!(content.stickers[2].emoji.as_deref(), Some("🔥"));
+    assert_eq!(invalid_files.len(), 1);
+    assert_eq!(invalid_files[0].file, "3.webp");
+}
+
+#[test]
+fn read_signal_bundle_test() {
+    use super::validation::test_png;
+
+    let manifest: SignalManifest = serde_json::from_str(
+        r#"{"title": "Signal pack", "stickers": [{"id": "b.png", "emoji": "🔥"}, {"file": "c.webp"}]}"#,
+    )
+    .unwrap();
+
+    let files = vec![("b.png".to_owned(), test_png(300, 100))];
+
+    let (content, invalid_files) = read_signal_bundle(files, manifest);
+
+    assert_eq!(content.title.as_deref(), Some("Signal pack"));
+    assert_eq!(content.stickers.len(), 1);
+    assert_eq!(content.stickers[0].emoji.as_deref(), Some("🔥"));
+    assert_eq!(
+        invalid_files,
+        vec![InvalidFile {
+            file: "c.webp".to_owned(),
+            reason: InvalidFileReason::NotFound,
+        }]
+    );
+}
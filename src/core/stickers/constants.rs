@@ -2,6 +2,26 @@ pub const STICKER_SETS_NUMBER_PER_PAGE: usize = 50;
 
 pub const MAX_STICKER_SET_LENGTH: usize = 120;
 
+/// Maximum number of sticker packs in `/user` message, otherwise it can be longer than Telegram allows
+pub const USER_MESSAGE_SETS_NUMBER: usize = 50;
+
 pub const TELEGRAM_STICKER_SET_URL: &str = "t.me/addstickers/";
 
 pub const CREATE_SET_IN_ONE_GO_LENGTH_LIMIT: usize = 50;
+
+pub const TELEGRAM_BOT_API_FILE_URL: &str = "https://api.telegram.org/file/bot";
+
+/// Maximum size of file, which bot can upload to Telegram
+pub const TELEGRAM_UPLOAD_FILE_SIZE_LIMIT: usize = 50 * 1024 * 1024;
+
+/// Maximum size of static sticker file (WEBP or PNG), which Telegram accepts
+pub const STATIC_STICKER_SIZE_LIMIT: usize = 512 * 1024;
+
+/// Maximum size of animated sticker file (TGS), which Telegram accepts
+pub const ANIMATED_STICKER_SIZE_LIMIT: usize = 64 * 1024;
+
+/// Maximum size of video sticker file (WEBM), which Telegram accepts
+pub const VIDEO_STICKER_SIZE_LIMIT: usize = 256 * 1024;
+
+/// Emoji, which will be used for stickers from archive without specified emoji
+pub const DEFAULT_STICKER_EMOJI: &str = "⭐";
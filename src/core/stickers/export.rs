@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{core::stickers::constants::TELEGRAM_STICKER_SET_URL, domain::entities::set::Set};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    /// Parse format from command argument. If argument is not specified, return `Json`.
+    pub fn from_arg(arg: Option<&str>) -> Option<Self> {
+        match arg.map(str::trim).map(str::to_lowercase).as_deref() {
+            None | Some("") | Some("json") => Some(Self::Json),
+            Some("csv") => Some(Self::Csv),
+            Some(_) => None,
+        }
+    }
+
+    pub const fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "stickers.json",
+            ExportFormat::Csv => "stickers.csv",
+        }
+    }
+}
+
+/// Sticker set in format, which used in `/export` and `/import` commands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedSet {
+    pub short_name: String,
+    pub title: String,
+    pub link: String,
+    /// Ignored on import, because only existing sticker sets are imported
+    pub deleted: bool,
+    /// Unix timestamp (in seconds) when sticker set was saved into database.
+    /// Ignored on import, imported sticker set is saved as created at the moment of import.
+    pub created: i64,
+}
+
+impl From<&Set> for ExportedSet {
+    fn from(set: &Set) -> Self {
+        Self {
+            short_name: set.short_name.clone(),
+            title: set.title.clone(),
+            link: format!("{TELEGRAM_STICKER_SET_URL}{}", set.short_name),
+            deleted: set.deleted,
+            created: set.created.unix_timestamp(),
+        }
+    }
+}
+
+pub fn sets_to_json(sets: &[Set]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&sets.iter().map(ExportedSet::from).collect::<Vec<_>>())
+}
+
+pub fn sets_to_csv(sets: &[Set]) -> String {
+    let mut csv = String::from("short_name,title,link,deleted,created\n");
+
+    for set in sets.iter().map(ExportedSet::from) {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&set.short_name),
+            csv_field(&set.title),
+            csv_field(&set.link),
+            set.deleted,
+            set.created
+        ));
+    }
+
+    csv
+}
+
+pub fn sets_from_json(json: &str) -> Result<Vec<ExportedSet>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// Quote field if it contains characters, that have special meaning in CSV
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[test]
+fn export_format_from_arg_test() {
+    assert_eq!(ExportFormat::from_arg(None), Some(ExportFormat::Json));
+    assert_eq!(
+        ExportFormat::from_arg(Some(" CSV ")),
+        Some(ExportFormat::Csv)
+    );
+    assert_eq!(ExportFormat::from_arg(Some("xml")), None);
+}
+
+#[test]
+fn sets_export_test() {
+    use sqlx::types::time::OffsetDateTime;
+
+    let sets = vec![Set {
+        tg_id: 1,
+        short_name: "short_name".to_owned(),
+        deleted: true,
+        title: "title, with \"quotes\"".to_owned(),
+        created: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+    }];
+
+    assert_eq!(
+        sets_to_csv(&sets),
+        "short_name,title,link,deleted,created\n\
+        short_name,\"title, with \"\"quotes\"\"\",t.me/addstickers/short_name,true,1700000000\n"
+    );
+
+    let exported = sets_from_json(&sets_to_json(&sets).unwrap()).unwrap();
+
+    assert_eq!(exported, vec![ExportedSet::from(&sets[0])]);
+}
@@ -0,0 +1,67 @@
+use std::io::Cursor;
+
+use image::{codecs::webp::WebPEncoder, imageops::FilterType, ImageError, ImageFormat};
+
+use super::{constants::STATIC_STICKER_SIZE_LIMIT, validation::STICKER_SIDE};
+
+#[derive(Debug, thiserror::Error)]
+pub enum NormalizeError {
+    #[error(transparent)]
+    Image(#[from] ImageError),
+    #[error("normalized image is too large ({size} bytes)")]
+    TooLarge { size: usize },
+}
+
+/// Convert image (PNG, APNG or WEBP) into static sticker, which Telegram accepts:
+/// the biggest side of the image becomes exactly 512 px and image is encoded as PNG
+/// (or lossless WEBP, if PNG is too large). Only the first frame of animated images is used.
+/// Return data of the sticker and extension of its format (`png` or `webp`).
+pub fn normalize_static_sticker(data: &[u8]) -> Result<(Vec<u8>, &'static str), NormalizeError> {
+    let image = image::load_from_memory(data)?;
+
+    let image = if image.width().max(image.height()) == STICKER_SIDE {
+        image
+    } else {
+        image.resize(STICKER_SIDE, STICKER_SIDE, FilterType::Lanczos3)
+    };
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    if png.len() <= STATIC_STICKER_SIZE_LIMIT {
+        return Ok((png, "png"));
+    }
+
+    let image = image.to_rgba8();
+
+    let mut webp = Vec::new();
+    image.write_with_encoder(WebPEncoder::new_lossless(&mut webp))?;
+
+    if webp.len() <= STATIC_STICKER_SIZE_LIMIT {
+        return Ok((webp, "webp"));
+    }
+
+    Err(NormalizeError::TooLarge {
+        size: png.len().min(webp.len()),
+    })
+}
+
+#[test]
+fn normalize_static_sticker_test() {
+    use image::{GenericImageView as _, RgbaImage};
+
+    for (width, height) in [(100, 50), (1024, 2048), (512, 300)] {
+        let mut png = Vec::new();
+        RgbaImage::new(width, height)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let (normalized, extension) = normalize_static_sticker(&png).unwrap();
+        assert_eq!(extension, "png");
+
+        let normalized = image::load_from_memory(&normalized).unwrap();
+        let (width, height) = normalized.dimensions();
+
+        assert_eq!(width.max(height), STICKER_SIDE);
+    }
+}
@@ -1,8 +1,14 @@
+use std::time::Duration;
+
 use telers::utils::text::{html_code, html_text_link};
 
 use crate::domain::entities::set::Set;
 
-use super::{common::get_page_begin_and_end, constants::TELEGRAM_STICKER_SET_URL};
+use super::{
+    archive::ArchiveError,
+    common::get_page_begin_and_end,
+    constants::{TELEGRAM_STICKER_SET_URL, USER_MESSAGE_SETS_NUMBER},
+};
 
 pub fn sticker_set_message(
     sticker_set_title: &str,
@@ -25,6 +31,48 @@ pub fn sticker_set_message(
     )
 }
 
+pub fn created_sticker_set_message(
+    sticker_set_title: &str,
+    sticker_set_name: &str,
+    sticker_set_link: &str,
+) -> String {
+    format!(
+        "
+        Now you have your own sticker pack {new_ss_url}! \
+        You can add stickers to this pack using command /addstickers!\n\nIf you want to manage your new sticker pack, \
+        use official Telegram bot @Stickers, which does an excellent job of managing sticker packs. \
+        (the name of your new sticker pack to handle it in @Stickers bot: {sticker_set_name})
+        ",
+        new_ss_url = html_text_link(sticker_set_title, sticker_set_link),
+        sticker_set_name = html_code(sticker_set_name)
+    )
+}
+
+pub fn archive_error_message(err: &ArchiveError) -> String {
+    match err {
+        ArchiveError::InvalidFiles(files) => {
+            let mut message =
+                String::from("Some files in this archive can't be used as stickers:\n");
+
+            for file in files {
+                message.push_str(&format!("- {file}\n"));
+            }
+
+            message.push_str("Fix them and try send archive again.");
+
+            message
+        }
+        ArchiveError::NoStickers => {
+            "This archive doesn't contain sticker files! Try send another archive.".to_owned()
+        }
+        ArchiveError::TooManyStickers { .. }
+        | ArchiveError::TooManyFiles
+        | ArchiveError::TooLarge
+        | ArchiveError::FileTooLarge { .. } => format!("{err}! Try send another archive."),
+        _ => "This file is not a valid zip archive! Try send another file.".to_owned(),
+    }
+}
+
 pub fn start_message(username: &str) -> String {
     format!(
         "
@@ -36,10 +84,113 @@ pub fn start_message(username: &str) -> String {
     /stealpack - Steal sticker pack\n\
     /addstickers - Add sticker to a sticker pack stolen by this bot\n\
     /mystickers - List of your stolen stickers\n\
+    /export - Export list of your stolen stickers (json or csv)\n\
+    /import - Import list of your stolen stickers from json file\n\
+    /download - Download sticker pack as zip archive\n\
+    /fromarchive - Create sticker pack from zip archive (also WhatsApp and Signal packs)\n\
         ",
     )
 }
 
+/// Message for users, whose steal or adding of stickers was interrupted by shutdown of the bot
+pub fn interrupted_operation_message() -> &'static str {
+    "Sorry, the bot is restarting, so your sticker pack was not finished :( \
+    Check it using command /mystickers and add the missing stickers using command /addstickers in a minute."
+}
+
+/// Message for users, whose steal or adding of stickers isn't started, because the bot is shutting down
+pub fn restarting_message() -> &'static str {
+    "Sorry, the bot is restarting :( Try again in a minute."
+}
+
+/// Message for users, who reached the `limit`, `wait` is time until they can retry
+pub fn quota_exceeded_message(limit: &str, wait: Duration) -> String {
+    let minutes = wait.as_secs().div_ceil(60);
+
+    let wait = match minutes {
+        0..=1 => "a minute".to_owned(),
+        2..=59 => format!("{minutes} minutes"),
+        _ if minutes % 60 == 0 => format!("{} h", minutes / 60),
+        _ => format!("{} h {} min", minutes / 60, minutes % 60),
+    };
+
+    format!("You have reached the limit of {limit} :( You can try again in {wait}.")
+}
+
+/// Statistics of the bot for `/stats` admin command
+pub fn stats_message(users: i64, sets: i64, deleted_sets: i64, stolen_today: i64) -> String {
+    format!(
+        "Users: {users}\n\
+        Sticker packs: {sets}\n\
+        Deleted sticker packs: {deleted_sets}\n\
+        Sticker packs stolen today: {stolen_today}"
+    )
+}
+
+/// Information about the user for `/user` admin command
+pub fn user_message(user_id: i64, banned: bool, sets: &[Set]) -> String {
+    let mut message = format!(
+        "User {user_id}{banned}, sticker packs: {number}\n",
+        user_id = html_code(user_id.to_string()),
+        banned = if banned { " (banned)" } else { "" },
+        number = sets.len(),
+    );
+
+    for set in sets.iter().take(USER_MESSAGE_SETS_NUMBER) {
+        let sticker_set_link = format!("{TELEGRAM_STICKER_SET_URL}{}", set.short_name);
+
+        message.push_str(&html_text_link(set.title.as_str(), sticker_set_link));
+
+        if set.deleted {
+            message.push_str(" (deleted)");
+        }
+
+        message.push('\n');
+    }
+
+    if sets.len() > USER_MESSAGE_SETS_NUMBER {
+        message.push_str(&format!(
+            "and {} more",
+            sets.len() - USER_MESSAGE_SETS_NUMBER
+        ));
+    }
+
+    message
+}
+
+/// Report for the admin, who started the broadcast
+pub fn broadcast_finished_message(delivered: i64, failed: i64, blocked: i64) -> String {
+    format!(
+        "Broadcast is finished!\n\
+        Delivered: {delivered}\n\
+        Failed: {failed}\n\
+        Blocked the bot: {blocked}"
+    )
+}
+
+/// Report for the user, who imported sticker packs
+pub fn import_finished_message(imported: u32, existing: u32, skipped: u32, failed: u32) -> String {
+    let mut message = format!(
+        "Imported sticker packs: {imported}. Already saved sticker packs: {existing}. \
+        Skipped sticker packs: {skipped} (they don't exist, weren't created by this bot or don't belong to you)."
+    );
+
+    if failed > 0 {
+        message.push_str(&format!(
+            " Sticker packs not imported due to an error: {failed}, try to import them again later."
+        ));
+    }
+
+    message.push_str(" You can see all your sticker packs using command /mystickers.");
+
+    message
+}
+
+/// Refusal for users, who send sticker pack from the blocklist
+pub const BLOCKED_SET_MESSAGE: &str =
+    "Sorry, the creator of this sticker pack asked not to copy it. \
+    Try to send another sticker pack.";
+
 pub fn current_page_message(
     current_page: usize,
     pages_number: u32,
@@ -68,6 +219,8 @@ pub fn current_page_message(
 
 #[test]
 fn current_page_message_test() {
+    use sqlx::types::time::OffsetDateTime;
+
     let mut list = Vec::new();
     for i in 0..5 {
         list.push(Set {
@@ -75,6 +228,7 @@ fn current_page_message_test() {
             short_name: format!("short_name{i}"),
             deleted: false,
             title: format!("title{i}"),
+            created: OffsetDateTime::UNIX_EPOCH,
         });
     }
 
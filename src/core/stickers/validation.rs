@@ -0,0 +1,476 @@
+use std::io::{Cursor, Read as _};
+
+use flate2::read::GzDecoder;
+use image::{ImageFormat, ImageReader};
+use serde::Deserialize;
+
+use super::constants::{
+    ANIMATED_STICKER_SIZE_LIMIT, STATIC_STICKER_SIZE_LIMIT, VIDEO_STICKER_SIZE_LIMIT,
+};
+
+/// Size of the biggest side of sticker, which Telegram requires
+pub const STICKER_SIDE: u32 = 512;
+
+/// Frame rate of animated sticker, which Telegram requires
+pub const ANIMATED_STICKER_FRAME_RATE: f64 = 60.0;
+
+/// Maximum duration of animated and video stickers (in seconds)
+pub const STICKER_MAX_DURATION: f64 = 3.0;
+
+/// Maximum size of unpacked Lottie JSON (protects from gzip bombs)
+const LOTTIE_SIZE_LIMIT: u64 = 10 * 1024 * 1024;
+
+/// Reason, why Telegram won't accept sticker file
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ValidationError {
+    #[error("file is too large ({size} bytes, but maximum is {limit} bytes)")]
+    TooLarge { size: usize, limit: usize },
+    #[error("image must be PNG or WEBP")]
+    UnsupportedImageFormat,
+    #[error("can't read image ({0})")]
+    InvalidImage(String),
+    #[error("one side of sticker must be exactly {STICKER_SIDE} px and other side {STICKER_SIDE} px or less, but sticker is {width}x{height} px")]
+    WrongDimensions { width: u32, height: u32 },
+    #[error("animated sticker must be gzip-compressed Lottie animation")]
+    InvalidTgs,
+    #[error(
+        "animated sticker must have {ANIMATED_STICKER_FRAME_RATE} fps, but it has {frame_rate} fps"
+    )]
+    WrongFrameRate { frame_rate: f64 },
+    #[error(
+        "sticker must be {STICKER_MAX_DURATION} seconds or less, but it is {duration:.2} seconds"
+    )]
+    TooLong { duration: f64 },
+    #[error("video sticker must be WEBM file")]
+    InvalidWebm,
+    #[error("video sticker must be encoded with VP9 codec, but it is encoded with {codec}")]
+    WrongCodec { codec: String },
+}
+
+/// Check, that Telegram will accept sticker file of the specified format (`static`, `animated` or `video`)
+pub fn validate_sticker(data: &[u8], format: &str) -> Result<(), ValidationError> {
+    match format {
+        "animated" => validate_animated_sticker(data),
+        "video" => validate_video_sticker(data),
+        _ => validate_static_sticker(data),
+    }
+}
+
+/// Check, that file is PNG or WEBP image, one side of which is exactly 512 px
+pub fn validate_static_sticker(data: &[u8]) -> Result<(), ValidationError> {
+    check_size(data, STATIC_STICKER_SIZE_LIMIT)?;
+
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| ValidationError::InvalidImage(err.to_string()))?;
+
+    if !matches!(reader.format(), Some(ImageFormat::Png | ImageFormat::WebP)) {
+        return Err(ValidationError::UnsupportedImageFormat);
+    }
+
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|err| ValidationError::InvalidImage(err.to_string()))?;
+
+    check_dimensions(width, height)
+}
+
+/// Part of Lottie animation, which is needed for validation
+#[derive(Debug, Deserialize)]
+struct Lottie {
+    #[serde(rename = "fr")]
+    frame_rate: f64,
+    #[serde(rename = "ip")]
+    in_point: f64,
+    #[serde(rename = "op")]
+    out_point: f64,
+    #[serde(rename = "w")]
+    width: u32,
+    #[serde(rename = "h")]
+    height: u32,
+}
+
+/// Check, that file is gzip-compressed Lottie animation with 60 fps, 512x512 px and 3 seconds or less
+pub fn validate_animated_sticker(data: &[u8]) -> Result<(), ValidationError> {
+    check_size(data, ANIMATED_STICKER_SIZE_LIMIT)?;
+
+    let mut json = Vec::new();
+    GzDecoder::new(data)
+        .take(LOTTIE_SIZE_LIMIT)
+        .read_to_end(&mut json)
+        .map_err(|_| ValidationError::InvalidTgs)?;
+
+    let lottie: Lottie = serde_json::from_slice(&json).map_err(|_| ValidationError::InvalidTgs)?;
+
+    if lottie.frame_rate != ANIMATED_STICKER_FRAME_RATE {
+        return Err(ValidationError::WrongFrameRate {
+            frame_rate: lottie.frame_rate,
+        });
+    }
+
+    check_duration((lottie.out_point - lottie.in_point) / lottie.frame_rate)?;
+
+    if lottie.width != STICKER_SIDE || lottie.height != STICKER_SIDE {
+        return Err(ValidationError::WrongDimensions {
+            width: lottie.width,
+            height: lottie.height,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check, that file is WEBM video encoded with VP9 codec and 3 seconds or less
+pub fn validate_video_sticker(data: &[u8]) -> Result<(), ValidationError> {
+    check_size(data, VIDEO_STICKER_SIZE_LIMIT)?;
+
+    let headers = read_webm_headers(data).ok_or(ValidationError::InvalidWebm)?;
+
+    if headers.doc_type != "webm" {
+        return Err(ValidationError::InvalidWebm);
+    }
+
+    match headers.codec {
+        Some(codec) if codec == "V_VP9" => {}
+        Some(codec) => return Err(ValidationError::WrongCodec { codec }),
+        None => return Err(ValidationError::InvalidWebm),
+    }
+
+    if let Some(duration) = headers.duration {
+        check_duration(duration * headers.timecode_scale as f64 / 1_000_000_000.0)?;
+    }
+
+    if let (Some(width), Some(height)) = (headers.width, headers.height) {
+        check_dimensions(width, height)?;
+    }
+
+    Ok(())
+}
+
+fn check_size(data: &[u8], limit: usize) -> Result<(), ValidationError> {
+    if data.len() > limit {
+        return Err(ValidationError::TooLarge {
+            size: data.len(),
+            limit,
+        });
+    }
+
+    Ok(())
+}
+
+fn check_dimensions(width: u32, height: u32) -> Result<(), ValidationError> {
+    if width.max(height) != STICKER_SIDE {
+        return Err(ValidationError::WrongDimensions { width, height });
+    }
+
+    Ok(())
+}
+
+fn check_duration(duration: f64) -> Result<(), ValidationError> {
+    // small epsilon, because duration is calculated from floats
+    if duration > STICKER_MAX_DURATION + 1e-3 {
+        return Err(ValidationError::TooLong { duration });
+    }
+
+    Ok(())
+}
+
+// EBML element IDs, which are needed to validate WEBM file
+const EBML_ID: u64 = 0x1A45_DFA3;
+const DOC_TYPE_ID: u64 = 0x4282;
+const SEGMENT_ID: u64 = 0x1853_8067;
+const INFO_ID: u64 = 0x1549_A966;
+const TIMECODE_SCALE_ID: u64 = 0x2A_D7B1;
+const DURATION_ID: u64 = 0x4489;
+const TRACKS_ID: u64 = 0x1654_AE6B;
+const TRACK_ENTRY_ID: u64 = 0xAE;
+const CODEC_ID_ID: u64 = 0x86;
+const VIDEO_ID: u64 = 0xE0;
+const PIXEL_WIDTH_ID: u64 = 0xB0;
+const PIXEL_HEIGHT_ID: u64 = 0xBA;
+
+/// Default timecode scale of WEBM file (1 ms in nanoseconds)
+const DEFAULT_TIMECODE_SCALE: u64 = 1_000_000;
+
+#[derive(Debug, Default)]
+struct WebmHeaders {
+    doc_type: String,
+    /// Duration in timecode scale units
+    duration: Option<f64>,
+    timecode_scale: u64,
+    codec: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+/// Read headers of WEBM file. Return `None` if file isn't valid EBML document.
+fn read_webm_headers(data: &[u8]) -> Option<WebmHeaders> {
+    let mut headers = WebmHeaders {
+        timecode_scale: DEFAULT_TIMECODE_SCALE,
+        ..Default::default()
+    };
+
+    let (id, ebml, mut rest) = read_element(data)?;
+    if id != EBML_ID {
+        return None;
+    }
+
+    let mut ebml = ebml;
+    while !ebml.is_empty() {
+        let (id, value, next) = read_element(ebml)?;
+        if id == DOC_TYPE_ID {
+            headers.doc_type = String::from_utf8_lossy(value)
+                .trim_end_matches('\0')
+                .to_owned();
+        }
+        ebml = next;
+    }
+
+    while !rest.is_empty() {
+        let (id, segment, next) = read_element(rest)?;
+        if id == SEGMENT_ID {
+            read_segment(segment, &mut headers)?;
+            break;
+        }
+        rest = next;
+    }
+
+    Some(headers)
+}
+
+fn read_segment(mut segment: &[u8], headers: &mut WebmHeaders) -> Option<()> {
+    while !segment.is_empty() {
+        // file can be truncated after headers, so stop on first invalid element
+        let Some((id, value, next)) = read_element(segment) else {
+            break;
+        };
+
+        match id {
+            INFO_ID => read_children(value, |id, value| match id {
+                TIMECODE_SCALE_ID => headers.timecode_scale = read_uint(value),
+                DURATION_ID => headers.duration = read_float(value),
+                _ => {}
+            })?,
+            TRACKS_ID => read_children(value, |id, value| {
+                if id == TRACK_ENTRY_ID && headers.codec.is_none() {
+                    let _ = read_track_entry(value, headers);
+                }
+            })?,
+            _ => {}
+        }
+
+        segment = next;
+    }
+
+    Some(())
+}
+
+fn read_track_entry(entry: &[u8], headers: &mut WebmHeaders) -> Option<()> {
+    read_children(entry, |id, value| match id {
+        CODEC_ID_ID => {
+            headers.codec = Some(
+                String::from_utf8_lossy(value)
+                    .trim_end_matches('\0')
+                    .to_owned(),
+            );
+        }
+        VIDEO_ID => {
+            let _ = read_children(value, |id, value| match id {
+                PIXEL_WIDTH_ID => headers.width = u32::try_from(read_uint(value)).ok(),
+                PIXEL_HEIGHT_ID => headers.height = u32::try_from(read_uint(value)).ok(),
+                _ => {}
+            });
+        }
+        _ => {}
+    })
+}
+
+fn read_children(mut data: &[u8], mut f: impl FnMut(u64, &[u8])) -> Option<()> {
+    while !data.is_empty() {
+        let (id, value, next) = read_element(data)?;
+        f(id, value);
+        data = next;
+    }
+
+    Some(())
+}
+
+/// Read EBML element and return its ID, value and rest of the data.
+/// Value of element with unknown size lasts until the end of the data.
+fn read_element(data: &[u8]) -> Option<(u64, &[u8], &[u8])> {
+    let (id, id_len) = read_vint(data, false)?;
+    let data = &data[id_len..];
+
+    let (size, size_len) = read_vint(data, true)?;
+    let data = &data[size_len..];
+
+    let unknown_size = size == (1 << (7 * size_len)) - 1;
+    let size = if unknown_size {
+        data.len()
+    } else {
+        usize::try_from(size).ok()?.min(data.len())
+    };
+
+    Some((id, &data[..size], &data[size..]))
+}
+
+/// Read EBML variable size integer. IDs keep their length marker, sizes don't.
+fn read_vint(data: &[u8], strip_marker: bool) -> Option<(u64, usize)> {
+    let first = *data.first()?;
+    if first == 0 {
+        return None;
+    }
+
+    let len = first.leading_zeros() as usize + 1;
+    let bytes = data.get(..len)?;
+
+    let first = if strip_marker {
+        u64::from(first & (0x7F >> (len - 1)))
+    } else {
+        u64::from(first)
+    };
+
+    let value = bytes[1..]
+        .iter()
+        .fold(first, |value, byte| (value << 8) | u64::from(*byte));
+
+    Some((value, len))
+}
+
+fn read_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .take(8)
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte))
+}
+
+fn read_float(data: &[u8]) -> Option<f64> {
+    match data.len() {
+        4 => Some(f64::from(f32::from_be_bytes(data.try_into().ok()?))),
+        8 => Some(f64::from_be_bytes(data.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub(super) fn test_png(width: u32, height: u32) -> Vec<u8> {
+    let mut png = Vec::new();
+    image::RgbaImage::new(width, height)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+
+    png
+}
+
+#[cfg(test)]
+pub(super) fn test_tgs(frame_rate: u32, out_point: u32) -> Vec<u8> {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write as _;
+
+    let json = format!(
+        r#"{{"v":"5.5.2","fr":{frame_rate},"ip":0,"op":{out_point},"w":512,"h":512,"layers":[]}}"#
+    );
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(json.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[cfg(test)]
+fn ebml_element(id: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut element = id.to_vec();
+    // 8-byte size
+    element.push(0x01);
+    element.extend_from_slice(&(value.len() as u64).to_be_bytes()[1..]);
+    element.extend_from_slice(value);
+    element
+}
+
+#[cfg(test)]
+fn test_webm(codec: &str, duration_ms: f64) -> Vec<u8> {
+    let ebml = ebml_element(
+        &[0x1A, 0x45, 0xDF, 0xA3],
+        &ebml_element(&[0x42, 0x82], b"webm"),
+    );
+
+    let info = ebml_element(
+        &[0x15, 0x49, 0xA9, 0x66],
+        &ebml_element(&[0x44, 0x89], &duration_ms.to_be_bytes()),
+    );
+
+    let video = ebml_element(
+        &[0xE0],
+        &[
+            ebml_element(&[0xB0], &[0x02, 0x00]),
+            ebml_element(&[0xBA], &[0x01, 0x00]),
+        ]
+        .concat(),
+    );
+    let track_entry = ebml_element(
+        &[0xAE],
+        &[ebml_element(&[0x86], codec.as_bytes()), video].concat(),
+    );
+    let tracks = ebml_element(&[0x16, 0x54, 0xAE, 0x6B], &track_entry);
+
+    // segment with unknown size, like in live streams
+    let mut segment = vec![0x18, 0x53, 0x80, 0x67, 0xFF];
+    segment.extend_from_slice(&info);
+    segment.extend_from_slice(&tracks);
+
+    [ebml, segment].concat()
+}
+
+#[test]
+fn validate_static_sticker_test() {
+    assert_eq!(validate_static_sticker(&test_png(512, 300)), Ok(()));
+    assert_eq!(validate_static_sticker(&test_png(100, 512)), Ok(()));
+    assert_eq!(
+        validate_static_sticker(&test_png(100, 100)),
+        Err(ValidationError::WrongDimensions {
+            width: 100,
+            height: 100
+        })
+    );
+    assert_eq!(
+        validate_static_sticker(b"GIF89a"),
+        Err(ValidationError::UnsupportedImageFormat)
+    );
+}
+
+#[test]
+fn validate_animated_sticker_test() {
+    assert_eq!(validate_animated_sticker(&test_tgs(60, 180)), Ok(()));
+    assert_eq!(
+        validate_animated_sticker(&test_tgs(30, 60)),
+        Err(ValidationError::WrongFrameRate { frame_rate: 30.0 })
+    );
+    assert_eq!(
+        validate_animated_sticker(&test_tgs(60, 240)),
+        Err(ValidationError::TooLong { duration: 4.0 })
+    );
+    assert_eq!(
+        validate_animated_sticker(b"not gzip"),
+        Err(ValidationError::InvalidTgs)
+    );
+}
+
+#[test]
+fn validate_video_sticker_test() {
+    assert_eq!(validate_video_sticker(&test_webm("V_VP9", 2900.0)), Ok(()));
+    assert_eq!(
+        validate_video_sticker(&test_webm("V_VP8", 2900.0)),
+        Err(ValidationError::WrongCodec {
+            codec: "V_VP8".to_owned()
+        })
+    );
+    assert_eq!(
+        validate_video_sticker(&test_webm("V_VP9", 3500.0)),
+        Err(ValidationError::TooLong { duration: 3.5 })
+    );
+    assert_eq!(
+        validate_video_sticker(&vec![0; VIDEO_STICKER_SIZE_LIMIT + 1]),
+        Err(ValidationError::TooLarge {
+            size: VIDEO_STICKER_SIZE_LIMIT + 1,
+            limit: VIDEO_STICKER_SIZE_LIMIT
+        })
+    );
+}
@@ -1,7 +1,10 @@
+use sqlx::types::time::OffsetDateTime;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Set {
     pub tg_id: i64,
     pub short_name: String,
     pub deleted: bool,
     pub title: String,
+    pub created: OffsetDateTime,
 }
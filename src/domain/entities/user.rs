@@ -4,4 +4,7 @@ use sqlx::types::time::OffsetDateTime;
 pub struct User {
     pub tg_id: i64,
     pub created: OffsetDateTime,
+    pub banned: bool,
+    /// `false`, if the user blocked the bot
+    pub active: bool,
 }
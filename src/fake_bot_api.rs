@@ -0,0 +1,504 @@
+//! Local fake of the Telegram Bot API server, which is used to test handlers without calling real Telegram.
+//! Server records every call of the bot and keeps created sticker sets in memory,
+//! and [`TestBot`] feeds updates through the real [`Dispatcher`] with the router of the bot.
+
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequest as _, Multipart, Path, Request, State},
+    http::header::CONTENT_TYPE,
+    routing::post,
+    Json, Router as HttpRouter,
+};
+use serde_json::{json, Value};
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use telers::{
+    client::{
+        telegram::{APIServer, BareFilesPathWrapper},
+        Reqwest,
+    },
+    errors::EventErrorKind,
+    event::{EventReturn, ToServiceProvider as _},
+    fsm::MemoryStorage,
+    middlewares::{outer::MiddlewareResponse, OuterMiddleware},
+    router::Request as RouterRequest,
+    types::Update,
+    Bot, Dispatcher, Router,
+};
+use tokio::net::TcpListener;
+
+use crate::{
+    config::QuotasConfig,
+    infrastructure::database::SQLITE_MIGRATOR,
+    middlewares::{Blocklist, Quotas},
+    private_router,
+};
+
+pub const TEST_BOT_TOKEN: &str = "42:TEST";
+pub const TEST_BOT_ID: i64 = 42;
+pub const TEST_BOT_USERNAME: &str = "steal_stickers_test_bot";
+
+/// Call of the Bot API method with its parameters
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub method: String,
+    pub params: Value,
+}
+
+#[derive(Debug, Default)]
+struct FakeBotApiState {
+    calls: Vec<Call>,
+    sticker_sets: HashMap<String, Value>,
+    /// Methods, which are answered with `Too Many Requests` once, and `retry_after` of the answer
+    floods: HashMap<String, i64>,
+    /// Chats of the users, who blocked the bot
+    blocked: HashSet<i64>,
+    last_message_id: i64,
+}
+
+#[derive(Clone)]
+pub struct FakeBotApi {
+    addr: SocketAddr,
+    state: Arc<Mutex<FakeBotApiState>>,
+}
+
+impl FakeBotApi {
+    /// Start server on the random local port
+    pub async fn start() -> Self {
+        let state = Arc::new(Mutex::new(FakeBotApiState::default()));
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("error occurded while bind fake Bot API server");
+        let addr = listener.local_addr().expect("listener without address");
+
+        let app = HttpRouter::new()
+            .route("/:token/:method", post(handle_method))
+            .with_state(state.clone());
+
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        Self { addr, state }
+    }
+
+    /// Bot, which sends requests to this server
+    pub fn bot(&self) -> Bot {
+        let api_server = APIServer::new(
+            &format!("http://{}/bot{{token}}/{{method}}", self.addr),
+            &format!("http://{}/file/bot{{token}}/{{path}}", self.addr),
+            true,
+            BareFilesPathWrapper,
+        );
+
+        Bot::with_client(
+            TEST_BOT_TOKEN,
+            Reqwest::default().with_api_server(api_server),
+        )
+    }
+
+    /// Add sticker set, which will be returned by `getStickerSet`
+    pub fn add_sticker_set(&self, sticker_set: Value) {
+        let name = sticker_set["name"]
+            .as_str()
+            .expect("sticker set without name")
+            .to_owned();
+
+        self.lock().sticker_sets.insert(name, sticker_set);
+    }
+
+    /// Answer the next call of the method with `Too Many Requests`
+    pub fn flood(&self, method: &str, retry_after: i64) {
+        self.lock().floods.insert(method.to_owned(), retry_after);
+    }
+
+    /// Answer every method sent into the chat with `Forbidden`, like the user blocked the bot
+    pub fn block(&self, chat_id: i64) {
+        self.lock().blocked.insert(chat_id);
+    }
+
+    pub fn sticker_set(&self, name: &str) -> Option<Value> {
+        self.lock().sticker_sets.get(name).cloned()
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.lock().calls.clone()
+    }
+
+    /// Parameters of every call of the method
+    pub fn calls_of(&self, method: &str) -> Vec<Value> {
+        self.lock()
+            .calls
+            .iter()
+            .filter(|call| call.method == method)
+            .map(|call| call.params.clone())
+            .collect()
+    }
+
+    /// Texts of the messages sent by the bot
+    pub fn sent_texts(&self) -> Vec<String> {
+        self.calls_of("sendMessage")
+            .iter()
+            .map(|params| text(&params["text"]))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FakeBotApiState> {
+        self.state.lock().expect("fake Bot API state lock poisoned")
+    }
+}
+
+/// Telegram sends parameters as form, where every complex value is serialized to JSON
+async fn read_params(request: Request) -> Result<Value, String> {
+    let is_multipart = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("multipart/form-data"));
+
+    if !is_multipart {
+        let body = axum::body::to_bytes(request.into_body(), usize::MAX)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        if body.is_empty() {
+            return Ok(json!({}));
+        }
+
+        return serde_json::from_slice(&body).map_err(|err| err.to_string());
+    }
+
+    let mut multipart = Multipart::from_request(request, &())
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let mut params = serde_json::Map::new();
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| err.to_string())?
+    {
+        let name = field.name().unwrap_or_default().to_owned();
+        let value = field.text().await.map_err(|err| err.to_string())?;
+
+        params.insert(
+            name,
+            serde_json::from_str(&value).unwrap_or(Value::String(value)),
+        );
+    }
+
+    Ok(Value::Object(params))
+}
+
+async fn handle_method(
+    State(state): State<Arc<Mutex<FakeBotApiState>>>,
+    Path((_token, method)): Path<(String, String)>,
+    request: Request,
+) -> Json<Value> {
+    let params = match read_params(request).await {
+        Ok(params) => params,
+        Err(err) => return error(400, &format!("Bad Request: {err}")),
+    };
+
+    let mut state = state.lock().expect("fake Bot API state lock poisoned");
+
+    state.calls.push(Call {
+        method: method.clone(),
+        params: params.clone(),
+    });
+
+    if let Some(retry_after) = state.floods.remove(&method) {
+        return Json(json!({
+            "ok": false,
+            "error_code": 429,
+            "description": format!("Too Many Requests: retry after {retry_after}"),
+            "parameters": { "retry_after": retry_after },
+        }));
+    }
+
+    if params["chat_id"]
+        .as_i64()
+        .is_some_and(|chat_id| state.blocked.contains(&chat_id))
+    {
+        return error(403, "Forbidden: bot was blocked by the user");
+    }
+
+    match method.as_str() {
+        "getMe" => ok(json!({
+            "id": TEST_BOT_ID,
+            "is_bot": true,
+            "first_name": "Steal stickers test bot",
+            "username": TEST_BOT_USERNAME,
+        })),
+        "getStickerSet" => match state.sticker_sets.get(&text(&params["name"])) {
+            Some(sticker_set) => ok(sticker_set.clone()),
+            None => error(400, "Bad Request: STICKERSET_INVALID"),
+        },
+        "createNewStickerSet" => {
+            let name = text(&params["name"]);
+
+            if state.sticker_sets.contains_key(&name) {
+                return error(400, "Bad Request: SHORTNAME_OCCUPY_FAILED");
+            }
+
+            let stickers = params["stickers"]
+                .as_array()
+                .map(|stickers| {
+                    stickers
+                        .iter()
+                        .map(|input_sticker| sticker_from_input(&name, input_sticker))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            let sticker_set = sticker_set(&name, &text(&params["title"]), stickers);
+            state.sticker_sets.insert(name, sticker_set);
+
+            ok(json!(true))
+        }
+        "addStickerToSet" => {
+            let name = text(&params["name"]);
+
+            let Some(sticker_set) = state.sticker_sets.get_mut(&name) else {
+                return error(400, "Bad Request: STICKERSET_INVALID");
+            };
+
+            sticker_set["stickers"]
+                .as_array_mut()
+                .expect("sticker set without stickers")
+                .push(sticker_from_input(&name, &params["sticker"]));
+
+            ok(json!(true))
+        }
+        "sendMessage" => {
+            state.last_message_id += 1;
+
+            ok(message(
+                state.last_message_id,
+                &params["chat_id"],
+                &text(&params["text"]),
+            ))
+        }
+        "copyMessage" => {
+            state.last_message_id += 1;
+
+            ok(json!({ "message_id": state.last_message_id }))
+        }
+        "editMessageText" => ok(message(
+            params["message_id"].as_i64().unwrap_or_default(),
+            &params["chat_id"],
+            &text(&params["text"]),
+        )),
+        "deleteMessage" => ok(json!(true)),
+        _ => error(404, "Not Found: method not found"),
+    }
+}
+
+fn ok(result: Value) -> Json<Value> {
+    Json(json!({ "ok": true, "result": result }))
+}
+
+fn error(error_code: u16, description: &str) -> Json<Value> {
+    Json(json!({
+        "ok": false,
+        "error_code": error_code,
+        "description": description,
+    }))
+}
+
+/// Numbers and strings are the same for form parameters
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+fn message(message_id: i64, chat_id: &Value, text: &str) -> Value {
+    json!({
+        "message_id": message_id,
+        "date": 0,
+        "chat": { "id": chat_id, "type": "private", "first_name": "Test" },
+        "from": {
+            "id": TEST_BOT_ID,
+            "is_bot": true,
+            "first_name": "Steal stickers test bot",
+            "username": TEST_BOT_USERNAME,
+        },
+        "text": text,
+    })
+}
+
+fn sticker_from_input(set_name: &str, input_sticker: &Value) -> Value {
+    let file_id = text(&input_sticker["sticker"]);
+    let emoji = input_sticker["emoji_list"][0].as_str().unwrap_or("🙂");
+
+    sticker(set_name, &file_id, emoji)
+}
+
+/// Static sticker in the sticker set with the specified name
+pub fn sticker(set_name: &str, file_id: &str, emoji: &str) -> Value {
+    json!({
+        "file_id": file_id,
+        "file_unique_id": file_id,
+        "type": "regular",
+        "width": 512,
+        "height": 512,
+        "is_animated": false,
+        "is_video": false,
+        "emoji": emoji,
+        "set_name": set_name,
+    })
+}
+
+pub fn sticker_set(name: &str, title: &str, stickers: Vec<Value>) -> Value {
+    json!({
+        "name": name,
+        "title": title,
+        "sticker_type": "regular",
+        "stickers": stickers,
+    })
+}
+
+/// Update with the message from the user in the private chat with the bot
+pub fn message_update(update_id: i64, user_id: i64, content: Value) -> Update {
+    let mut message = json!({
+        "message_id": update_id,
+        "date": 0,
+        "chat": { "id": user_id, "type": "private", "first_name": "Test" },
+        "from": { "id": user_id, "is_bot": false, "first_name": "Test" },
+    });
+
+    message.as_object_mut().expect("message is object").extend(
+        content
+            .as_object()
+            .expect("content of message should be object")
+            .clone(),
+    );
+
+    serde_json::from_value(json!({ "update_id": update_id, "message": message }))
+        .expect("invalid update")
+}
+
+type FeedUpdate = Box<dyn Fn(Update) -> Pin<Box<dyn Future<Output = ()>>>>;
+
+/// Replaces middleware of the client application, because client can't be created without
+/// connection to Telegram (handlers, which need client, can't be tested)
+struct NoClientMiddleware;
+
+#[async_trait]
+impl OuterMiddleware for NoClientMiddleware {
+    async fn call(&self, request: RouterRequest) -> Result<MiddlewareResponse, EventErrorKind> {
+        Ok((request, EventReturn::default()))
+    }
+}
+
+/// Bot with the router from `main.rs`, which uses fake Bot API server,
+/// in-memory FSM storage and in-memory SQLite database
+pub struct TestBot {
+    pub api: FakeBotApi,
+    pub bot: Bot,
+    pub pool: SqlitePool,
+    pub storage: MemoryStorage,
+    feed_update: FeedUpdate,
+}
+
+impl TestBot {
+    pub async fn new() -> Self {
+        let api = FakeBotApi::start().await;
+        let bot = api.bot();
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+
+        let storage = MemoryStorage::new();
+
+        let mut main_router: Router<Reqwest> = Router::new("main");
+        main_router.include(
+            private_router(
+                storage.clone(),
+                &bot,
+                pool.clone(),
+                NoClientMiddleware,
+                Quotas::new(pool.clone(), &QuotasConfig::default()),
+                Blocklist::new(pool.clone()),
+            )
+            .await,
+        );
+
+        let dispatcher = Dispatcher::builder()
+            .bot(bot.clone())
+            .allowed_updates(main_router.resolve_used_update_types())
+            .router(main_router)
+            .build();
+
+        let service = Arc::new(
+            dispatcher
+                .to_service_provider_default()
+                .expect("error occurded while convert the service factory to the service"),
+        );
+
+        let feed_bot = bot.clone();
+        let feed_update: FeedUpdate = Box::new(move |update| {
+            let service = service.clone();
+            let bot = feed_bot.clone();
+
+            Box::pin(async move {
+                service
+                    .feed_update(bot, update)
+                    .await
+                    .expect("error occurded while process update");
+            })
+        });
+
+        Self {
+            api,
+            bot,
+            pool,
+            storage,
+            feed_update,
+        }
+    }
+
+    /// Process update by the bot and wait for all handlers to finish
+    pub async fn feed_update(&self, update: Update) {
+        (self.feed_update)(update).await
+    }
+}
+
+#[tokio::test]
+async fn fake_bot_api_test() {
+    use telers::methods::{GetMe, GetStickerSet};
+
+    let api = FakeBotApi::start().await;
+    let bot = api.bot();
+
+    api.add_sticker_set(sticker_set(
+        "original",
+        "Original",
+        vec![sticker("original", "file_id", "😀")],
+    ));
+
+    let me = bot.send(GetMe::new()).await.unwrap();
+    assert_eq!(me.username.as_deref(), Some(TEST_BOT_USERNAME));
+
+    let sticker_set = bot.send(GetStickerSet::new("original")).await.unwrap();
+    assert_eq!(sticker_set.stickers.len(), 1);
+
+    assert!(bot.send(GetStickerSet::new("unknown")).await.is_err());
+
+    let methods: Vec<String> = api.calls().into_iter().map(|call| call.method).collect();
+    assert_eq!(methods, ["getMe", "getStickerSet", "getStickerSet"]);
+}
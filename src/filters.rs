@@ -0,0 +1,35 @@
+use std::{collections::HashSet, sync::Arc};
+
+use async_trait::async_trait;
+use telers::{router::Request, Filter};
+
+/// Pass only updates of the users, whose IDs are specified in `admin_ids` of the config
+#[derive(Debug, Clone)]
+pub struct Admin {
+    admin_ids: Arc<HashSet<i64>>,
+}
+
+impl Admin {
+    pub fn new(admin_ids: impl IntoIterator<Item = i64>) -> Self {
+        Self {
+            admin_ids: Arc::new(admin_ids.into_iter().collect()),
+        }
+    }
+
+    pub fn is_admin(&self, user_id: i64) -> bool {
+        self.admin_ids.contains(&user_id)
+    }
+}
+
+#[async_trait]
+impl<Client> Filter<Client> for Admin
+where
+    Client: Send + Sync + 'static,
+{
+    async fn check(&self, request: &Request<Client>) -> bool {
+        request
+            .update
+            .from_id()
+            .is_some_and(|user_id| self.is_admin(user_id))
+    }
+}
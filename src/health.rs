@@ -0,0 +1,254 @@
+use std::{io, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use axum::{http::StatusCode, routing::get, Json, Router as HttpRouter};
+use grammers_client::Client;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use sqlx::{database::HasArguments, Database, Executor, IntoArguments, Pool};
+use tokio::{net::TcpListener, sync::Mutex};
+
+use crate::polling::PollingStatus;
+
+/// Maximum duration of the single check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Check of the dependency, without which bot can't process updates
+#[async_trait]
+pub trait ReadinessCheck: Send + Sync {
+    /// Name of the check in the response of `/readyz`
+    fn name(&self) -> &'static str;
+
+    async fn check(&self) -> Result<(), String>;
+}
+
+/// Check that database answers to `SELECT 1`
+pub struct DatabaseCheck<DB: Database> {
+    pool: Pool<DB>,
+}
+
+impl<DB: Database> DatabaseCheck<DB> {
+    pub const fn new(pool: Pool<DB>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl<DB> ReadinessCheck for DatabaseCheck<DB>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+{
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let mut conn = self.pool.acquire().await.map_err(|err| err.to_string())?;
+
+        sqlx::query("SELECT 1")
+            .execute(&mut *conn)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Check that client application is authorized
+pub struct ClientCheck {
+    client: Arc<Mutex<Client>>,
+}
+
+impl ClientCheck {
+    pub const fn new(client: Arc<Mutex<Client>>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ReadinessCheck for ClientCheck {
+    fn name(&self) -> &'static str {
+        "client"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let client = self.client.lock().await.clone();
+
+        match client.is_authorized().await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("client is not authorized".to_owned()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+/// Check that the last `getUpdates` succeeded recently
+pub struct PollingCheck {
+    status: PollingStatus,
+    max_age: Duration,
+}
+
+impl PollingCheck {
+    pub const fn new(status: PollingStatus, max_age: Duration) -> Self {
+        Self { status, max_age }
+    }
+}
+
+#[async_trait]
+impl ReadinessCheck for PollingCheck {
+    fn name(&self) -> &'static str {
+        "polling"
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let Some(last_success) = self.status.last_success() else {
+            return Err("getUpdates didn't succeed yet".to_owned());
+        };
+
+        let age = last_success.elapsed();
+
+        if age > self.max_age {
+            return Err(format!(
+                "last successful getUpdates was {} seconds ago",
+                age.as_secs()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CheckStatus {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run every check and return `503 Service Unavailable`, if any of them failed
+async fn readiness(checks: &[Box<dyn ReadinessCheck>]) -> (StatusCode, Json<Value>) {
+    let mut statuses = Map::new();
+    let mut ready = true;
+
+    for check in checks {
+        let result = match tokio::time::timeout(CHECK_TIMEOUT, check.check()).await {
+            Ok(result) => result,
+            Err(_) => Err("check timed out".to_owned()),
+        };
+
+        let status = match result {
+            Ok(()) => CheckStatus {
+                status: "ok",
+                error: None,
+            },
+            Err(err) => {
+                ready = false;
+
+                CheckStatus {
+                    status: "fail",
+                    error: Some(err),
+                }
+            }
+        };
+
+        statuses.insert(
+            check.name().to_owned(),
+            serde_json::to_value(status).expect("status should be serialized"),
+        );
+    }
+
+    let (status_code, status) = if ready {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "fail")
+    };
+
+    (
+        status_code,
+        Json(json!({ "status": status, "checks": statuses })),
+    )
+}
+
+/// Create HTTP router with `/healthz` (liveness) and `/readyz` (readiness) paths
+pub fn health_router(checks: Vec<Box<dyn ReadinessCheck>>) -> HttpRouter {
+    let checks = Arc::new(checks);
+
+    HttpRouter::new()
+        .route(
+            "/healthz",
+            get(|| async { Json(json!({ "status": "ok" })) }),
+        )
+        .route(
+            "/readyz",
+            get(move || {
+                let checks = checks.clone();
+
+                async move { readiness(&checks).await }
+            }),
+        )
+}
+
+pub async fn serve_health(address: &str, checks: Vec<Box<dyn ReadinessCheck>>) -> io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+
+    axum::serve(listener, health_router(checks)).await
+}
+
+#[tokio::test]
+async fn health_router_test() {
+    struct StaticCheck(&'static str, Result<(), String>);
+
+    #[async_trait]
+    impl ReadinessCheck for StaticCheck {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn check(&self) -> Result<(), String> {
+            self.1.clone()
+        }
+    }
+
+    let status = PollingStatus::default();
+
+    let router = health_router(vec![
+        Box::new(StaticCheck("database", Ok(()))),
+        Box::new(StaticCheck(
+            "client",
+            Err("client is not authorized".to_owned()),
+        )),
+        Box::new(PollingCheck::new(status.clone(), Duration::from_secs(60))),
+    ]);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let response = reqwest::get(format!("{url}/healthz")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = reqwest::get(format!("{url}/readyz")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body: Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "status": "fail",
+            "checks": {
+                "database": { "status": "ok" },
+                "client": { "status": "fail", "error": "client is not authorized" },
+                "polling": { "status": "fail", "error": "getUpdates didn't succeed yet" },
+            },
+        })
+    );
+
+    status.succeeded();
+
+    let response = reqwest::get(format!("{url}/readyz")).await.unwrap();
+    let body: Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(body["checks"]["polling"], json!({ "status": "ok" }));
+}
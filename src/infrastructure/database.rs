@@ -1,3 +1,54 @@
+use std::collections::HashSet;
+
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migrator},
+    Database, Pool,
+};
+
+pub mod blocked_sets;
+pub mod broadcasts;
+#[cfg(test)]
+pub mod memory;
 pub mod models;
+pub mod quota_events;
 pub mod repositories;
 pub mod uow;
+
+/// Postgres migrations from `src/infrastructure/database/migrations`, embedded into binary
+pub static MIGRATOR: Migrator = sqlx::migrate!("src/infrastructure/database/migrations");
+
+/// SQLite migrations from `src/infrastructure/database/sqlite_migrations`, embedded into binary
+pub static SQLITE_MIGRATOR: Migrator =
+    sqlx::migrate!("src/infrastructure/database/sqlite_migrations");
+
+/// Return versions of embedded migrations, which are not applied to the database yet
+pub async fn pending_migrations<DB>(
+    migrator: &Migrator,
+    pool: &Pool<DB>,
+) -> Result<Vec<i64>, MigrateError>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    let mut conn = pool.acquire().await?;
+
+    conn.ensure_migrations_table().await?;
+
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(MigrateError::Dirty(version));
+    }
+
+    let applied: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+
+    Ok(migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| migration.version)
+        .collect())
+}
@@ -0,0 +1,338 @@
+use async_trait::async_trait;
+use sea_query::{
+    Alias, Asterisk, Cond, DeleteStatement, Expr, Func, InsertStatement, OnConflict,
+    PostgresQueryBuilder, Query, SelectStatement, SimpleExpr, SqliteQueryBuilder,
+};
+use sea_query_binder::SqlxBinder as _;
+use sqlx::{PgPool, SqlitePool};
+use tracing::{debug, Instrument as _};
+
+use super::repositories::query_span;
+
+/// Sticker sets, which creators asked not to copy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockedSet {
+    /// One sticker set by its short name (in lowercase)
+    ShortName(String),
+    /// Every sticker set of the owner
+    OwnerId(i64),
+}
+
+impl BlockedSet {
+    /// Short names are case insensitive in Telegram, so they are kept in lowercase
+    pub fn short_name(short_name: &str) -> Self {
+        Self::ShortName(short_name.to_lowercase())
+    }
+
+    fn column(&self) -> Alias {
+        match self {
+            Self::ShortName(_) => Alias::new("short_name"),
+            Self::OwnerId(_) => Alias::new("owner_id"),
+        }
+    }
+
+    fn value(&self) -> SimpleExpr {
+        match self {
+            Self::ShortName(short_name) => short_name.as_str().into(),
+            Self::OwnerId(owner_id) => (*owner_id).into(),
+        }
+    }
+}
+
+/// Pool of the database, which contains `blocked_sets` table
+#[async_trait]
+pub trait BlockedSetsPool: Send + Sync + 'static {
+    /// Return `false`, if the sticker set is already blocked
+    async fn block(&self, set: &BlockedSet) -> Result<bool, sqlx::Error>;
+
+    /// Block the sticker set on request of its creator.
+    /// Return `false`, if the sticker set is already blocked.
+    async fn protect(&self, set: &BlockedSet, user_id: i64) -> Result<bool, sqlx::Error>;
+
+    /// Remove sticker sets, which are protected by the creator, and return their number
+    async fn unprotect(&self, user_id: i64) -> Result<u64, sqlx::Error>;
+
+    /// Return `false`, if the sticker set isn't blocked
+    async fn unblock(&self, set: &BlockedSet) -> Result<bool, sqlx::Error>;
+
+    /// Return `true`, if the sticker set is blocked by its short name or by its owner
+    async fn is_blocked(
+        &self,
+        short_name: &str,
+        owner_id: Option<i64>,
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Return `true`, if any owner is blocked, otherwise owners of sticker sets don't need to be checked
+    async fn has_blocked_owners(&self) -> Result<bool, sqlx::Error>;
+}
+
+fn block_query(set: &BlockedSet, protected_by: Option<i64>) -> InsertStatement {
+    Query::insert()
+        .into_table(Alias::new("blocked_sets"))
+        .columns([set.column(), Alias::new("protected_by")])
+        .values_panic([set.value(), protected_by.into()])
+        .on_conflict(OnConflict::column(set.column()).do_nothing().to_owned())
+        .to_owned()
+}
+
+fn unblock_query(set: &BlockedSet) -> DeleteStatement {
+    Query::delete()
+        .from_table(Alias::new("blocked_sets"))
+        .and_where(Expr::col(set.column()).eq(set.value()))
+        .to_owned()
+}
+
+fn unprotect_query(user_id: i64) -> DeleteStatement {
+    Query::delete()
+        .from_table(Alias::new("blocked_sets"))
+        .and_where(Expr::col(Alias::new("protected_by")).eq(user_id))
+        .to_owned()
+}
+
+fn is_blocked_query(short_name: &str, owner_id: Option<i64>) -> SelectStatement {
+    let short_name = BlockedSet::short_name(short_name);
+
+    let mut cond = Cond::any().add(Expr::col(short_name.column()).eq(short_name.value()));
+    if let Some(owner_id) = owner_id {
+        let owner_id = BlockedSet::OwnerId(owner_id);
+
+        cond = cond.add(Expr::col(owner_id.column()).eq(owner_id.value()));
+    }
+
+    Query::select()
+        .expr(Func::count(Expr::col(Asterisk)))
+        .from(Alias::new("blocked_sets"))
+        .cond_where(cond)
+        .to_owned()
+}
+
+fn has_blocked_owners_query() -> SelectStatement {
+    Query::select()
+        .expr(Func::count(Expr::col(Asterisk)))
+        .from(Alias::new("blocked_sets"))
+        .and_where(Expr::col(Alias::new("owner_id")).is_not_null())
+        .to_owned()
+}
+
+#[async_trait]
+impl BlockedSetsPool for PgPool {
+    async fn block(&self, set: &BlockedSet) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = block_query(set, None).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn protect(&self, set: &BlockedSet, user_id: i64) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = block_query(set, Some(user_id)).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn unprotect(&self, user_id: i64) -> Result<u64, sqlx::Error> {
+        let (sql_query, values) = unprotect_query(user_id).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn unblock(&self, set: &BlockedSet) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = unblock_query(set).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn is_blocked(
+        &self,
+        short_name: &str,
+        owner_id: Option<i64>,
+    ) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) =
+            is_blocked_query(short_name, owner_id).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let count: i64 = sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    async fn has_blocked_owners(&self) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = has_blocked_owners_query().build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let count: i64 = sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(count > 0)
+    }
+}
+
+#[async_trait]
+impl BlockedSetsPool for SqlitePool {
+    async fn block(&self, set: &BlockedSet) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = block_query(set, None).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn protect(&self, set: &BlockedSet, user_id: i64) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = block_query(set, Some(user_id)).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn unprotect(&self, user_id: i64) -> Result<u64, sqlx::Error> {
+        let (sql_query, values) = unprotect_query(user_id).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn unblock(&self, set: &BlockedSet) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = unblock_query(set).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn is_blocked(
+        &self,
+        short_name: &str,
+        owner_id: Option<i64>,
+    ) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) =
+            is_blocked_query(short_name, owner_id).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let count: i64 = sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    async fn has_blocked_owners(&self) -> Result<bool, sqlx::Error> {
+        let (sql_query, values) = has_blocked_owners_query().build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let count: i64 = sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(count > 0)
+    }
+}
+
+#[tokio::test]
+async fn sqlite_blocked_sets_test() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::infrastructure::database::SQLITE_MIGRATOR;
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    SQLITE_MIGRATOR.run(&pool).await.unwrap();
+
+    assert!(!pool.has_blocked_owners().await.unwrap());
+
+    assert!(pool
+        .block(&BlockedSet::short_name("Blocked"))
+        .await
+        .unwrap());
+    assert!(!pool
+        .block(&BlockedSet::short_name("blocked"))
+        .await
+        .unwrap());
+    assert!(pool.block(&BlockedSet::OwnerId(7)).await.unwrap());
+
+    assert!(pool.has_blocked_owners().await.unwrap());
+    assert!(pool.is_blocked("BLOCKED", None).await.unwrap());
+    assert!(pool.is_blocked("other", Some(7)).await.unwrap());
+    assert!(!pool.is_blocked("other", Some(8)).await.unwrap());
+
+    assert!(pool.unblock(&BlockedSet::OwnerId(7)).await.unwrap());
+    assert!(!pool.unblock(&BlockedSet::OwnerId(7)).await.unwrap());
+    assert!(!pool.is_blocked("other", Some(7)).await.unwrap());
+
+    // creator removes only sticker sets, which are protected by them
+    assert!(pool.protect(&BlockedSet::OwnerId(9), 9).await.unwrap());
+    assert!(pool
+        .protect(&BlockedSet::short_name("mine"), 9)
+        .await
+        .unwrap());
+    assert!(!pool
+        .protect(&BlockedSet::short_name("blocked"), 9)
+        .await
+        .unwrap());
+
+    assert!(pool.is_blocked("other", Some(9)).await.unwrap());
+    assert_eq!(pool.unprotect(9).await.unwrap(), 2);
+    assert!(!pool.is_blocked("mine", Some(9)).await.unwrap());
+    assert!(pool.is_blocked("blocked", Some(9)).await.unwrap());
+}
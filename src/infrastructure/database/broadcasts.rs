@@ -0,0 +1,289 @@
+use async_trait::async_trait;
+use sea_query::{
+    Alias, Expr, InsertStatement, OnConflict, Order, PostgresQueryBuilder, Query, SelectStatement,
+    SqliteQueryBuilder, UpdateStatement,
+};
+use sea_query_binder::SqlxBinder as _;
+use sqlx::{FromRow, PgPool, SqlitePool};
+use tracing::{debug, Instrument as _};
+
+use super::repositories::query_span;
+
+/// Message of the admin, which is copied to every user in order of their IDs
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct Broadcast {
+    pub id: i64,
+    /// Chat of the admin, which contains the message
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Cursor of the broadcast: users with IDs up to this one are already processed
+    pub last_tg_id: i64,
+    pub delivered: i64,
+    pub failed: i64,
+    /// Users, who blocked the bot
+    pub blocked: i64,
+}
+
+/// Pool of the database, which contains `broadcasts` table
+#[async_trait]
+pub trait BroadcastsPool: Send + Sync + 'static {
+    /// Return `None`, if the message is already broadcast
+    async fn create(&self, chat_id: i64, message_id: i64)
+        -> Result<Option<Broadcast>, sqlx::Error>;
+
+    /// Return broadcasts, which are not finished yet (for example, interrupted by restart)
+    async fn unfinished(&self) -> Result<Vec<Broadcast>, sqlx::Error>;
+
+    /// Return IDs of the active users after `tg_id` in ascending order
+    async fn users_after(&self, tg_id: i64, limit: u64) -> Result<Vec<i64>, sqlx::Error>;
+
+    /// Save cursor and counters of the broadcast
+    async fn save(&self, broadcast: &Broadcast, finished: bool) -> Result<(), sqlx::Error>;
+
+    /// Mark the user, who blocked the bot, as inactive
+    async fn set_inactive(&self, tg_id: i64) -> Result<(), sqlx::Error>;
+}
+
+const COLUMNS: [&str; 7] = [
+    "id",
+    "chat_id",
+    "message_id",
+    "last_tg_id",
+    "delivered",
+    "failed",
+    "blocked",
+];
+
+fn insert_query(chat_id: i64, message_id: i64) -> InsertStatement {
+    Query::insert()
+        .into_table(Alias::new("broadcasts"))
+        .columns([Alias::new("chat_id"), Alias::new("message_id")])
+        .values_panic([chat_id.into(), message_id.into()])
+        .on_conflict(
+            OnConflict::columns([Alias::new("chat_id"), Alias::new("message_id")])
+                .do_nothing()
+                .to_owned(),
+        )
+        .returning(Query::returning().columns(COLUMNS.map(Alias::new)))
+        .to_owned()
+}
+
+fn unfinished_query() -> SelectStatement {
+    Query::select()
+        .columns(COLUMNS.map(Alias::new))
+        .from(Alias::new("broadcasts"))
+        .and_where(Expr::col(Alias::new("finished")).eq(false))
+        .order_by(Alias::new("id"), Order::Asc)
+        .to_owned()
+}
+
+fn users_after_query(tg_id: i64, limit: u64) -> SelectStatement {
+    Query::select()
+        .column(Alias::new("tg_id"))
+        .from(Alias::new("users"))
+        .and_where(Expr::col(Alias::new("tg_id")).gt(tg_id))
+        .and_where(Expr::col(Alias::new("active")).eq(true))
+        .order_by(Alias::new("tg_id"), Order::Asc)
+        .limit(limit)
+        .to_owned()
+}
+
+fn save_query(broadcast: &Broadcast, finished: bool) -> UpdateStatement {
+    Query::update()
+        .table(Alias::new("broadcasts"))
+        .values([
+            (Alias::new("last_tg_id"), broadcast.last_tg_id.into()),
+            (Alias::new("delivered"), broadcast.delivered.into()),
+            (Alias::new("failed"), broadcast.failed.into()),
+            (Alias::new("blocked"), broadcast.blocked.into()),
+            (Alias::new("finished"), finished.into()),
+        ])
+        .and_where(Expr::col(Alias::new("id")).eq(broadcast.id))
+        .to_owned()
+}
+
+fn set_inactive_query(tg_id: i64) -> UpdateStatement {
+    Query::update()
+        .table(Alias::new("users"))
+        .value(Alias::new("active"), false)
+        .and_where(Expr::col(Alias::new("tg_id")).eq(tg_id))
+        .to_owned()
+}
+
+#[async_trait]
+impl BroadcastsPool for PgPool {
+    async fn create(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+    ) -> Result<Option<Broadcast>, sqlx::Error> {
+        let (sql_query, values) =
+            insert_query(chat_id, message_id).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_as_with(&sql_query, values)
+            .fetch_optional(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await
+    }
+
+    async fn unfinished(&self) -> Result<Vec<Broadcast>, sqlx::Error> {
+        let (sql_query, values) = unfinished_query().build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_as_with(&sql_query, values)
+            .fetch_all(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await
+    }
+
+    async fn users_after(&self, tg_id: i64, limit: u64) -> Result<Vec<i64>, sqlx::Error> {
+        let (sql_query, values) = users_after_query(tg_id, limit).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_scalar_with(&sql_query, values)
+            .fetch_all(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await
+    }
+
+    async fn save(&self, broadcast: &Broadcast, finished: bool) -> Result<(), sqlx::Error> {
+        let (sql_query, values) = save_query(broadcast, finished).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_inactive(&self, tg_id: i64) -> Result<(), sqlx::Error> {
+        let (sql_query, values) = set_inactive_query(tg_id).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BroadcastsPool for SqlitePool {
+    async fn create(
+        &self,
+        chat_id: i64,
+        message_id: i64,
+    ) -> Result<Option<Broadcast>, sqlx::Error> {
+        let (sql_query, values) = insert_query(chat_id, message_id).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_as_with(&sql_query, values)
+            .fetch_optional(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+    }
+
+    async fn unfinished(&self) -> Result<Vec<Broadcast>, sqlx::Error> {
+        let (sql_query, values) = unfinished_query().build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_as_with(&sql_query, values)
+            .fetch_all(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+    }
+
+    async fn users_after(&self, tg_id: i64, limit: u64) -> Result<Vec<i64>, sqlx::Error> {
+        let (sql_query, values) = users_after_query(tg_id, limit).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_scalar_with(&sql_query, values)
+            .fetch_all(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+    }
+
+    async fn save(&self, broadcast: &Broadcast, finished: bool) -> Result<(), sqlx::Error> {
+        let (sql_query, values) = save_query(broadcast, finished).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_inactive(&self, tg_id: i64) -> Result<(), sqlx::Error> {
+        let (sql_query, values) = set_inactive_query(tg_id).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn sqlite_broadcasts_test() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::infrastructure::database::SQLITE_MIGRATOR;
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    SQLITE_MIGRATOR.run(&pool).await.unwrap();
+
+    sqlx::query("INSERT INTO users (tg_id) VALUES (3), (1), (2)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut broadcast = pool.create(1, 10).await.unwrap().unwrap();
+
+    assert_eq!(broadcast.last_tg_id, 0);
+    assert_eq!(pool.create(1, 10).await.unwrap(), None);
+    assert_eq!(pool.users_after(0, 2).await.unwrap(), [1, 2]);
+    assert_eq!(pool.users_after(2, 2).await.unwrap(), [3]);
+
+    broadcast.last_tg_id = 2;
+    broadcast.blocked = 1;
+    pool.save(&broadcast, false).await.unwrap();
+    pool.set_inactive(2).await.unwrap();
+
+    assert_eq!(pool.unfinished().await.unwrap(), [broadcast.clone()]);
+
+    pool.save(&broadcast, true).await.unwrap();
+
+    assert_eq!(pool.unfinished().await.unwrap(), []);
+
+    let active: Vec<bool> = sqlx::query_scalar("SELECT active FROM users ORDER BY tg_id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(active, [true, false, true]);
+    // the user, who blocked the bot, is skipped
+    assert_eq!(pool.users_after(0, 3).await.unwrap(), [1, 3]);
+}
@@ -0,0 +1,378 @@
+//! In-memory implementation of the database layer, which is used in tests instead of the live database
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use sqlx::types::time::OffsetDateTime;
+use telers::FromContext;
+
+use crate::{
+    application::{
+        common::{
+            exceptions::{BeginError, CommitError, RepoError, RepoKind, RollbackError},
+            traits::uow::{UoW as UnitOfWork, UoWFactory as UoWFactoryTrait},
+        },
+        set::{
+            dto::{
+                count::Count, create::Create as CreateSet, delete_by_short_name::DeleteByShortName,
+                get_by_short_name::GetByShortName, get_by_tg_id::GetByTgID as GetSetsByTgID,
+                set_deleted_col_by_short_name::SetDeletedColByShortName,
+            },
+            exceptions::{SetShortNameAlreadyExist, SetShortNameNotExist, SetTgIdNotExist},
+            traits::SetRepo,
+        },
+        user::{
+            dto::{
+                create::Create as CreateUser, get_by_tg_id::GetByTgID as GetUserByTgID,
+                set_active_col::SetActiveCol, set_banned_col::SetBannedCol,
+            },
+            exceptions::{UserTgIdAlreadyExists, UserTgIdNotExist},
+            traits::UserRepo,
+        },
+    },
+    domain::entities::{set::Set, user::User},
+};
+
+/// Content of the in-memory database
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDatabase {
+    pub users: Vec<User>,
+    pub sets: Vec<Set>,
+    /// If `true`, every query returns unexpected error (like if connection to the database is lost)
+    pub unavailable: bool,
+}
+
+impl MemoryDatabase {
+    pub fn check_available(&self) -> Result<(), RepoError> {
+        if self.unavailable {
+            return Err(RepoError::new("database is unavailable"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default, FromContext)]
+#[context(key = "uow_factory")]
+pub struct UoWFactory {
+    database: Arc<Mutex<MemoryDatabase>>,
+}
+
+impl UoWFactory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return copy of the committed data
+    pub fn snapshot(&self) -> MemoryDatabase {
+        self.database
+            .lock()
+            .expect("database lock poisoned")
+            .clone()
+    }
+
+    /// Create unit of work, every query of which fails with unexpected error (like if connection to the database is lost)
+    pub fn create_unavailable_uow(&self) -> UoW {
+        UoW {
+            database: self.database.clone(),
+            transaction: None,
+            unavailable: true,
+        }
+    }
+}
+
+impl UoWFactoryTrait for UoWFactory {
+    type UoW = UoW;
+
+    fn create_uow(&self) -> Self::UoW {
+        UoW {
+            database: self.database.clone(),
+            transaction: None,
+            unavailable: false,
+        }
+    }
+}
+
+/// Unit of work, which works with copy of the data during transaction
+/// and replaces committed data with this copy on commit
+pub struct UoW {
+    database: Arc<Mutex<MemoryDatabase>>,
+    transaction: Option<MemoryDatabase>,
+    unavailable: bool,
+}
+
+#[async_trait]
+impl UnitOfWork for UoW {
+    type Connection<'a> = &'a mut MemoryDatabase;
+
+    type UserRepo<'a> = UserRepoImpl<'a>;
+
+    type SetRepo<'a> = SetRepoImpl<'a>;
+
+    async fn connect(&mut self) -> Result<Self::Connection<'_>, BeginError> {
+        if self.transaction.is_none() {
+            self.begin().await?
+        }
+
+        Ok(self
+            .transaction
+            .as_mut()
+            .expect("transaction is not specified"))
+    }
+
+    async fn begin(&mut self) -> Result<(), BeginError> {
+        let database = self
+            .database
+            .lock()
+            .map_err(|err| BeginError::new(err.to_string()))?;
+
+        self.transaction = Some(MemoryDatabase {
+            unavailable: self.unavailable,
+            ..database.clone()
+        });
+
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> Result<(), CommitError> {
+        if self.unavailable {
+            self.transaction = None;
+            return Err(CommitError::new("database is unavailable"));
+        }
+
+        if let Some(transaction) = self.transaction.take() {
+            *self
+                .database
+                .lock()
+                .map_err(|err| CommitError::new(err.to_string()))? = transaction;
+        }
+
+        Ok(())
+    }
+
+    async fn rollback(&mut self) -> Result<(), RollbackError> {
+        self.transaction = None;
+
+        Ok(())
+    }
+
+    async fn user_repo(&mut self) -> Result<Self::UserRepo<'_>, BeginError> {
+        Ok(UserRepoImpl {
+            database: self.connect().await?,
+        })
+    }
+
+    async fn set_repo(&mut self) -> Result<Self::SetRepo<'_>, BeginError> {
+        Ok(SetRepoImpl {
+            database: self.connect().await?,
+        })
+    }
+}
+
+pub struct UserRepoImpl<'a> {
+    database: &'a mut MemoryDatabase,
+}
+
+pub struct SetRepoImpl<'a> {
+    database: &'a mut MemoryDatabase,
+}
+
+#[async_trait]
+impl UserRepo for UserRepoImpl<'_> {
+    async fn create(&mut self, user: CreateUser) -> Result<(), RepoKind<UserTgIdAlreadyExists>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        if database.users.iter().any(|u| u.tg_id == user.tg_id()) {
+            return Err(RepoKind::exception(UserTgIdAlreadyExists::new(
+                user.tg_id(),
+                "duplicate key value violates unique constraint",
+            )));
+        }
+
+        database.users.push(User {
+            tg_id: user.tg_id(),
+            created: OffsetDateTime::now_utc(),
+            banned: false,
+            active: true,
+        });
+
+        Ok(())
+    }
+
+    async fn get_by_tg_id(
+        &mut self,
+        user: GetUserByTgID,
+    ) -> Result<User, RepoKind<UserTgIdNotExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        database
+            .users
+            .iter()
+            .find(|u| u.tg_id == user.tg_id())
+            .cloned()
+            .ok_or_else(|| {
+                RepoKind::exception(UserTgIdNotExist::new(user.tg_id(), "no rows returned"))
+            })
+    }
+
+    async fn set_banned_col(
+        &mut self,
+        user: SetBannedCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        let found = database
+            .users
+            .iter_mut()
+            .find(|u| u.tg_id == user.tg_id())
+            .ok_or_else(|| {
+                RepoKind::exception(UserTgIdNotExist::new(user.tg_id(), "no rows updated"))
+            })?;
+
+        found.banned = user.banned();
+
+        Ok(())
+    }
+
+    async fn set_active_col(
+        &mut self,
+        user: SetActiveCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        let found = database
+            .users
+            .iter_mut()
+            .find(|u| u.tg_id == user.tg_id())
+            .ok_or_else(|| {
+                RepoKind::exception(UserTgIdNotExist::new(user.tg_id(), "no rows updated"))
+            })?;
+
+        found.active = user.active();
+
+        Ok(())
+    }
+
+    async fn count(&mut self) -> Result<i64, RepoError> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        Ok(database.users.len() as i64)
+    }
+}
+
+#[async_trait]
+impl SetRepo for SetRepoImpl<'_> {
+    async fn create<'a>(
+        &'a mut self,
+        set: CreateSet<'a>,
+    ) -> Result<(), RepoKind<SetShortNameAlreadyExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        if database
+            .sets
+            .iter()
+            .any(|s| s.short_name == set.short_name())
+        {
+            return Err(RepoKind::exception(SetShortNameAlreadyExist::new(
+                set.short_name().to_owned(),
+                "duplicate key value violates unique constraint",
+            )));
+        }
+
+        database.sets.push(Set {
+            tg_id: set.tg_id(),
+            short_name: set.short_name().to_owned(),
+            deleted: false,
+            title: set.title().to_owned(),
+            created: OffsetDateTime::now_utc(),
+        });
+
+        Ok(())
+    }
+
+    /// Like `DELETE` query, doesn't return exception if set doesn't exist
+    async fn delete_by_short_name<'a>(
+        &'a mut self,
+        set: DeleteByShortName<'a>,
+    ) -> Result<(), RepoKind<SetShortNameNotExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        database.sets.retain(|s| s.short_name != set.short_name());
+
+        Ok(())
+    }
+
+    async fn get_by_tg_id(
+        &mut self,
+        set: GetSetsByTgID,
+    ) -> Result<Vec<Set>, RepoKind<SetTgIdNotExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        Ok(database
+            .sets
+            .iter()
+            .filter(|s| s.tg_id == set.tg_id())
+            .filter(|s| set.get_deleted().is_none_or(|deleted| s.deleted == deleted))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_one_by_short_name<'a>(
+        &'a mut self,
+        set: GetByShortName<'a>,
+    ) -> Result<Set, RepoKind<SetShortNameNotExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        database
+            .sets
+            .iter()
+            .find(|s| s.short_name == set.short_name())
+            .cloned()
+            .ok_or_else(|| {
+                RepoKind::exception(SetShortNameNotExist::new(
+                    set.short_name().to_owned(),
+                    "no rows returned",
+                ))
+            })
+    }
+
+    /// Like `UPDATE` query, doesn't return exception if set doesn't exist
+    async fn set_deleted_col_by_short_name<'a>(
+        &'a mut self,
+        set: SetDeletedColByShortName<'a>,
+    ) -> Result<(), RepoKind<SetShortNameNotExist>> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        database
+            .sets
+            .iter_mut()
+            .filter(|s| s.short_name == set.short_name())
+            .for_each(|s| s.deleted = set.deleted());
+
+        Ok(())
+    }
+
+    async fn count(&mut self, set: Count) -> Result<i64, RepoError> {
+        let database = &mut *self.database;
+        database.check_available()?;
+
+        let today = OffsetDateTime::now_utc().date();
+
+        Ok(database
+            .sets
+            .iter()
+            .filter(|s| set.get_deleted().is_none_or(|deleted| s.deleted == deleted))
+            .filter(|s| !set.created_today() || s.created.date() == today)
+            .count() as i64)
+    }
+}
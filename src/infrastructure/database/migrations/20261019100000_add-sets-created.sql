@@ -0,0 +1,5 @@
+BEGIN;
+
+ALTER TABLE sets ADD COLUMN IF NOT EXISTS created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+COMMIT;
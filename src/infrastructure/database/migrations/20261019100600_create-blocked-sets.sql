@@ -0,0 +1,11 @@
+BEGIN;
+
+-- every row blocks either one sticker set by its short name (in lowercase) or every sticker set of the owner
+CREATE TABLE IF NOT EXISTS blocked_sets (
+    short_name TEXT UNIQUE,
+    owner_id BIGINT UNIQUE,
+    created TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((short_name IS NULL) <> (owner_id IS NULL))
+);
+
+COMMIT;
@@ -0,0 +1,6 @@
+BEGIN;
+
+-- creator of the sticker sets, who protected them with `/protectmypacks` (NULL, if blocked by admin)
+ALTER TABLE blocked_sets ADD COLUMN IF NOT EXISTS protected_by BIGINT;
+
+COMMIT;
@@ -1,5 +1,5 @@
 use crate::domain::entities::set::Set as SetEntitie;
-use sqlx::FromRow;
+use sqlx::{types::time::OffsetDateTime, FromRow};
 
 #[derive(Debug, Clone, PartialEq, Eq, FromRow)]
 pub struct Set {
@@ -7,6 +7,7 @@ pub struct Set {
     pub short_name: String,
     pub deleted: bool,
     pub title: String,
+    pub created: OffsetDateTime,
 }
 
 impl From<Set> for SetEntitie {
@@ -16,6 +17,7 @@ impl From<Set> for SetEntitie {
             short_name: value.short_name,
             deleted: value.deleted,
             title: value.title,
+            created: value.created,
         }
     }
 }
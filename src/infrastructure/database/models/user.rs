@@ -5,6 +5,9 @@ use sqlx::{types::time::OffsetDateTime, FromRow};
 pub struct User {
     pub tg_id: i64,
     pub created: OffsetDateTime,
+    pub banned: bool,
+    /// `false`, if the user blocked the bot
+    pub active: bool,
 }
 
 impl From<User> for UserEntitie {
@@ -12,6 +15,8 @@ impl From<User> for UserEntitie {
         Self {
             tg_id: value.tg_id,
             created: value.created,
+            banned: value.banned,
+            active: value.active,
         }
     }
 }
@@ -0,0 +1,337 @@
+use async_trait::async_trait;
+use sea_query::{
+    Alias, DeleteStatement, Expr, Func, InsertStatement, Order, PostgresQueryBuilder, Query,
+    SelectStatement, SimpleExpr, SqliteQueryBuilder, UpdateStatement,
+};
+use sea_query_binder::SqlxBinder as _;
+use sqlx::{types::time::OffsetDateTime, FromRow, PgPool, SqlitePool};
+use tracing::{debug, Instrument as _};
+
+use super::repositories::query_span;
+
+/// Usage of the limited action by the user (steal, added stickers, etc.)
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct QuotaEvent {
+    pub kind: String,
+    pub amount: i64,
+    pub created: OffsetDateTime,
+}
+
+/// Pool of the database, which contains `quota_events` table.
+/// Events are kept only during the last day, because longer limits are not used.
+#[async_trait]
+pub trait QuotaEventsPool: Send + Sync + 'static {
+    /// Check events of the user by `allowed` and record `usage` of the user, if it returns `true`.
+    /// Events of the user are locked until the usage is recorded, so concurrent usage can't exceed limits.
+    /// Return `false`, if nothing is recorded.
+    async fn record_if(
+        &self,
+        tg_id: i64,
+        usage: &[(&str, i64)],
+        allowed: &mut (dyn for<'a> FnMut(&'a [QuotaEvent]) -> bool + Send),
+    ) -> Result<bool, sqlx::Error>;
+
+    /// Remove the last recorded events of the usage (e.g. usage of the failed operation)
+    async fn refund(&self, tg_id: i64, usage: &[(&str, i64)]) -> Result<(), sqlx::Error>;
+
+    /// Remove events of all users older than a day and return their number
+    async fn remove_expired(&self) -> Result<u64, sqlx::Error>;
+}
+
+fn select_query(tg_id: i64) -> SelectStatement {
+    Query::select()
+        .columns([
+            Alias::new("kind"),
+            Alias::new("amount"),
+            Alias::new("created"),
+        ])
+        .from(Alias::new("quota_events"))
+        .and_where(Expr::col(Alias::new("tg_id")).eq(tg_id))
+        .to_owned()
+}
+
+fn insert_query(tg_id: i64, kind: &str, amount: i64) -> InsertStatement {
+    Query::insert()
+        .into_table(Alias::new("quota_events"))
+        .columns([
+            Alias::new("tg_id"),
+            Alias::new("kind"),
+            Alias::new("amount"),
+        ])
+        .values_panic([tg_id.into(), kind.into(), amount.into()])
+        .to_owned()
+}
+
+/// SQLite has only one writer, so update without changes makes other writers wait the transaction
+fn sqlite_lock_query(tg_id: i64) -> UpdateStatement {
+    Query::update()
+        .table(Alias::new("quota_events"))
+        .value(Alias::new("amount"), Expr::col(Alias::new("amount")))
+        .and_where(Expr::col(Alias::new("tg_id")).eq(tg_id))
+        .to_owned()
+}
+
+/// Lock is released at the end of the transaction
+fn postgres_lock_query(tg_id: i64) -> SelectStatement {
+    Query::select()
+        .expr(Func::cust(Alias::new("pg_advisory_xact_lock")).arg(tg_id))
+        .to_owned()
+}
+
+/// `row_id` is the name of hidden column of the database, which identifies the row
+fn refund_query(tg_id: i64, kind: &str, amount: i64, row_id: &str) -> DeleteStatement {
+    Query::delete()
+        .from_table(Alias::new("quota_events"))
+        .and_where(
+            Expr::col(Alias::new(row_id)).in_subquery(
+                Query::select()
+                    .column(Alias::new(row_id))
+                    .from(Alias::new("quota_events"))
+                    .and_where(Expr::col(Alias::new("tg_id")).eq(tg_id))
+                    .and_where(Expr::col(Alias::new("kind")).eq(kind))
+                    .and_where(Expr::col(Alias::new("amount")).eq(amount))
+                    .order_by(Alias::new("created"), Order::Desc)
+                    .limit(1)
+                    .to_owned(),
+            ),
+        )
+        .to_owned()
+}
+
+/// `day_ago` is expression of the database, which returns time a day ago
+fn delete_query(day_ago: SimpleExpr) -> DeleteStatement {
+    Query::delete()
+        .from_table(Alias::new("quota_events"))
+        .and_where(Expr::col(Alias::new("created")).lt(day_ago))
+        .to_owned()
+}
+
+#[async_trait]
+impl QuotaEventsPool for PgPool {
+    async fn record_if(
+        &self,
+        tg_id: i64,
+        usage: &[(&str, i64)],
+        allowed: &mut (dyn for<'a> FnMut(&'a [QuotaEvent]) -> bool + Send),
+    ) -> Result<bool, sqlx::Error> {
+        let mut transaction = self.begin().await?;
+
+        let (sql_query, values) = postgres_lock_query(tg_id).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(&mut *transaction)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        let (sql_query, values) = select_query(tg_id).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let events: Vec<QuotaEvent> = sqlx::query_as_with(&sql_query, values)
+            .fetch_all(&mut *transaction)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        if !allowed(&events) {
+            return Ok(false);
+        }
+
+        for (kind, amount) in usage {
+            let (sql_query, values) =
+                insert_query(tg_id, kind, *amount).build_sqlx(PostgresQueryBuilder);
+
+            debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+            sqlx::query_with(&sql_query, values)
+                .execute(&mut *transaction)
+                .instrument(query_span("postgresql", &sql_query))
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn refund(&self, tg_id: i64, usage: &[(&str, i64)]) -> Result<(), sqlx::Error> {
+        for (kind, amount) in usage {
+            let (sql_query, values) =
+                refund_query(tg_id, kind, *amount, "ctid").build_sqlx(PostgresQueryBuilder);
+
+            debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+            sqlx::query_with(&sql_query, values)
+                .execute(self)
+                .instrument(query_span("postgresql", &sql_query))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn remove_expired(&self) -> Result<u64, sqlx::Error> {
+        let (sql_query, values) =
+            delete_query(Expr::cust("NOW() - INTERVAL '1 day'")).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl QuotaEventsPool for SqlitePool {
+    async fn record_if(
+        &self,
+        tg_id: i64,
+        usage: &[(&str, i64)],
+        allowed: &mut (dyn for<'a> FnMut(&'a [QuotaEvent]) -> bool + Send),
+    ) -> Result<bool, sqlx::Error> {
+        let mut transaction = self.begin().await?;
+
+        let (sql_query, values) = sqlite_lock_query(tg_id).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(&mut *transaction)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        let (sql_query, values) = select_query(tg_id).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let events: Vec<QuotaEvent> = sqlx::query_as_with(&sql_query, values)
+            .fetch_all(&mut *transaction)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        if !allowed(&events) {
+            return Ok(false);
+        }
+
+        for (kind, amount) in usage {
+            let (sql_query, values) =
+                insert_query(tg_id, kind, *amount).build_sqlx(SqliteQueryBuilder);
+
+            debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+            sqlx::query_with(&sql_query, values)
+                .execute(&mut *transaction)
+                .instrument(query_span("sqlite", &sql_query))
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn refund(&self, tg_id: i64, usage: &[(&str, i64)]) -> Result<(), sqlx::Error> {
+        for (kind, amount) in usage {
+            let (sql_query, values) =
+                refund_query(tg_id, kind, *amount, "rowid").build_sqlx(SqliteQueryBuilder);
+
+            debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+            sqlx::query_with(&sql_query, values)
+                .execute(self)
+                .instrument(query_span("sqlite", &sql_query))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn remove_expired(&self) -> Result<u64, sqlx::Error> {
+        let (sql_query, values) =
+            delete_query(Expr::cust("datetime('now', '-1 day')")).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(self)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[tokio::test]
+async fn sqlite_quota_events_test() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::infrastructure::database::SQLITE_MIGRATOR;
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    SQLITE_MIGRATOR.run(&pool).await.unwrap();
+
+    sqlx::query("INSERT INTO quota_events (tg_id, kind, amount, created) VALUES (1, 'steal', 1, datetime('now', '-2 days'))")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let usage = |events: &[QuotaEvent]| {
+        events
+            .iter()
+            .map(|event| (event.kind.clone(), event.amount))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(pool
+        .record_if(1, &[("steal", 1), ("sticker", 50)], &mut |_| true)
+        .await
+        .unwrap());
+    assert!(pool
+        .record_if(2, &[("steal", 1)], &mut |_| true)
+        .await
+        .unwrap());
+
+    // nothing is recorded, when it's not allowed
+    let mut seen = Vec::new();
+    assert!(!pool
+        .record_if(1, &[("sticker", 10)], &mut |events| {
+            seen = usage(events);
+            false
+        })
+        .await
+        .unwrap());
+    assert_eq!(seen.len(), 3);
+
+    // the last event of the usage is removed
+    assert!(pool
+        .record_if(1, &[("sticker", 50)], &mut |_| true)
+        .await
+        .unwrap());
+    pool.refund(1, &[("sticker", 50)]).await.unwrap();
+
+    // event of two days ago is removed
+    assert_eq!(pool.remove_expired().await.unwrap(), 1);
+
+    let mut events = Vec::new();
+    pool.record_if(1, &[], &mut |current| {
+        events = current.to_vec();
+        true
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        usage(&events),
+        [("steal".to_owned(), 1), ("sticker".to_owned(), 50)]
+    );
+
+    assert!(events[0].created <= OffsetDateTime::now_utc());
+}
@@ -1,8 +1,21 @@
+use tracing::{info_span, Span};
+
 use crate::application::common::exceptions::{ApplicationException, RepoError, RepoKind};
 
 pub mod set;
 pub mod user;
 
+/// Span of the single query, `system` is name of the database in terms of OpenTelemetry
+/// (`postgresql` or `sqlite`)
+pub(crate) fn query_span(system: &'static str, statement: &str) -> Span {
+    info_span!(
+        "db_query",
+        otel.kind = "client",
+        db.system = system,
+        db.statement = statement,
+    )
+}
+
 impl From<sqlx::Error> for RepoError {
     fn from(error: sqlx::Error) -> Self {
         Self::new(error.to_string())
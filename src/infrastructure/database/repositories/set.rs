@@ -1,15 +1,15 @@
 use async_trait::async_trait;
-use sea_query::{Alias, Expr, PostgresQueryBuilder, Query};
+use sea_query::{Alias, Asterisk, Expr, Func, PostgresQueryBuilder, Query, SqliteQueryBuilder};
 use sea_query_binder::SqlxBinder;
-use sqlx::PgConnection;
-use tracing::debug;
+use sqlx::{PgConnection, SqliteConnection};
+use tracing::{debug, Instrument as _};
 
 use crate::{
     application::{
-        common::exceptions::RepoKind,
+        common::exceptions::{RepoError, RepoKind},
         set::{
             dto::{
-                create::Create, delete_by_short_name::DeleteByShortName,
+                count::Count, create::Create, delete_by_short_name::DeleteByShortName,
                 get_by_short_name::GetByShortName, get_by_tg_id::GetByTgID,
                 set_deleted_col_by_short_name::SetDeletedColByShortName,
             },
@@ -18,7 +18,7 @@ use crate::{
         },
     },
     domain::entities::set::Set,
-    infrastructure::database::models::set::Set as SetModel,
+    infrastructure::database::{models::set::Set as SetModel, repositories::query_span},
 };
 
 pub struct SetRepoImpl<Conn> {
@@ -55,6 +55,7 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
 
         sqlx::query_with(&sql_query, values)
             .execute(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
             .await
             .map(|_| ())
             .map_err(|err| {
@@ -86,6 +87,7 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
 
         sqlx::query_with(&sql_query, values)
             .execute(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
             .await
             .map(|_| ())
             .map_err(|err| {
@@ -111,6 +113,7 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
                     Alias::new("short_name"),
                     Alias::new("title"),
                     Alias::new("deleted"),
+                    Alias::new("created"),
                 ])
                 .from(Alias::new("sets"))
                 .and_where(Expr::col(Alias::new("tg_id")).eq(set.tg_id()))
@@ -126,6 +129,7 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
                     Alias::new("short_name"),
                     Alias::new("title"),
                     Alias::new("deleted"),
+                    Alias::new("created"),
                 ])
                 .from(Alias::new("sets"))
                 .and_where(Expr::col(Alias::new("tg_id")).eq(set.tg_id()))
@@ -136,6 +140,7 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
 
         sqlx::query_as_with(&sql_query, values)
             .fetch_all(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
             .await
             .map(|set_model: Vec<SetModel>| set_model.into_iter().map(Into::into).collect())
             .map_err(|err| {
@@ -156,6 +161,8 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
                 Alias::new("tg_id"),
                 Alias::new("short_name"),
                 Alias::new("title"),
+                Alias::new("deleted"),
+                Alias::new("created"),
             ])
             .from(Alias::new("sets"))
             .and_where(Expr::col(Alias::new("short_name")).eq(set.short_name()))
@@ -165,6 +172,7 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
 
         sqlx::query_as_with(&sql_query, values)
             .fetch_one(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
             .await
             .map(|set_model: SetModel| set_model.into())
             .map_err(|err| {
@@ -193,6 +201,7 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
 
         sqlx::query_with(&sql_query, values)
             .execute(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
             .await
             .map(|_| ())
             .map_err(|err| {
@@ -206,4 +215,286 @@ impl SetRepo for SetRepoImpl<&mut PgConnection> {
                 RepoKind::unexpected(err)
             })
     }
+
+    async fn count(&mut self, set: Count) -> Result<i64, RepoError> {
+        let mut query = Query::select();
+        query
+            .expr(Func::count(Expr::col(Asterisk)))
+            .from(Alias::new("sets"));
+
+        if let Some(deleted) = set.get_deleted() {
+            query.and_where(Expr::col(Alias::new("deleted")).eq(deleted));
+        }
+        if set.created_today() {
+            query.and_where(Expr::col(Alias::new("created")).gte(Expr::cust("CURRENT_DATE")));
+        }
+
+        let (sql_query, values) = query.build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        Ok(sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?)
+    }
+}
+
+#[async_trait]
+impl SetRepo for SetRepoImpl<&mut SqliteConnection> {
+    async fn create<'a>(
+        &'a mut self,
+        set: Create<'a>,
+    ) -> Result<(), RepoKind<SetShortNameAlreadyExist>> {
+        let (sql_query, values) = Query::insert()
+            .into_table(Alias::new("sets"))
+            .columns([
+                Alias::new("tg_id"),
+                Alias::new("short_name"),
+                Alias::new("title"),
+            ])
+            .values_panic([
+                set.tg_id().into(),
+                set.short_name().into(),
+                set.title().into(),
+            ])
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+            .map(|_| ())
+            .map_err(|err| {
+                if let Some(err) = err.as_database_error() {
+                    if err.is_unique_violation() {
+                        return RepoKind::exception(SetShortNameAlreadyExist::new(
+                            set.short_name().to_string(),
+                            err.to_string(),
+                        ));
+                    }
+                }
+
+                RepoKind::unexpected(err)
+            })
+    }
+
+    async fn delete_by_short_name<'a>(
+        &'a mut self,
+        set: DeleteByShortName<'a>,
+    ) -> Result<(), RepoKind<SetShortNameNotExist>> {
+        let (sql_query, values) = Query::delete()
+            .from_table(Alias::new("sets"))
+            .and_where(Expr::col(Alias::new("short_name")).eq(set.short_name()))
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+            .map(|_| ())
+            .map_err(|err| {
+                if let sqlx::Error::RowNotFound = err {
+                    return RepoKind::exception(SetShortNameNotExist::new(
+                        set.short_name().to_string(),
+                        err.to_string(),
+                    ));
+                }
+
+                RepoKind::unexpected(err)
+            })
+    }
+
+    async fn get_by_tg_id(
+        &mut self,
+        set: GetByTgID,
+    ) -> Result<Vec<Set>, RepoKind<SetTgIdNotExist>> {
+        let (sql_query, values) = if set.get_deleted().is_some() {
+            Query::select()
+                .columns([
+                    Alias::new("tg_id"),
+                    Alias::new("short_name"),
+                    Alias::new("title"),
+                    Alias::new("deleted"),
+                    Alias::new("created"),
+                ])
+                .from(Alias::new("sets"))
+                .and_where(Expr::col(Alias::new("tg_id")).eq(set.tg_id()))
+                .and_where(
+                    Expr::col(Alias::new("deleted"))
+                        .eq(set.get_deleted().expect("`get_deleted` is None")),
+                )
+                .build_sqlx(SqliteQueryBuilder)
+        } else {
+            Query::select()
+                .columns([
+                    Alias::new("tg_id"),
+                    Alias::new("short_name"),
+                    Alias::new("title"),
+                    Alias::new("deleted"),
+                    Alias::new("created"),
+                ])
+                .from(Alias::new("sets"))
+                .and_where(Expr::col(Alias::new("tg_id")).eq(set.tg_id()))
+                .build_sqlx(SqliteQueryBuilder)
+        };
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_as_with(&sql_query, values)
+            .fetch_all(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+            .map(|set_model: Vec<SetModel>| set_model.into_iter().map(Into::into).collect())
+            .map_err(|err| {
+                if let sqlx::Error::RowNotFound = err {
+                    return RepoKind::exception(SetTgIdNotExist::new(set.tg_id(), err.to_string()));
+                }
+
+                RepoKind::unexpected(err)
+            })
+    }
+
+    async fn get_one_by_short_name<'a>(
+        &'a mut self,
+        set: GetByShortName<'a>,
+    ) -> Result<Set, RepoKind<SetShortNameNotExist>> {
+        let (sql_query, values) = Query::select()
+            .columns([
+                Alias::new("tg_id"),
+                Alias::new("short_name"),
+                Alias::new("title"),
+                Alias::new("deleted"),
+                Alias::new("created"),
+            ])
+            .from(Alias::new("sets"))
+            .and_where(Expr::col(Alias::new("short_name")).eq(set.short_name()))
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_as_with(&sql_query, values)
+            .fetch_one(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+            .map(|set_model: SetModel| set_model.into())
+            .map_err(|err| {
+                if let sqlx::Error::RowNotFound = err {
+                    return RepoKind::exception(SetShortNameNotExist::new(
+                        set.short_name().to_string(),
+                        err.to_string(),
+                    ));
+                }
+
+                RepoKind::unexpected(err)
+            })
+    }
+
+    async fn set_deleted_col_by_short_name<'a>(
+        &'a mut self,
+        set: SetDeletedColByShortName<'a>,
+    ) -> Result<(), RepoKind<SetShortNameNotExist>> {
+        let (sql_query, values) = Query::update()
+            .table(Alias::new("sets"))
+            .value(Alias::new("deleted"), set.deleted())
+            .and_where(Expr::col(Alias::new("short_name")).eq(set.short_name()))
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+            .map(|_| ())
+            .map_err(|err| {
+                if let sqlx::Error::RowNotFound = err {
+                    return RepoKind::exception(SetShortNameNotExist::new(
+                        set.short_name().to_string(),
+                        err.to_string(),
+                    ));
+                }
+
+                RepoKind::unexpected(err)
+            })
+    }
+
+    async fn count(&mut self, set: Count) -> Result<i64, RepoError> {
+        let mut query = Query::select();
+        query
+            .expr(Func::count(Expr::col(Asterisk)))
+            .from(Alias::new("sets"));
+
+        if let Some(deleted) = set.get_deleted() {
+            query.and_where(Expr::col(Alias::new("deleted")).eq(deleted));
+        }
+        if set.created_today() {
+            query.and_where(Expr::col(Alias::new("created")).gte(Expr::cust("date('now')")));
+        }
+
+        let (sql_query, values) = query.build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        Ok(sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?)
+    }
+}
+
+#[tokio::test]
+async fn sqlite_set_repo_test() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::infrastructure::database::SQLITE_MIGRATOR;
+
+    // every connection to in-memory database creates new database, so only one connection is used
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    SQLITE_MIGRATOR.run(&pool).await.unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let mut repo = SetRepoImpl::new(&mut *conn);
+
+    repo.create(Create::new(1, "short_name", "title"))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        repo.create(Create::new(2, "short_name", "title")).await,
+        Err(RepoKind::Exception(_))
+    ));
+
+    repo.set_deleted_col_by_short_name(SetDeletedColByShortName::new("short_name", true))
+        .await
+        .unwrap();
+
+    let set = repo
+        .get_one_by_short_name(GetByShortName::new("short_name"))
+        .await
+        .unwrap();
+
+    assert_eq!(set.tg_id, 1);
+    assert!(set.deleted);
+    assert_eq!(
+        repo.get_by_tg_id(GetByTgID::new(1, Some(false)))
+            .await
+            .unwrap(),
+        Vec::new()
+    );
+
+    repo.create(Create::new(1, "other_short_name", "title"))
+        .await
+        .unwrap();
+
+    assert_eq!(repo.count(Count::new(None, true)).await.unwrap(), 2);
+    assert_eq!(repo.count(Count::new(Some(true), false)).await.unwrap(), 1);
 }
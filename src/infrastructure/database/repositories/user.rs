@@ -1,20 +1,23 @@
 use async_trait::async_trait;
-use sea_query::{Alias, Expr, PostgresQueryBuilder, Query};
+use sea_query::{Alias, Asterisk, Expr, Func, PostgresQueryBuilder, Query, SqliteQueryBuilder};
 use sea_query_binder::SqlxBinder as _;
-use sqlx::PgConnection;
-use tracing::debug;
+use sqlx::{PgConnection, SqliteConnection};
+use tracing::{debug, Instrument as _};
 
 use crate::{
     application::{
-        common::exceptions::RepoKind,
+        common::exceptions::{RepoError, RepoKind},
         user::{
-            dto::{create::Create, get_by_tg_id::GetByTgID},
+            dto::{
+                create::Create, get_by_tg_id::GetByTgID, set_active_col::SetActiveCol,
+                set_banned_col::SetBannedCol,
+            },
             exceptions::{UserTgIdAlreadyExists, UserTgIdNotExist},
             traits::UserRepo,
         },
     },
     domain::entities::user::User,
-    infrastructure::database::models::user::User as UserModel,
+    infrastructure::database::{models::user::User as UserModel, repositories::query_span},
 };
 
 pub struct UserRepoImpl<Conn> {
@@ -40,6 +43,7 @@ impl UserRepo for UserRepoImpl<&mut PgConnection> {
 
         sqlx::query_with(&sql_query, values)
             .execute(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
             .await
             .map(|_| ())
             .map_err(|err| {
@@ -61,7 +65,12 @@ impl UserRepo for UserRepoImpl<&mut PgConnection> {
 
     async fn get_by_tg_id(&mut self, user: GetByTgID) -> Result<User, RepoKind<UserTgIdNotExist>> {
         let (sql_query, values) = Query::select()
-            .columns([Alias::new("tg_id"), Alias::new("created")])
+            .columns([
+                Alias::new("tg_id"),
+                Alias::new("created"),
+                Alias::new("banned"),
+                Alias::new("active"),
+            ])
             .from(Alias::new("users"))
             .and_where(Expr::col(Alias::new("tg_id")).eq(user.tg_id()))
             .build_sqlx(PostgresQueryBuilder);
@@ -70,6 +79,7 @@ impl UserRepo for UserRepoImpl<&mut PgConnection> {
 
         sqlx::query_as_with(&sql_query, values)
             .fetch_one(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
             .await
             .map(|user_model: UserModel| user_model.into())
             .map_err(|err| {
@@ -83,4 +93,250 @@ impl UserRepo for UserRepoImpl<&mut PgConnection> {
                 RepoKind::unexpected(err)
             })
     }
+
+    async fn set_banned_col(
+        &mut self,
+        user: SetBannedCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>> {
+        let (sql_query, values) = Query::update()
+            .table(Alias::new("users"))
+            .value(Alias::new("banned"), user.banned())
+            .and_where(Expr::col(Alias::new("tg_id")).eq(user.tg_id()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepoKind::exception(UserTgIdNotExist::new(
+                user.tg_id(),
+                "no rows updated",
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn set_active_col(
+        &mut self,
+        user: SetActiveCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>> {
+        let (sql_query, values) = Query::update()
+            .table(Alias::new("users"))
+            .value(Alias::new("active"), user.active())
+            .and_where(Expr::col(Alias::new("tg_id")).eq(user.tg_id()))
+            .build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepoKind::exception(UserTgIdNotExist::new(
+                user.tg_id(),
+                "no rows updated",
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn count(&mut self) -> Result<i64, RepoError> {
+        let (sql_query, values) = Query::select()
+            .expr(Func::count(Expr::col(Asterisk)))
+            .from(Alias::new("users"))
+            .build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        Ok(sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(&mut *self.conn)
+            .instrument(query_span("postgresql", &sql_query))
+            .await?)
+    }
+}
+
+#[async_trait]
+impl UserRepo for UserRepoImpl<&mut SqliteConnection> {
+    async fn create(&mut self, user: Create) -> Result<(), RepoKind<UserTgIdAlreadyExists>> {
+        let (sql_query, values) = Query::insert()
+            .into_table(Alias::new("users"))
+            .columns([Alias::new("tg_id")])
+            .values_panic([user.tg_id().into()])
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+            .map(|_| ())
+            .map_err(|err| {
+                if let Some(err) = err.as_database_error() {
+                    // if unique `tg_id` already exists
+                    if err.is_unique_violation() {
+                        return RepoKind::exception(UserTgIdAlreadyExists::new(
+                            user.tg_id(),
+                            err.to_string(),
+                        ));
+                    }
+                }
+
+                RepoKind::unexpected(err)
+            })
+    }
+
+    async fn get_by_tg_id(&mut self, user: GetByTgID) -> Result<User, RepoKind<UserTgIdNotExist>> {
+        let (sql_query, values) = Query::select()
+            .columns([
+                Alias::new("tg_id"),
+                Alias::new("created"),
+                Alias::new("banned"),
+                Alias::new("active"),
+            ])
+            .from(Alias::new("users"))
+            .and_where(Expr::col(Alias::new("tg_id")).eq(user.tg_id()))
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_as_with(&sql_query, values)
+            .fetch_one(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await
+            .map(|user_model: UserModel| user_model.into())
+            .map_err(|err| {
+                if let sqlx::Error::RowNotFound = err {
+                    return RepoKind::exception(UserTgIdNotExist::new(
+                        user.tg_id(),
+                        err.to_string(),
+                    ));
+                }
+
+                RepoKind::unexpected(err)
+            })
+    }
+
+    async fn set_banned_col(
+        &mut self,
+        user: SetBannedCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>> {
+        let (sql_query, values) = Query::update()
+            .table(Alias::new("users"))
+            .value(Alias::new("banned"), user.banned())
+            .and_where(Expr::col(Alias::new("tg_id")).eq(user.tg_id()))
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepoKind::exception(UserTgIdNotExist::new(
+                user.tg_id(),
+                "no rows updated",
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn set_active_col(
+        &mut self,
+        user: SetActiveCol,
+    ) -> Result<(), RepoKind<UserTgIdNotExist>> {
+        let (sql_query, values) = Query::update()
+            .table(Alias::new("users"))
+            .value(Alias::new("active"), user.active())
+            .and_where(Expr::col(Alias::new("tg_id")).eq(user.tg_id()))
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let result = sqlx::query_with(&sql_query, values)
+            .execute(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepoKind::exception(UserTgIdNotExist::new(
+                user.tg_id(),
+                "no rows updated",
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn count(&mut self) -> Result<i64, RepoError> {
+        let (sql_query, values) = Query::select()
+            .expr(Func::count(Expr::col(Asterisk)))
+            .from(Alias::new("users"))
+            .build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        Ok(sqlx::query_scalar_with(&sql_query, values)
+            .fetch_one(&mut *self.conn)
+            .instrument(query_span("sqlite", &sql_query))
+            .await?)
+    }
+}
+
+#[tokio::test]
+async fn sqlite_user_repo_test() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use crate::infrastructure::database::SQLITE_MIGRATOR;
+
+    // every connection to in-memory database creates new database, so only one connection is used
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    SQLITE_MIGRATOR.run(&pool).await.unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let mut repo = UserRepoImpl::new(&mut *conn);
+
+    repo.create(Create::new(1)).await.unwrap();
+
+    assert!(matches!(
+        repo.create(Create::new(1)).await,
+        Err(RepoKind::Exception(_))
+    ));
+    assert_eq!(repo.get_by_tg_id(GetByTgID::new(1)).await.unwrap().tg_id, 1);
+    assert!(matches!(
+        repo.get_by_tg_id(GetByTgID::new(2)).await,
+        Err(RepoKind::Exception(_))
+    ));
+
+    repo.set_banned_col(SetBannedCol::new(1, true))
+        .await
+        .unwrap();
+
+    assert!(repo.get_by_tg_id(GetByTgID::new(1)).await.unwrap().banned);
+    assert!(matches!(
+        repo.set_banned_col(SetBannedCol::new(2, true)).await,
+        Err(RepoKind::Exception(_))
+    ));
+
+    repo.set_active_col(SetActiveCol::new(1, false))
+        .await
+        .unwrap();
+
+    assert!(!repo.get_by_tg_id(GetByTgID::new(1)).await.unwrap().active);
+    assert_eq!(repo.count().await.unwrap(), 1);
 }
@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS users (
+    tg_id INTEGER NOT NULL,
+    created TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(tg_id)
+);
+
+CREATE TABLE IF NOT EXISTS sets (
+    tg_id INTEGER NOT NULL,
+    short_name TEXT NOT NULL,
+    title TEXT NOT NULL,
+    deleted BOOLEAN NOT NULL DEFAULT FALSE,
+    created TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(short_name)
+);
+
+CREATE TABLE IF NOT EXISTS fsm_states (
+    bot_id INTEGER NOT NULL,
+    chat_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    destiny TEXT NOT NULL,
+    states TEXT NOT NULL DEFAULT '[]',
+    data TEXT NOT NULL DEFAULT '{}',
+    PRIMARY KEY (bot_id, chat_id, user_id, destiny)
+);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS quota_events (
+    tg_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    created TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS quota_events_tg_id_created ON quota_events (tg_id, created);
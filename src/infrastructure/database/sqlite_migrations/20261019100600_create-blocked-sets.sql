@@ -0,0 +1,7 @@
+-- every row blocks either one sticker set by its short name (in lowercase) or every sticker set of the owner
+CREATE TABLE IF NOT EXISTS blocked_sets (
+    short_name TEXT UNIQUE,
+    owner_id INTEGER UNIQUE,
+    created TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((short_name IS NULL) <> (owner_id IS NULL))
+);
@@ -0,0 +1,2 @@
+-- creator of the sticker sets, who protected them with `/protectmypacks` (NULL, if blocked by admin)
+ALTER TABLE blocked_sets ADD COLUMN protected_by INTEGER;
@@ -0,0 +1,3 @@
+pub mod database;
+#[cfg(feature = "redis-storage")]
+pub mod redis;
@@ -0,0 +1,281 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use async_trait::async_trait;
+use sea_query::{
+    Alias, Expr, InsertStatement, OnConflict, PostgresQueryBuilder, Query, SelectStatement,
+    SqliteQueryBuilder,
+};
+use sea_query_binder::SqlxBinder as _;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sqlx::{types::Json, FromRow, PgPool, SqlitePool};
+use telers::fsm::{Storage, StorageKey};
+use tracing::debug;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseStorageError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// States and data of the user in the specified chat
+#[derive(Debug, Default, FromRow)]
+pub struct FsmState {
+    states: Json<Vec<String>>,
+    data: Json<HashMap<String, Value>>,
+}
+
+/// Pool of the database, which contains `fsm_states` table
+#[async_trait]
+pub trait FsmStatesPool: Clone + Send + Sync + 'static {
+    /// Return states and data for the key (empty, if row doesn't exist)
+    async fn get(&self, key: &StorageKey) -> Result<FsmState, DatabaseStorageError>;
+
+    /// Insert row for the key or update specified column, if row already exists
+    async fn upsert(
+        &self,
+        key: &StorageKey,
+        column: &'static str,
+        value: Value,
+    ) -> Result<(), DatabaseStorageError>;
+}
+
+fn select_query(key: &StorageKey) -> SelectStatement {
+    Query::select()
+        .columns([Alias::new("states"), Alias::new("data")])
+        .from(Alias::new("fsm_states"))
+        .and_where(Expr::col(Alias::new("bot_id")).eq(key.bot_id))
+        .and_where(Expr::col(Alias::new("chat_id")).eq(key.chat_id))
+        .and_where(Expr::col(Alias::new("user_id")).eq(key.user_id))
+        .and_where(Expr::col(Alias::new("destiny")).eq(key.destiny.to_string()))
+        .to_owned()
+}
+
+fn upsert_query(key: &StorageKey, column: &'static str, value: Value) -> InsertStatement {
+    let key_columns = [
+        Alias::new("bot_id"),
+        Alias::new("chat_id"),
+        Alias::new("user_id"),
+        Alias::new("destiny"),
+    ];
+
+    Query::insert()
+        .into_table(Alias::new("fsm_states"))
+        .columns(key_columns.clone().into_iter().chain([Alias::new(column)]))
+        .values_panic([
+            key.bot_id.into(),
+            key.chat_id.into(),
+            key.user_id.into(),
+            key.destiny.to_string().into(),
+            value.into(),
+        ])
+        .on_conflict(
+            OnConflict::columns(key_columns)
+                .update_column(Alias::new(column))
+                .to_owned(),
+        )
+        .to_owned()
+}
+
+#[async_trait]
+impl FsmStatesPool for PgPool {
+    async fn get(&self, key: &StorageKey) -> Result<FsmState, DatabaseStorageError> {
+        let (sql_query, values) = select_query(key).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        let state: Option<FsmState> = sqlx::query_as_with(&sql_query, values)
+            .fetch_optional(self)
+            .await?;
+
+        Ok(state.unwrap_or_default())
+    }
+
+    async fn upsert(
+        &self,
+        key: &StorageKey,
+        column: &'static str,
+        value: Value,
+    ) -> Result<(), DatabaseStorageError> {
+        let (sql_query, values) = upsert_query(key, column, value).build_sqlx(PostgresQueryBuilder);
+
+        debug!("Postgres query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values).execute(self).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FsmStatesPool for SqlitePool {
+    async fn get(&self, key: &StorageKey) -> Result<FsmState, DatabaseStorageError> {
+        let (sql_query, values) = select_query(key).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        let state: Option<FsmState> = sqlx::query_as_with(&sql_query, values)
+            .fetch_optional(self)
+            .await?;
+
+        Ok(state.unwrap_or_default())
+    }
+
+    async fn upsert(
+        &self,
+        key: &StorageKey,
+        column: &'static str,
+        value: Value,
+    ) -> Result<(), DatabaseStorageError> {
+        let (sql_query, values) = upsert_query(key, column, value).build_sqlx(SqliteQueryBuilder);
+
+        debug!("SQLite query: {sql_query};\nValues for query: {values:?}");
+
+        sqlx::query_with(&sql_query, values).execute(self).await?;
+
+        Ok(())
+    }
+}
+
+/// FSM storage, which keeps states and data in the `fsm_states` table of the bot database,
+/// so they are not lost after restart of the bot
+#[derive(Debug, Clone)]
+pub struct DatabaseStorage<Pool> {
+    pool: Pool,
+}
+
+impl<Pool: FsmStatesPool> DatabaseStorage<Pool> {
+    pub const fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    async fn set_states(
+        &self,
+        key: &StorageKey,
+        states: Vec<String>,
+    ) -> Result<(), DatabaseStorageError> {
+        self.pool
+            .upsert(key, "states", serde_json::to_value(states)?)
+            .await
+    }
+
+    async fn set_data_map(
+        &self,
+        key: &StorageKey,
+        data: HashMap<String, Value>,
+    ) -> Result<(), DatabaseStorageError> {
+        self.pool
+            .upsert(key, "data", serde_json::to_value(data)?)
+            .await
+    }
+}
+
+#[async_trait]
+impl<Pool: FsmStatesPool> Storage for DatabaseStorage<Pool> {
+    type Error = DatabaseStorageError;
+
+    async fn set_state<State>(&self, key: &StorageKey, state: State) -> Result<(), Self::Error>
+    where
+        State: Into<Cow<'static, str>> + Send,
+    {
+        let Json(mut states) = self.pool.get(key).await?.states;
+
+        states.push(state.into().into_owned());
+
+        self.set_states(key, states).await
+    }
+
+    async fn set_previous_state(&self, key: &StorageKey) -> Result<(), Self::Error> {
+        let Json(mut states) = self.pool.get(key).await?.states;
+
+        if states.pop().is_none() {
+            return Ok(());
+        }
+
+        self.set_states(key, states).await
+    }
+
+    async fn get_state(&self, key: &StorageKey) -> Result<Option<Cow<'static, str>>, Self::Error> {
+        let Json(mut states) = self.pool.get(key).await?.states;
+
+        Ok(states.pop().map(Into::into))
+    }
+
+    async fn get_states(&self, key: &StorageKey) -> Result<Box<[Cow<'static, str>]>, Self::Error> {
+        let Json(states) = self.pool.get(key).await?.states;
+
+        Ok(states.into_iter().map(Into::into).collect())
+    }
+
+    async fn remove_states(&self, key: &StorageKey) -> Result<(), Self::Error> {
+        self.set_states(key, Vec::new()).await
+    }
+
+    async fn set_data<Key, Val>(
+        &self,
+        key: &StorageKey,
+        data: HashMap<Key, Val>,
+    ) -> Result<(), Self::Error>
+    where
+        Val: Serialize + Send,
+        Key: Serialize + Into<Cow<'static, str>> + Send,
+    {
+        let data = data
+            .into_iter()
+            .map(|(key, value)| Ok((key.into().into_owned(), serde_json::to_value(value)?)))
+            .collect::<Result<_, serde_json::Error>>()?;
+
+        self.set_data_map(key, data).await
+    }
+
+    async fn set_value<Key, Val>(
+        &self,
+        key: &StorageKey,
+        value_key: Key,
+        value: Val,
+    ) -> Result<(), Self::Error>
+    where
+        Val: Serialize + Send,
+        Key: Into<Cow<'static, str>> + Send,
+    {
+        let Json(mut data) = self.pool.get(key).await?.data;
+
+        data.insert(value_key.into().into_owned(), serde_json::to_value(value)?);
+
+        self.set_data_map(key, data).await
+    }
+
+    async fn get_data<Val>(&self, key: &StorageKey) -> Result<HashMap<Box<str>, Val>, Self::Error>
+    where
+        Val: DeserializeOwned,
+    {
+        let Json(data) = self.pool.get(key).await?.data;
+
+        data.into_iter()
+            .map(|(key, value)| Ok((key.into_boxed_str(), serde_json::from_value(value)?)))
+            .collect()
+    }
+
+    async fn get_value<Key, Val>(
+        &self,
+        key: &StorageKey,
+        value_key: Key,
+    ) -> Result<Option<Val>, Self::Error>
+    where
+        Val: DeserializeOwned,
+        Key: Into<Cow<'static, str>> + Send,
+    {
+        let Json(mut data) = self.pool.get(key).await?.data;
+
+        data.remove(value_key.into().as_ref())
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn remove_data(&self, key: &StorageKey) -> Result<(), Self::Error> {
+        self.set_data_map(key, HashMap::new()).await
+    }
+}
@@ -0,0 +1,311 @@
+use std::{borrow::Cow, collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use redis::{aio::ConnectionManager, AsyncCommands as _, Pipeline, RedisError};
+use serde::{de::DeserializeOwned, Serialize};
+use telers::fsm::{Storage, StorageKey};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RedisStorageError {
+    #[error(transparent)]
+    Redis(#[from] RedisError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// FSM storage, which keeps states and data in Redis, so they can be shared between several replicas
+/// of the bot. States are kept in the list `{prefix}:{bot_id}:{chat_id}:{user_id}:{destiny}:states`
+/// and data in the hash `{prefix}:{bot_id}:{chat_id}:{user_id}:{destiny}:data`.
+#[derive(Clone)]
+pub struct RedisStorage {
+    conn: ConnectionManager,
+    prefix: String,
+    ttl: Option<Duration>,
+}
+
+impl RedisStorage {
+    pub async fn connect(url: &str) -> Result<Self, RedisError> {
+        let conn = redis::Client::open(url)?.get_connection_manager().await?;
+
+        Ok(Self {
+            conn,
+            prefix: "fsm".to_owned(),
+            ttl: None,
+        })
+    }
+
+    pub fn prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..self
+        }
+    }
+
+    /// Keys of user are removed, if they are not changed during `ttl`
+    pub fn ttl(self, ttl: Option<Duration>) -> Self {
+        Self { ttl, ..self }
+    }
+
+    fn key(&self, key: &StorageKey, part: &str) -> String {
+        format!(
+            "{prefix}:{bot_id}:{chat_id}:{user_id}:{destiny}:{part}",
+            prefix = self.prefix,
+            bot_id = key.bot_id,
+            chat_id = key.chat_id,
+            user_id = key.user_id,
+            destiny = key.destiny,
+        )
+    }
+
+    /// Add `EXPIRE` command for the key into pipeline, if TTL is specified
+    fn expire(&self, pipe: &mut Pipeline, key: &str) {
+        if let Some(ttl) = self.ttl {
+            pipe.expire(key, ttl.as_secs() as i64).ignore();
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for RedisStorage {
+    type Error = RedisStorageError;
+
+    async fn set_state<State>(&self, key: &StorageKey, state: State) -> Result<(), Self::Error>
+    where
+        State: Into<Cow<'static, str>> + Send,
+    {
+        let states_key = self.key(key, "states");
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .rpush(&states_key, state.into().as_ref())
+            .ignore();
+        self.expire(&mut pipe, &states_key);
+
+        pipe.query_async::<()>(&mut self.conn.clone()).await?;
+
+        Ok(())
+    }
+
+    async fn set_previous_state(&self, key: &StorageKey) -> Result<(), Self::Error> {
+        self.conn
+            .clone()
+            .rpop::<_, Option<String>>(self.key(key, "states"), None)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_state(&self, key: &StorageKey) -> Result<Option<Cow<'static, str>>, Self::Error> {
+        let state: Option<String> = self
+            .conn
+            .clone()
+            .lindex(self.key(key, "states"), -1)
+            .await?;
+
+        Ok(state.map(Into::into))
+    }
+
+    async fn get_states(&self, key: &StorageKey) -> Result<Box<[Cow<'static, str>]>, Self::Error> {
+        let states: Vec<String> = self
+            .conn
+            .clone()
+            .lrange(self.key(key, "states"), 0, -1)
+            .await?;
+
+        Ok(states.into_iter().map(Into::into).collect())
+    }
+
+    async fn remove_states(&self, key: &StorageKey) -> Result<(), Self::Error> {
+        self.conn
+            .clone()
+            .del::<_, ()>(self.key(key, "states"))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_data<Key, Val>(
+        &self,
+        key: &StorageKey,
+        data: HashMap<Key, Val>,
+    ) -> Result<(), Self::Error>
+    where
+        Val: Serialize + Send,
+        Key: Serialize + Into<Cow<'static, str>> + Send,
+    {
+        let data_key = self.key(key, "data");
+
+        let data = data
+            .into_iter()
+            .map(|(key, value)| Ok((key.into().into_owned(), serde_json::to_string(&value)?)))
+            .collect::<Result<Vec<_>, serde_json::Error>>()?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(&data_key).ignore();
+        if !data.is_empty() {
+            pipe.hset_multiple(&data_key, &data).ignore();
+            self.expire(&mut pipe, &data_key);
+        }
+
+        pipe.query_async::<()>(&mut self.conn.clone()).await?;
+
+        Ok(())
+    }
+
+    async fn set_value<Key, Val>(
+        &self,
+        key: &StorageKey,
+        value_key: Key,
+        value: Val,
+    ) -> Result<(), Self::Error>
+    where
+        Val: Serialize + Send,
+        Key: Into<Cow<'static, str>> + Send,
+    {
+        let data_key = self.key(key, "data");
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .hset(
+                &data_key,
+                value_key.into().as_ref(),
+                serde_json::to_string(&value)?,
+            )
+            .ignore();
+        self.expire(&mut pipe, &data_key);
+
+        pipe.query_async::<()>(&mut self.conn.clone()).await?;
+
+        Ok(())
+    }
+
+    async fn get_data<Val>(&self, key: &StorageKey) -> Result<HashMap<Box<str>, Val>, Self::Error>
+    where
+        Val: DeserializeOwned,
+    {
+        let data: HashMap<String, String> =
+            self.conn.clone().hgetall(self.key(key, "data")).await?;
+
+        data.into_iter()
+            .map(|(key, value)| Ok((key.into_boxed_str(), serde_json::from_str(&value)?)))
+            .collect()
+    }
+
+    async fn get_value<Key, Val>(
+        &self,
+        key: &StorageKey,
+        value_key: Key,
+    ) -> Result<Option<Val>, Self::Error>
+    where
+        Val: DeserializeOwned,
+        Key: Into<Cow<'static, str>> + Send,
+    {
+        let value: Option<String> = self
+            .conn
+            .clone()
+            .hget(self.key(key, "data"), value_key.into().as_ref())
+            .await?;
+
+        value
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn remove_data(&self, key: &StorageKey) -> Result<(), Self::Error> {
+        self.conn
+            .clone()
+            .del::<_, ()>(self.key(key, "data"))
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Connect to the local redis-server (or to the server from `REDIS_URL` env variable).
+/// Every test uses its own prefix, so tests don't affect each other.
+/// Run tests using command `cargo test --features redis-storage -- --ignored`.
+#[cfg(test)]
+async fn test_storage(prefix: &str) -> RedisStorage {
+    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_owned());
+
+    RedisStorage::connect(&url)
+        .await
+        .expect("redis-server should be running")
+        .prefix(format!("steal_stickers_bot_test:{prefix}"))
+}
+
+#[cfg(test)]
+fn test_key() -> StorageKey {
+    StorageKey::new(1, 2, 3)
+}
+
+#[tokio::test]
+#[ignore = "requires running redis-server"]
+async fn redis_storage_states_test() {
+    let storage = test_storage("states").await;
+    let key = test_key();
+
+    storage.remove_states(&key).await.unwrap();
+
+    storage.set_state(&key, "first").await.unwrap();
+    storage.set_state(&key, "second").await.unwrap();
+
+    assert_eq!(
+        storage.get_state(&key).await.unwrap().as_deref(),
+        Some("second")
+    );
+    assert_eq!(storage.get_states(&key).await.unwrap().len(), 2);
+
+    storage.set_previous_state(&key).await.unwrap();
+
+    assert_eq!(
+        storage.get_state(&key).await.unwrap().as_deref(),
+        Some("first")
+    );
+
+    storage.remove_states(&key).await.unwrap();
+
+    assert_eq!(storage.get_state(&key).await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "requires running redis-server"]
+async fn redis_storage_data_test() {
+    let storage = test_storage("data").await;
+    let key = test_key();
+
+    storage
+        .set_data(&key, HashMap::from([("first", 1), ("second", 2)]))
+        .await
+        .unwrap();
+    storage.set_value(&key, "third", 3).await.unwrap();
+
+    assert_eq!(storage.get_data::<i32>(&key).await.unwrap().len(), 3);
+    assert_eq!(storage.get_value(&key, "third").await.unwrap(), Some(3));
+
+    storage.remove_data(&key).await.unwrap();
+
+    assert_eq!(
+        storage.get_value::<_, i32>(&key, "first").await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires running redis-server"]
+async fn redis_storage_ttl_test() {
+    let storage = test_storage("ttl").await.ttl(Some(Duration::from_secs(1)));
+    let key = test_key();
+
+    storage.set_state(&key, "state").await.unwrap();
+    storage.set_value(&key, "value", 1).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+
+    assert_eq!(storage.get_state(&key).await.unwrap(), None);
+    assert_eq!(
+        storage.get_value::<_, i32>(&key, "value").await.unwrap(),
+        None
+    );
+}
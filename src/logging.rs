@@ -0,0 +1,138 @@
+use opentelemetry_sdk::trace::TracerProvider;
+use telers::types::Update;
+use tracing::{field::Empty, info_span, Span};
+use tracing_subscriber::{fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter};
+
+use crate::{
+    config::{LogFormat, Tracing},
+    metrics::METRICS,
+    telemetry::otel_layer,
+};
+
+/// Initialize global subscriber, which writes logs in the format specified in config
+/// and passes spans into `tracer_provider`, if it's specified.
+/// Log level from `LOG_LEVEL` environment variable takes precedence over config.
+pub fn init_tracing(config: &Tracing, tracer_provider: Option<&TracerProvider>) {
+    let log_level = match std::env::var("LOG_LEVEL") {
+        Ok(log_level) => log_level,
+        Err(_) => config.log_level.clone(),
+    };
+
+    let (plain_layer, json_layer) = match config.format {
+        LogFormat::Plain => (Some(fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            Some(
+                fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true),
+            ),
+        ),
+    };
+
+    tracing_subscriber::registry()
+        .with(plain_layer)
+        .with(json_layer)
+        .with(tracer_provider.map(otel_layer))
+        .with(
+            EnvFilter::new(log_level)
+                .add_directive("hyper=warn".parse().expect("Invalid directive"))
+                .add_directive("reqwest=warn".parse().expect("Invalid directive"))
+                .add_directive("grammers=warn".parse().expect("Invalid directive"))
+                .add_directive("sqlx=warn".parse().expect("Invalid directive"))
+                // requests of OTLP exporter itself
+                .add_directive("h2=warn".parse().expect("Invalid directive"))
+                .add_directive("tonic=warn".parse().expect("Invalid directive"))
+                .add_directive(
+                    "telers::client::session::base=off"
+                        .parse()
+                        .expect("Invalid directive"),
+                ),
+        )
+        .init();
+}
+
+/// Create span, in which the update is processed.
+/// `handler` and `fsm_state` fields are recorded later, when they are known.
+pub fn update_span(update: &Update) -> Span {
+    info_span!(
+        "update",
+        update_id = update.update_id,
+        user_id = update.from_id(),
+        handler = Empty,
+        fsm_state = Empty,
+    )
+}
+
+/// Record name of the handler, which processes the current update, into the update span and metrics
+pub fn handler_called(handler: &'static str) {
+    Span::current().record("handler", handler);
+
+    METRICS.update_handled(handler);
+}
+
+#[test]
+fn update_span_test() {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use serde_json::{json, Value};
+    use tracing::info;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+
+    let subscriber = fmt()
+        .json()
+        .with_current_span(true)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    let update: Update = serde_json::from_value(json!({ "update_id": 7, "message": {
+        "message_id": 1,
+        "date": 0,
+        "chat": { "id": 1, "type": "private", "first_name": "Test" },
+        "from": { "id": 1, "is_bot": false, "first_name": "Test" },
+        "text": "/start",
+    }}))
+    .unwrap();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let _span = update_span(&update).entered();
+
+        handler_called("start_handler");
+        Span::current().record("fsm_state", "AddStickerState::GetStickers");
+
+        info!("Update processed");
+    });
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let log: Value = serde_json::from_str(output.trim()).unwrap();
+
+    assert_eq!(
+        log["span"],
+        json!({
+            "name": "update",
+            "update_id": 7,
+            "user_id": 1,
+            "handler": "start_handler",
+            "fsm_state": "AddStickerState::GetStickers",
+        })
+    );
+}
@@ -1,45 +1,90 @@
-use std::process;
+use std::{fmt::Debug, path::PathBuf, process, sync::Arc, time::Duration};
 
 use application::common::traits::uow::UoWFactory as _;
-use infrastructure::database::uow::UoWFactory;
-use sqlx::Postgres;
+use application::{set::traits::SetRepo, user::traits::UserRepo};
+use grammers_client::Client;
+#[cfg(feature = "redis-storage")]
+use infrastructure::fsm::redis::RedisStorage;
+use infrastructure::{
+    database::{
+        blocked_sets::BlockedSetsPool,
+        broadcasts::BroadcastsPool,
+        pending_migrations,
+        quota_events::QuotaEventsPool,
+        repositories::{set::SetRepoImpl, user::UserRepoImpl},
+        uow::UoWFactory,
+        MIGRATOR, SQLITE_MIGRATOR,
+    },
+    fsm::database::{DatabaseStorage, FsmStatesPool},
+};
+use sqlx::{
+    database::HasArguments,
+    migrate::{Migrate, Migrator},
+    sqlite::SqliteConnectOptions,
+    Database, Executor, IntoArguments, PgPool, Pool, Postgres, Sqlite, SqlitePool,
+};
 use telers::{
     client::Reqwest,
     enums::ContentType as ContentTypeEnum,
     errors::HandlerError,
     event::ToServiceProvider as _,
-    fsm::{MemoryStorage, Strategy},
+    fsm::{MemoryStorage, Storage, Strategy},
     methods::SetMyCommands,
-    middlewares::outer::FSMContext,
-    types::{BotCommand, BotCommandScopeAllPrivateChats},
+    middlewares::{outer::FSMContext, OuterMiddleware},
+    types::{BotCommand, BotCommandScopeAllPrivateChats, BotCommandScopeChat, Update},
     Bot, Dispatcher, Router,
 };
 
 use clap::{Parser, Subcommand};
-use tracing::{debug, error};
-use tracing_subscriber::{fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter};
+use tracing::{debug, error, Instrument as _};
 
 pub mod application;
+pub mod bot_api;
 pub mod bot_commands;
+pub mod broadcast;
 pub mod config;
 pub mod core;
 pub mod domain;
+pub mod filters;
+mod health;
 pub mod infrastructure;
+pub mod logging;
+pub mod metrics;
 pub mod middlewares;
+mod polling;
+mod shutdown;
 mod telegram_application;
+pub mod telemetry;
+mod webhook;
 
+#[cfg(test)]
+mod fake_bot_api;
+
+use bot_api::init_rate_limiter;
 use bot_commands::{
-    add_stickers_command, cancel_command, my_stickers, process_non_command, process_non_sticker,
-    source_command, start_command, steal_sticker_set_command,
+    add_stickers_command, admin_commands, cancel_command, download_command, export_command,
+    from_archive_command, import_command, my_stickers, process_non_command, process_non_document,
+    process_non_sticker, protect_command, source_command, start_command, steal_sticker_set_command,
 };
-use config::ConfigToml;
+use broadcast::Broadcasts;
+use config::{load_config, ConfigToml, DatabaseBackend, FsmStorageKind};
 use core::{common, texts};
+use filters::Admin;
+use health::{serve_health, ClientCheck, DatabaseCheck, PollingCheck, ReadinessCheck};
+use logging::{init_tracing, update_span};
+use metrics::serve_metrics;
 use middlewares::{
+    BannedUserMiddleware, Blocklist, BlocklistMiddleware, BroadcastsMiddleware,
     ClientApplicationMiddleware, CreateUserMiddleware, DatabaseMiddleware, DeletedSetsMiddleware,
+    FsmStateMiddleware, QuotaMiddleware, Quotas,
 };
-use telegram_application::{client_authorize, client_connect};
+use polling::{run_polling, PollingStatus};
+use shutdown::{finish_operations, OPERATIONS};
+use telegram_application::{client_authorize, client_connect, client_save_session};
+use telemetry::otlp_tracer_provider;
+use webhook::run_webhook;
 
-async fn set_commands(bot: Bot) -> Result<(), HandlerError> {
+async fn set_commands(bot: Bot, admin_ids: Vec<i64>) -> Result<(), HandlerError> {
     let help = BotCommand::new("help", "Show help message");
     let source = BotCommand::new("source", "Show the source of the bot");
     let src = BotCommand::new("src", "Show the source of the bot");
@@ -50,18 +95,233 @@ async fn set_commands(bot: Bot) -> Result<(), HandlerError> {
     );
     let my_stickers = BotCommand::new("mystickers", "List of your stolen stickers");
     let cancel = BotCommand::new("cancel", "Cancel last command");
+    let export = BotCommand::new("export", "Export list of your stolen stickers");
+    let import = BotCommand::new("import", "Import list of your stolen stickers");
+    let download = BotCommand::new("download", "Download sticker pack as zip archive");
+    let from_archive = BotCommand::new("fromarchive", "Create sticker pack from zip archive");
+    let protect = BotCommand::new("protectmypacks", "Protect your sticker packs from stealing");
+    let unprotect = BotCommand::new("unprotect", "Remove protection of your sticker packs");
+
+    let private_chats = [
+        help,
+        source,
+        src,
+        steal,
+        steal_sticker,
+        cancel,
+        my_stickers,
+        export,
+        import,
+        download,
+        from_archive,
+        protect,
+        unprotect,
+    ];
+
+    bot.send(SetMyCommands::new(private_chats.clone()).scope(BotCommandScopeAllPrivateChats {}))
+        .await?;
 
-    let private_chats = [help, source, src, steal, steal_sticker, cancel, my_stickers];
+    let stats = BotCommand::new("stats", "Show statistics of the bot");
+    let user = BotCommand::new("user", "Show sticker packs of the user by ID");
+    let ban = BotCommand::new("ban", "Ban the user by ID");
+    let unban = BotCommand::new("unban", "Unban the user by ID");
+    let block = BotCommand::new(
+        "block",
+        "Block sticker pack by name, link or ID of its owner",
+    );
+    let unblock = BotCommand::new(
+        "unblock",
+        "Unblock sticker pack by name, link or ID of its owner",
+    );
+    let broadcast = BotCommand::new(
+        "broadcast",
+        "Send the replied message to every user of the bot",
+    );
 
-    bot.send(SetMyCommands::new(private_chats).scope(BotCommandScopeAllPrivateChats {}))
-        .await?;
+    let admin_chats: Vec<BotCommand> = private_chats
+        .into_iter()
+        .chain([stats, user, ban, unban, block, unblock, broadcast])
+        .collect();
+
+    for admin_id in admin_ids {
+        // admin may have never started the bot, so commands can't be set in the chat
+        if let Err(err) = bot
+            .send(SetMyCommands::new(admin_chats.clone()).scope(BotCommandScopeChat::new(admin_id)))
+            .await
+        {
+            error!(
+                ?err,
+                admin_id, "An error occurded while set commands of the admin:"
+            );
+        }
+    }
 
     Ok(())
 }
 
+/// Create router for admin commands, which handles only updates of the users from `admin_ids`
+async fn admin_router<DB>(
+    pool: Pool<DB>,
+    admin_ids: &[i64],
+    broadcasts: Broadcasts,
+    blocklist: Blocklist,
+) -> Router<Reqwest>
+where
+    DB: Database,
+    for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
+    for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
+{
+    let mut admin_router = Router::new("admin");
+
+    admin_router
+        .update
+        .outer_middlewares
+        .register(DatabaseMiddleware::new(UoWFactory::new(pool)));
+
+    admin_router
+        .update
+        .outer_middlewares
+        .register(BroadcastsMiddleware::new(broadcasts));
+
+    admin_router
+        .update
+        .outer_middlewares
+        .register(BlocklistMiddleware::new(blocklist));
+
+    admin_commands::<DB>(&mut admin_router, Admin::new(admin_ids.iter().copied())).await;
+
+    admin_router
+}
+
+/// Create router for private chats with all middlewares and commands of the bot.
+/// `client_middleware` is [`ClientApplicationMiddleware`] (tests replace it, because client can't be
+/// created without connection to Telegram).
+async fn private_router<S, DB, C>(
+    storage: S,
+    bot: &Bot,
+    pool: Pool<DB>,
+    client_middleware: C,
+    quotas: Quotas,
+    blocklist: Blocklist,
+) -> Router<Reqwest>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+    C: OuterMiddleware + 'static,
+    S::Error: Debug,
+    DB: Database,
+    for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
+    for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
+{
+    let mut private_router = Router::new("private");
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(FSMContext::new(storage.clone()).strategy(Strategy::UserInChat));
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(FsmStateMiddleware::new(storage));
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(DatabaseMiddleware::new(UoWFactory::new(pool.clone())));
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(client_middleware);
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(CreateUserMiddleware::new(
+            UoWFactory::new(pool.clone()).create_uow(),
+        ));
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(BannedUserMiddleware::new(
+            UoWFactory::new(pool.clone()).create_uow(),
+        ));
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(QuotaMiddleware::new(quotas));
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(BlocklistMiddleware::new(blocklist));
+
+    private_router
+        .update
+        .outer_middlewares
+        .register(DeletedSetsMiddleware::new(
+            UoWFactory::new(pool).create_uow(),
+            bot.clone(),
+        ));
+
+    process_non_command::<S>(
+        &mut private_router,
+        &[
+            "source",
+            "src",
+            "stealpack",
+            "addstickers",
+            "help",
+            "cancel",
+            "mystickers",
+            "export",
+            "import",
+            "download",
+            "fromarchive",
+            "protectmypacks",
+            "unprotect",
+        ],
+    )
+    .await;
+
+    start_command::<S>(&mut private_router, &["start", "help"]).await;
+
+    source_command::<S>(&mut private_router, &["src", "source"]).await;
+
+    cancel_command::<S>(&mut private_router, &["cancel"]).await;
+
+    add_stickers_command::<S, DB>(&mut private_router, "addstickers", "done").await;
+
+    steal_sticker_set_command::<S, DB>(&mut private_router, "stealpack").await;
+
+    my_stickers::<S, DB>(&mut private_router, "mystickers").await;
+
+    export_command::<S, DB>(&mut private_router, "export").await;
+
+    import_command::<S, DB>(&mut private_router, "import").await;
+
+    download_command::<S>(&mut private_router, "download").await;
+
+    from_archive_command::<S, DB>(&mut private_router, "fromarchive").await;
+
+    protect_command::<S>(&mut private_router, "protectmypacks", "unprotect").await;
+
+    process_non_sticker(&mut private_router, ContentTypeEnum::Sticker).await;
+
+    process_non_document(&mut private_router).await;
+
+    private_router
+}
+
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    /// Path to the config file [default: configs/config.toml]. Every field of the config can be
+    /// overridden by environment variable like `STEAL_BOT__POSTGRES__HOST`.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -70,44 +330,62 @@ struct Cli {
 enum Commands {
     /// Authorize client and exit
     Auth,
-    /// Run programm (exit if client not authorized)
-    Run,
+    /// Apply pending database migrations and exit
+    Migrate,
+    /// Run programm (exit if client not authorized or database schema is outdated)
+    Run {
+        /// Apply pending database migrations before start
+        #[arg(long)]
+        migrate: bool,
+    },
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
-    let config = std::fs::read_to_string("configs/config.toml")
-        .expect("error occurded while read config file");
-    let config: ConfigToml = toml::from_str(&config).unwrap();
+    let cli = Cli::parse();
+
+    let config = match load_config(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            // tracing isn't initialized yet, because log level is specified in config
+            eprintln!("{err}");
+
+            process::exit(1);
+        }
+    };
+
+    let tracer_provider = match config.telemetry.as_ref().map(otlp_tracer_provider) {
+        Some(Ok(provider)) => Some(provider),
+        Some(Err(err)) => {
+            eprintln!("An error occurded while create OTLP exporter: {err}");
 
-    let log_level = match std::env::var("LOG_LEVEL") {
-        Ok(log_level) => log_level,
-        Err(_) => config.clone().tracing.log_level,
+            process::exit(1);
+        }
+        None => None,
     };
 
-    let db_url = config.get_postgres_url();
-
-    tracing_subscriber::registry()
-        .with(fmt::layer())
-        .with(
-            EnvFilter::new(log_level)
-                .add_directive("hyper=warn".parse().expect("Invalid directive"))
-                .add_directive("reqwest=warn".parse().expect("Invalid directive"))
-                .add_directive("grammers=warn".parse().expect("Invalid directive"))
-                .add_directive("sqlx=warn".parse().expect("Invalid directive"))
-                .add_directive(
-                    "telers::client::session::base=off"
-                        .parse()
-                        .expect("Invalid directive"),
-                ),
-        )
-        .init();
+    init_tracing(&config.tracing, tracer_provider.as_ref());
 
-    let (api_id, api_hash) = (config.tg_app.api_id, config.tg_app.api_hash);
+    if Commands::Migrate == cli.command {
+        let result = match connect_database(&config).await {
+            DatabasePool::Postgres(pool) => MIGRATOR.run(&pool).await,
+            DatabasePool::Sqlite(pool) => SQLITE_MIGRATOR.run(&pool).await,
+        };
+
+        if let Err(err) = result {
+            error!(?err, "An error occurded while apply migrations:");
+
+            process::exit(1);
+        }
+
+        debug!("Migrations successfully applied");
+
+        process::exit(0);
+    }
 
     debug!("Connecting client..");
 
-    let client = match client_connect(api_id, api_hash.clone()).await {
+    let client = match client_connect(config.tg_app.api_id, config.tg_app.api_hash.clone()).await {
         Ok(client) => client,
         Err(err) => {
             error!(?err, "An error occurded while client connect:");
@@ -118,8 +396,6 @@ async fn main() {
 
     debug!("Client connected");
 
-    let cli = Cli::parse();
-
     if Commands::Auth == cli.command {
         if let Err(err) = client_authorize(
             &client,
@@ -137,105 +413,327 @@ async fn main() {
 
         process::exit(0);
     }
-    if Commands::Run == cli.command && !client.is_authorized().await.expect("error to authorize") {
+    if matches!(cli.command, Commands::Run { .. })
+        && !client.is_authorized().await.expect("error to authorize")
+    {
         error!("Client is not authorized! Run programm with command auth:\njust auth");
 
         process::exit(1);
     }
 
+    let migrate = matches!(cli.command, Commands::Run { migrate: true });
+
+    match connect_database(&config).await {
+        DatabasePool::Postgres(pool) => {
+            check_migrations(&MIGRATOR, &pool, migrate).await;
+
+            run_bot::<Postgres>(config, pool, client).await;
+        }
+        DatabasePool::Sqlite(pool) => {
+            check_migrations(&SQLITE_MIGRATOR, &pool, migrate).await;
+
+            run_bot::<Sqlite>(config, pool, client).await;
+        }
+    }
+
+    // export spans, which are still in the batch
+    if let Some(provider) = tracer_provider {
+        if let Err(err) = provider.shutdown() {
+            error!(?err, "An error occurded while shutdown tracer provider:");
+        }
+    }
+}
+
+enum DatabasePool {
+    Postgres(PgPool),
+    Sqlite(SqlitePool),
+}
+
+/// Connect to the database, which is specified in config (exit if connection failed)
+async fn connect_database(config: &ConfigToml) -> DatabasePool {
     debug!("Connecting to the database..");
 
-    let pool = match sqlx::PgPool::connect(&db_url).await {
-        Ok(pool) => pool,
+    let pool = match config.database.backend {
+        DatabaseBackend::Postgres => {
+            let Some(db_url) = config.get_postgres_url() else {
+                error!("`[postgres]` section should be specified to use postgres database");
+
+                process::exit(1);
+            };
+
+            PgPool::connect(&db_url).await.map(DatabasePool::Postgres)
+        }
+        DatabaseBackend::Sqlite => {
+            let options = SqliteConnectOptions::new()
+                .filename(&config.database.sqlite_path)
+                .create_if_missing(true);
+
+            SqlitePool::connect_with(options)
+                .await
+                .map(DatabasePool::Sqlite)
+        }
+    };
+
+    match pool {
+        Ok(pool) => {
+            debug!("Connected to database");
+
+            pool
+        }
         Err(err) => {
             error!(?err, "An error occurded while connect to database:");
 
             process::exit(1);
         }
-    };
+    }
+}
 
-    debug!("Connected to database");
+/// Apply pending migrations if `migrate` is `true` and exit if database schema is still outdated
+async fn check_migrations<DB>(migrator: &Migrator, pool: &Pool<DB>, migrate: bool)
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    if migrate {
+        debug!("Applying migrations..");
 
-    let bot = Bot::new(config.bot.bot_token);
+        if let Err(err) = migrator.run(pool).await {
+            error!(?err, "An error occurded while apply migrations:");
 
-    let mut main_router: Router<Reqwest> = Router::new("main");
-    let mut private_router = Router::new("private");
+            process::exit(1);
+        }
+    }
 
-    let storage = MemoryStorage::new();
+    match pending_migrations(migrator, pool).await {
+        Ok(pending) if pending.is_empty() => {}
+        Ok(pending) => {
+            error!(
+                ?pending,
+                "Database schema is outdated! Apply migrations with command:\njust migrate OR run programm with command `run --migrate`"
+            );
 
-    private_router
-        .update
-        .outer_middlewares
-        .register(FSMContext::new(storage).strategy(Strategy::UserInChat));
+            process::exit(1);
+        }
+        Err(err) => {
+            error!(?err, "An error occurded while check migrations:");
 
-    private_router
-        .update
-        .outer_middlewares
-        .register(DatabaseMiddleware::new(UoWFactory::new(pool.clone())));
+            process::exit(1);
+        }
+    }
+}
 
-    private_router
-        .update
-        .outer_middlewares
-        .register(ClientApplicationMiddleware::new(client, api_id, api_hash));
+async fn run_bot<DB>(config: ConfigToml, pool: Pool<DB>, client: Client)
+where
+    DB: Database,
+    Pool<DB>: FsmStatesPool + QuotaEventsPool + BroadcastsPool + BlockedSetsPool,
+    for<'a> UserRepoImpl<&'a mut DB::Connection>: UserRepo,
+    for<'a> SetRepoImpl<&'a mut DB::Connection>: SetRepo,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+{
+    let (api_id, api_hash) = (config.tg_app.api_id, config.tg_app.api_hash);
 
-    private_router
-        .update
-        .outer_middlewares
-        .register(CreateUserMiddleware::new(
-            UoWFactory::new(pool.clone()).create_uow(),
-        ));
+    if let Some(metrics) = config.metrics {
+        let pool = pool.clone();
 
-    private_router
-        .update
-        .outer_middlewares
-        .register(DeletedSetsMiddleware::new(
-            UoWFactory::new(pool).create_uow(),
-            bot.clone(),
-        ));
+        tokio::spawn(async move {
+            if let Err(err) = serve_metrics(&metrics.address, pool).await {
+                error!(?err, "An error occurded while serve metrics:");
+            }
+        });
+    }
 
-    process_non_command(
-        &mut private_router,
-        &[
-            "source",
-            "src",
-            "stealpack",
-            "addstickers",
-            "help",
-            "cancel",
-            "mystickers",
-        ],
-    )
-    .await;
+    init_rate_limiter(&config.rate_limit);
 
-    start_command(&mut private_router, &["start", "help"]).await;
+    let bot = Bot::new(config.bot.bot_token);
 
-    source_command(&mut private_router, &["src", "source"]).await;
+    let quotas = Quotas::new(pool.clone(), &config.quotas);
+    quotas.spawn_cleanup();
+    let broadcasts = Broadcasts::new(pool.clone());
 
-    cancel_command(&mut private_router, &["cancel"]).await;
+    let client_middleware = ClientApplicationMiddleware::new(client, api_id, api_hash);
+    let shared_client = client_middleware.shared_client();
 
-    add_stickers_command::<Postgres>(&mut private_router, "addstickers", "done").await;
+    let blocklist = Blocklist::new(pool.clone()).client(shared_client.clone());
 
-    steal_sticker_set_command::<Postgres>(&mut private_router, "stealpack").await;
+    let mut main_router: Router<Reqwest> = Router::new("main");
 
-    my_stickers::<Postgres>(&mut private_router, "mystickers").await;
+    let private_router = match config.fsm.storage {
+        FsmStorageKind::Memory => {
+            private_router(
+                MemoryStorage::new(),
+                &bot,
+                pool.clone(),
+                client_middleware,
+                quotas,
+                blocklist.clone(),
+            )
+            .await
+        }
+        FsmStorageKind::Database => {
+            let storage = DatabaseStorage::new(pool.clone());
+
+            private_router(
+                storage,
+                &bot,
+                pool.clone(),
+                client_middleware,
+                quotas,
+                blocklist.clone(),
+            )
+            .await
+        }
+        #[cfg(feature = "redis-storage")]
+        FsmStorageKind::Redis => {
+            let fsm = &config.fsm;
+
+            let Some(redis_url) = fsm.redis_url.as_deref() else {
+                error!("`redis_url` should be specified in `[fsm]` section to use redis storage");
+
+                process::exit(1);
+            };
+
+            let storage = match RedisStorage::connect(redis_url).await {
+                Ok(storage) => storage
+                    .prefix(fsm.prefix.as_str())
+                    .ttl(fsm.ttl.map(Duration::from_secs)),
+                Err(err) => {
+                    error!(?err, "An error occurded while connect to redis:");
+
+                    process::exit(1);
+                }
+            };
+
+            private_router(
+                storage,
+                &bot,
+                pool.clone(),
+                client_middleware,
+                quotas,
+                blocklist.clone(),
+            )
+            .await
+        }
+        #[cfg(not(feature = "redis-storage"))]
+        FsmStorageKind::Redis => {
+            error!("Bot built without `redis-storage` feature, so redis storage can't be used");
 
-    process_non_sticker(&mut private_router, ContentTypeEnum::Sticker).await;
+            process::exit(1);
+        }
+    };
+
+    // admin commands are handled before the commands of the users
+    if !config.admin_ids.is_empty() {
+        main_router.include(
+            admin_router(
+                pool.clone(),
+                &config.admin_ids,
+                broadcasts.clone(),
+                blocklist,
+            )
+            .await,
+        );
+    }
 
     main_router.include(private_router);
-    main_router.startup.register(set_commands, (bot.clone(),));
+    main_router
+        .startup
+        .register(set_commands, (bot.clone(), config.admin_ids.clone()));
+
+    let allowed_updates: Vec<String> = main_router
+        .resolve_used_update_types()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
 
     let dispatcher = Dispatcher::builder()
-        .bot(bot)
-        .allowed_updates(main_router.resolve_used_update_types())
+        .bot(bot.clone())
         .router(main_router)
         .build();
 
-    match dispatcher
-        .to_service_provider_default()
-        .expect("error occurded while convert the service factory to the service")
-        .run_polling()
-        .await
-    {
+    let service = Arc::new(
+        dispatcher
+            .to_service_provider_default()
+            .expect("error occurded while convert the service factory to the service"),
+    );
+
+    let polling_status = PollingStatus::default();
+
+    if let Some(health) = config.health {
+        let mut checks: Vec<Box<dyn ReadinessCheck>> = vec![
+            Box::new(DatabaseCheck::new(pool.clone())),
+            Box::new(ClientCheck::new(shared_client.clone())),
+        ];
+        // updates aren't polled in webhook mode
+        if config.webhook.is_none() {
+            checks.push(Box::new(PollingCheck::new(
+                polling_status.clone(),
+                Duration::from_secs(health.max_poll_age),
+            )));
+        }
+
+        tokio::spawn(async move {
+            if let Err(err) = serve_health(&health.address, checks).await {
+                error!(?err, "An error occurded while serve health probes:");
+            }
+        });
+    }
+
+    if let Err(err) = service.emit_startup().await {
+        error!(?err, "An error occurded while emit startup:");
+
+        process::exit(1);
+    }
+
+    if let Err(err) = broadcasts.resume(&bot).await {
+        error!(?err, "An error occurded while resume broadcasts:");
+    }
+
+    let feed_service = service.clone();
+    let feed_bot = bot.clone();
+    let feed = move |update: Update| {
+        let service = feed_service.clone();
+        let bot = feed_bot.clone();
+        let span = update_span(&update);
+
+        async move {
+            if let Err(err) = service.feed_update(bot, update).await {
+                error!(?err, "An error occurded while process update:");
+            }
+        }
+        .instrument(span)
+    };
+
+    let result = match config.webhook {
+        Some(webhook) => run_webhook(&bot, &webhook, allowed_updates, feed).await,
+        None => {
+            run_polling(&bot, allowed_updates, polling_status, feed).await;
+
+            Ok(())
+        }
+    };
+
+    debug!("Updates are not received anymore");
+
+    finish_operations(
+        &bot,
+        &OPERATIONS,
+        Duration::from_secs(config.shutdown.deadline),
+    )
+    .await;
+
+    if let Err(err) = service.emit_shutdown().await {
+        error!(?err, "An error occurded while emit shutdown:");
+    }
+
+    pool.close().await;
+
+    let client = shared_client.lock().await.clone();
+    if let Err(err) = client_save_session(&client) {
+        error!(?err, "An error occurded while save client session:");
+    }
+
+    match result {
         Ok(()) => debug!("Bot stopped"),
         Err(err) => debug!("Bot stopped with error: {err}"),
     }
@@ -0,0 +1,217 @@
+use std::{io, sync::LazyLock};
+
+use axum::{http::header::CONTENT_TYPE, routing::get, Router as HttpRouter};
+use prometheus::{
+    core::Collector, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    Opts, Registry, TextEncoder,
+};
+use sqlx::{Database, Pool};
+use tokio::net::TcpListener;
+
+/// Prefix of the every metric of the bot
+pub const METRICS_NAMESPACE: &str = "steal_stickers_bot";
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    registry: Registry,
+    /// Updates processed by handler (label is name of the handler function)
+    pub updates: IntCounterVec,
+    pub steals_started: IntCounter,
+    pub steals_completed: IntCounter,
+    pub steals_failed: IntCounter,
+    pub steal_duration: Histogram,
+    pub stickers_added: IntCounter,
+    /// Errors of `AddStickerToSet` method by type of the error (see `add_sticker_error_label`)
+    pub add_sticker_to_set_errors: IntCounterVec,
+    pub get_sticker_set_user_id_duration: Histogram,
+    /// Duration of the outer middlewares (label is name of the middleware)
+    pub middleware_duration: HistogramVec,
+    /// Sticker sets, which were deleted by users and marked as deleted in the database
+    pub deleted_sets: IntCounter,
+    pub client_reconnects: IntCounter,
+    pub db_pool_connections: IntGauge,
+    pub db_pool_idle_connections: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some(METRICS_NAMESPACE.to_owned()), None)
+            .expect("namespace of the metrics should be valid");
+
+        Self {
+            updates: register(
+                &registry,
+                IntCounterVec::new(
+                    Opts::new("updates_total", "Updates processed by handler"),
+                    &["handler"],
+                ),
+            ),
+            steals_started: register(
+                &registry,
+                IntCounter::new("steals_started_total", "Started steals of sticker sets"),
+            ),
+            steals_completed: register(
+                &registry,
+                IntCounter::new(
+                    "steals_completed_total",
+                    "Steals of sticker sets, after which new sticker set was created",
+                ),
+            ),
+            steals_failed: register(
+                &registry,
+                IntCounter::new(
+                    "steals_failed_total",
+                    "Steals of sticker sets, after which new sticker set wasn't created",
+                ),
+            ),
+            steal_duration: register(
+                &registry,
+                Histogram::with_opts(
+                    HistogramOpts::new(
+                        "steal_duration_seconds",
+                        "Duration of steal of sticker set",
+                    )
+                    .buckets(vec![1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0]),
+                ),
+            ),
+            stickers_added: register(
+                &registry,
+                IntCounter::new(
+                    "stickers_added_total",
+                    "Stickers added into sticker sets one by one",
+                ),
+            ),
+            add_sticker_to_set_errors: register(
+                &registry,
+                IntCounterVec::new(
+                    Opts::new(
+                        "add_sticker_to_set_errors_total",
+                        "Errors of `AddStickerToSet` method by type of the error",
+                    ),
+                    &["error"],
+                ),
+            ),
+            get_sticker_set_user_id_duration: register(
+                &registry,
+                Histogram::with_opts(HistogramOpts::new(
+                    "get_sticker_set_user_id_duration_seconds",
+                    "Latency of getting owner of sticker set using client application",
+                )),
+            ),
+            middleware_duration: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "middleware_duration_seconds",
+                        "Duration of outer middleware",
+                    ),
+                    &["middleware"],
+                ),
+            ),
+            deleted_sets: register(
+                &registry,
+                IntCounter::new(
+                    "deleted_sets_total",
+                    "Sticker sets marked as deleted, because they were deleted by users",
+                ),
+            ),
+            client_reconnects: register(
+                &registry,
+                IntCounter::new(
+                    "client_reconnects_total",
+                    "Reconnections of client application",
+                ),
+            ),
+            db_pool_connections: register(
+                &registry,
+                IntGauge::new("db_pool_connections", "Connections opened by database pool"),
+            ),
+            db_pool_idle_connections: register(
+                &registry,
+                IntGauge::new(
+                    "db_pool_idle_connections",
+                    "Idle connections of database pool",
+                ),
+            ),
+            registry,
+        }
+    }
+
+    pub fn update_handled(&self, handler: &str) {
+        self.updates.with_label_values(&[handler]).inc();
+    }
+
+    /// Update gauges of the database pool
+    pub fn observe_pool<DB: Database>(&self, pool: &Pool<DB>) {
+        self.db_pool_connections.set(pool.size().into());
+        self.db_pool_idle_connections.set(pool.num_idle() as i64);
+    }
+
+    /// Return every metric in Prometheus text format
+    pub fn encode(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .expect("metrics should be encoded to text format")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn register<M>(registry: &Registry, metric: prometheus::Result<M>) -> M
+where
+    M: Collector + Clone + 'static,
+{
+    let metric = metric.expect("options of the metric should be valid");
+
+    registry
+        .register(Box::new(metric.clone()))
+        .expect("metric should be registered once");
+
+    metric
+}
+
+/// Serve metrics on `/metrics` path
+pub async fn serve_metrics<DB: Database>(address: &str, pool: Pool<DB>) -> io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+
+    let app = HttpRouter::new().route(
+        "/metrics",
+        get(move || {
+            let pool = pool.clone();
+
+            async move {
+                METRICS.observe_pool(&pool);
+
+                ([(CONTENT_TYPE, prometheus::TEXT_FORMAT)], METRICS.encode())
+            }
+        }),
+    );
+
+    axum::serve(listener, app).await
+}
+
+#[test]
+fn metrics_test() {
+    let metrics = Metrics::new();
+
+    metrics.update_handled("start_handler");
+    metrics.update_handled("start_handler");
+    metrics.steals_started.inc();
+    metrics
+        .add_sticker_to_set_errors
+        .with_label_values(&["STICKERS_TOO_MUCH"])
+        .inc();
+
+    let text = metrics.encode();
+
+    assert!(text.contains(r#"steal_stickers_bot_updates_total{handler="start_handler"} 2"#));
+    assert!(text.contains("steal_stickers_bot_steals_started_total 1"));
+    assert!(text.contains(
+        r#"steal_stickers_bot_add_sticker_to_set_errors_total{error="STICKERS_TOO_MUCH"} 1"#
+    ));
+}
@@ -1,9 +1,19 @@
+mod banned_user;
+mod blocklist;
+mod broadcasts;
 mod client_application;
 mod create_user;
 mod database;
 mod deleted_sets;
+mod fsm_state;
+mod quotas;
 
+pub use banned_user::BannedUserMiddleware;
+pub use blocklist::{Blocklist, BlocklistError, BlocklistMiddleware};
+pub use broadcasts::BroadcastsMiddleware;
 pub use client_application::{Client, ClientApplicationMiddleware};
 pub use create_user::CreateUserMiddleware;
 pub use database::DatabaseMiddleware;
 pub use deleted_sets::DeletedSetsMiddleware;
+pub use fsm_state::FsmStateMiddleware;
+pub use quotas::{QuotaKind, QuotaMiddleware, Quotas};
@@ -0,0 +1,78 @@
+use std::ops::DerefMut;
+use tokio::sync::RwLock;
+
+use telers::{
+    errors::{EventErrorKind, MiddlewareError},
+    event::EventReturn,
+    middlewares::{outer::MiddlewareResponse, OuterMiddleware},
+    router::Request,
+};
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::metrics::METRICS;
+
+use crate::application::{
+    common::traits::uow::UoW as UoWTrait,
+    user::{dto::get_by_tg_id::GetByTgID, traits::UserRepo as _},
+};
+
+/// Ignore updates of the users, who are banned by admins.
+/// Should be registered after [`super::CreateUserMiddleware`], so the user always exists.
+#[derive(Debug)]
+pub struct BannedUserMiddleware<UoW> {
+    uow: RwLock<UoW>,
+}
+
+impl<UoW> BannedUserMiddleware<UoW>
+where
+    UoW: UoWTrait,
+{
+    pub fn new(uow: UoW) -> Self {
+        Self {
+            uow: RwLock::new(uow),
+        }
+    }
+}
+
+#[async_trait]
+impl<UoW> OuterMiddleware for BannedUserMiddleware<UoW>
+where
+    UoW: UoWTrait + Send + Sync,
+    for<'a> UoW::UserRepo<'a>: Send + Sync,
+{
+    async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        let _timer = METRICS
+            .middleware_duration
+            .with_label_values(&["banned_user"])
+            .start_timer();
+
+        let Some(user_id) = request.update.from_id() else {
+            return Ok((request, EventReturn::default()));
+        };
+
+        let mut uow = self.uow.write().await;
+        let uow = uow.deref_mut();
+
+        let result = uow
+            .user_repo()
+            .await
+            .map_err(MiddlewareError::new)?
+            .get_by_tg_id(GetByTgID::new(user_id))
+            .await;
+
+        // close transaction, so connection is not held until the next call
+        uow.rollback().await.map_err(MiddlewareError::new)?;
+
+        let user = result.map_err(MiddlewareError::new)?;
+
+        if user.banned {
+            debug!(user_id, "Update of the banned user is ignored");
+
+            return Ok((request, EventReturn::Cancel));
+        }
+
+        Ok((request, EventReturn::default()))
+    }
+}
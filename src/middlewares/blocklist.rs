@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use telers::{
+    errors::EventErrorKind,
+    event::EventReturn,
+    middlewares::{outer::MiddlewareResponse, OuterMiddleware},
+    router::Request,
+    FromContext,
+};
+
+use async_trait::async_trait;
+use grammers_client::Client as ClientGrammers;
+use thiserror::Error;
+
+use crate::{
+    infrastructure::database::blocked_sets::{BlockedSet, BlockedSetsPool},
+    telegram_application::{errors::OwnerError, resolve_sticker_set_owner},
+};
+
+#[derive(Debug, Error)]
+pub enum BlocklistError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Owner(#[from] OwnerError),
+}
+
+/// Sticker packs, which can't be stolen or used as source of stickers.
+/// Handlers get it from context to check sticker packs of the users.
+#[derive(Clone, FromContext)]
+#[context(key = "blocklist")]
+pub struct Blocklist {
+    pool: Arc<dyn BlockedSetsPool>,
+    /// Client to get owners of sticker packs, without it only short names are checked
+    client: Option<Arc<Mutex<ClientGrammers>>>,
+}
+
+impl Blocklist {
+    pub fn new(pool: impl BlockedSetsPool) -> Self {
+        Self {
+            pool: Arc::new(pool),
+            client: None,
+        }
+    }
+
+    pub fn client(self, client: Arc<Mutex<ClientGrammers>>) -> Self {
+        Self {
+            client: Some(client),
+            ..self
+        }
+    }
+
+    /// Return `false`, if the sticker pack is already blocked
+    pub async fn block(&self, set: &BlockedSet) -> Result<bool, sqlx::Error> {
+        self.pool.block(set).await
+    }
+
+    /// Return `false`, if the sticker pack isn't blocked
+    pub async fn unblock(&self, set: &BlockedSet) -> Result<bool, sqlx::Error> {
+        self.pool.unblock(set).await
+    }
+
+    /// Block the sticker pack on request of its creator.
+    /// Return `false`, if the sticker pack is already blocked.
+    pub async fn protect(&self, set: &BlockedSet, user_id: i64) -> Result<bool, sqlx::Error> {
+        self.pool.protect(set, user_id).await
+    }
+
+    /// Return number of sticker packs, which were protected by the creator
+    pub async fn unprotect(&self, user_id: i64) -> Result<u64, sqlx::Error> {
+        self.pool.unprotect(user_id).await
+    }
+
+    /// Check the sticker pack by its short name and by its owner.
+    /// If the owner isn't known, it's got using client only when any owner is blocked.
+    pub async fn is_blocked(
+        &self,
+        set_name: &str,
+        owner_id: Option<i64>,
+    ) -> Result<bool, BlocklistError> {
+        let owner_id = match (owner_id, &self.client) {
+            (Some(owner_id), _) => Some(owner_id),
+            (None, Some(client)) if self.pool.has_blocked_owners().await? => {
+                let client = client.lock().await.clone();
+
+                Some(resolve_sticker_set_owner(set_name, &client).await?)
+            }
+            (None, _) => None,
+        };
+
+        Ok(self.pool.is_blocked(set_name, owner_id).await?)
+    }
+}
+
+/// Put [`Blocklist`] into context
+pub struct BlocklistMiddleware {
+    blocklist: Blocklist,
+}
+
+impl BlocklistMiddleware {
+    pub const fn new(blocklist: Blocklist) -> Self {
+        Self { blocklist }
+    }
+}
+
+#[async_trait]
+impl OuterMiddleware for BlocklistMiddleware {
+    async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        request
+            .context
+            .insert("blocklist", Box::new(self.blocklist.clone()));
+
+        Ok((request, EventReturn::default()))
+    }
+}
@@ -0,0 +1,32 @@
+use telers::{
+    errors::EventErrorKind,
+    event::EventReturn,
+    middlewares::{outer::MiddlewareResponse, OuterMiddleware},
+    router::Request,
+};
+
+use async_trait::async_trait;
+
+use crate::broadcast::Broadcasts;
+
+/// Put [`Broadcasts`] into context for admin handlers
+pub struct BroadcastsMiddleware {
+    broadcasts: Broadcasts,
+}
+
+impl BroadcastsMiddleware {
+    pub const fn new(broadcasts: Broadcasts) -> Self {
+        Self { broadcasts }
+    }
+}
+
+#[async_trait]
+impl OuterMiddleware for BroadcastsMiddleware {
+    async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        request
+            .context
+            .insert("broadcasts", Box::new(self.broadcasts.clone()));
+
+        Ok((request, EventReturn::default()))
+    }
+}
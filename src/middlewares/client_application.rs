@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use telers::{
@@ -14,7 +15,7 @@ use grammers_client::Client as ClientGrammers;
 
 use tracing::debug;
 
-use crate::telegram_application::client_connect;
+use crate::{metrics::METRICS, telegram_application::client_connect};
 
 #[derive(Debug, Clone, FromContext)]
 #[context(key = "client", from = ClientGrammers)]
@@ -29,7 +30,7 @@ impl From<ClientGrammers> for Client {
 #[derive(Debug)]
 pub struct ClientApplicationMiddleware {
     pub key: &'static str,
-    pub client: Mutex<ClientGrammers>,
+    pub client: Arc<Mutex<ClientGrammers>>,
     pub last_update_time: Mutex<NaiveTime>,
     pub api_id: i32,
     pub api_hash: String,
@@ -39,17 +40,27 @@ impl ClientApplicationMiddleware {
     pub fn new(client: ClientGrammers, api_id: i32, api_hash: String) -> Self {
         Self {
             key: "client",
-            client: Mutex::new(client),
+            client: Arc::new(Mutex::new(client)),
             last_update_time: Mutex::new(Utc::now().time()),
             api_id,
             api_hash,
         }
     }
+
+    /// Client, which is replaced by the middleware after reconnection
+    pub fn shared_client(&self) -> Arc<Mutex<ClientGrammers>> {
+        self.client.clone()
+    }
 }
 
 #[async_trait]
 impl OuterMiddleware for ClientApplicationMiddleware {
     async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        let _timer = METRICS
+            .middleware_duration
+            .with_label_values(&["client_application"])
+            .start_timer();
+
         let mut lock = self.last_update_time.lock().await;
 
         let now = Utc::now().time();
@@ -57,6 +68,8 @@ impl OuterMiddleware for ClientApplicationMiddleware {
         if (now - *lock).num_minutes() >= 10 {
             debug!("Update client");
 
+            METRICS.client_reconnects.inc();
+
             *lock = now;
 
             let client = client_connect(self.api_id, self.api_hash.clone())
@@ -10,6 +10,8 @@ use telers::{
 
 use async_trait::async_trait;
 
+use crate::metrics::METRICS;
+
 use crate::application::{
     commands::create_user::create_user, common::traits::uow::UoW as UoWTrait,
     user::dto::create::Create,
@@ -38,6 +40,11 @@ where
     for<'a> UoW::UserRepo<'a>: Send + Sync,
 {
     async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        let _timer = METRICS
+            .middleware_duration
+            .with_label_values(&["create_user"])
+            .start_timer();
+
         let mut uow = self.uow.write().await;
 
         let user_id = match request.update.from_id() {
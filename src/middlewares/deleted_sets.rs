@@ -1,5 +1,5 @@
 use chrono::{NaiveTime, Utc};
-use std::{ops::DerefMut, time::Duration};
+use std::ops::DerefMut;
 use tokio::sync::{Mutex, RwLock};
 use tracing::debug;
 
@@ -14,6 +14,9 @@ use telers::{
 
 use async_trait::async_trait;
 
+use crate::bot_api::BotApi as _;
+use crate::metrics::METRICS;
+
 use crate::application::{
     commands::set_deleted_col::set_deleted_col,
     common::traits::uow::UoW as UoWTrait,
@@ -50,6 +53,11 @@ where
     for<'a> UoW::SetRepo<'a>: Send + Sync,
 {
     async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        let _timer = METRICS
+            .middleware_duration
+            .with_label_values(&["deleted_sets"])
+            .start_timer();
+
         let mut uow = self.uow.write().await;
 
         let mut last_upd_time_lock = self.last_update_time.lock().await;
@@ -79,9 +87,9 @@ where
                 .await
                 .map_err(MiddlewareError::new)?;
 
-            for (i, sticker) in sets.into_iter().enumerate() {
+            for sticker in sets {
                 if let Err(err) = bot
-                    .send(GetStickerSet::new(sticker.short_name.as_str()))
+                    .send_limited(GetStickerSet::new(sticker.short_name.as_str()))
                     .await
                 {
                     if matches!(err,  ErrorKind::Telegram(TelegramErrorKind::BadRequest { message }) if message.as_ref()
@@ -93,11 +101,9 @@ where
                         )
                         .await
                         .map_err(MiddlewareError::new)?;
-                    }
-                }
 
-                if i % 5 == 0 {
-                    tokio::time::sleep(Duration::from_millis(1010)).await;
+                        METRICS.deleted_sets.inc();
+                    }
                 }
             }
         }
@@ -0,0 +1,54 @@
+use std::fmt::Debug;
+
+use telers::{
+    errors::EventErrorKind,
+    event::EventReturn,
+    fsm::{Storage, StorageKey},
+    middlewares::{outer::MiddlewareResponse, OuterMiddleware},
+    router::Request,
+};
+use tracing::{error, Span};
+
+use async_trait::async_trait;
+
+/// Record FSM state of the user into the span of the update.
+/// Key of the state is the same as with `Strategy::UserInChat` of `FSMContext`.
+#[derive(Debug, Clone)]
+pub struct FsmStateMiddleware<S> {
+    storage: S,
+}
+
+impl<S> FsmStateMiddleware<S> {
+    pub const fn new(storage: S) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl<S> OuterMiddleware for FsmStateMiddleware<S>
+where
+    S: Storage + Send + Sync + 'static,
+    S::Error: Debug,
+{
+    async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        let (Some(chat_id), Some(user_id)) = (request.update.chat_id(), request.update.from_id())
+        else {
+            return Ok((request, EventReturn::default()));
+        };
+
+        let key = StorageKey::new(request.bot.bot_id, chat_id, user_id);
+
+        match self.storage.get_state(&key).await {
+            Ok(Some(state)) => {
+                Span::current().record("fsm_state", state.as_ref());
+            }
+            Ok(None) => {}
+            // state is only needed for logs, so update is processed anyway
+            Err(err) => {
+                error!(?err, "An error occurded while get state for logs:");
+            }
+        }
+
+        Ok((request, EventReturn::default()))
+    }
+}
@@ -0,0 +1,407 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+
+use telers::{
+    errors::{EventErrorKind, MiddlewareError},
+    event::EventReturn,
+    methods::SendMessage,
+    middlewares::{outer::MiddlewareResponse, OuterMiddleware},
+    router::Request,
+    FromContext,
+};
+
+use async_trait::async_trait;
+use sqlx::types::time::OffsetDateTime;
+use tracing::{debug, error};
+
+use crate::{
+    bot_api::BotApi as _,
+    config::QuotasConfig,
+    infrastructure::database::quota_events::{QuotaEvent, QuotaEventsPool},
+    metrics::METRICS,
+    texts::quota_exceeded_message,
+};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    /// Any update from the user
+    Message,
+    /// Stolen sticker pack or sticker pack created from archive
+    Steal,
+    /// Sticker added to sticker pack
+    Sticker,
+}
+
+impl QuotaKind {
+    /// Value of `kind` column of `quota_events` table
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Message => "message",
+            Self::Steal => "steal",
+            Self::Sticker => "sticker",
+        }
+    }
+}
+
+/// Maximum usage of the kind during the window
+#[derive(Debug, Clone)]
+struct Limit {
+    kind: QuotaKind,
+    max: u32,
+    window: Duration,
+    description: &'static str,
+}
+
+impl Limit {
+    /// Return time, after which `amount` more doesn't exceed the limit (`None`, if it doesn't exceed now).
+    /// Usage larger than the limit is allowed, when nothing is used during the window.
+    fn retry_at(
+        &self,
+        events: &[QuotaEvent],
+        amount: u32,
+        now: OffsetDateTime,
+    ) -> Option<OffsetDateTime> {
+        let mut used: Vec<&QuotaEvent> = events
+            .iter()
+            .filter(|event| event.kind == self.kind.as_str() && event.created > now - self.window)
+            .collect();
+        used.sort_by_key(|event| event.created);
+
+        let mut exceeded = used.iter().map(|event| event.amount).sum::<i64>()
+            + i64::from(amount.min(self.max))
+            - i64::from(self.max);
+
+        if exceeded <= 0 {
+            return None;
+        }
+
+        // wait until enough events leave the window
+        for event in used {
+            exceeded -= event.amount;
+
+            if exceeded <= 0 {
+                return Some(event.created + self.window);
+            }
+        }
+
+        Some(now + self.window)
+    }
+}
+
+/// Limit, which is exceeded by the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub description: &'static str,
+    pub retry_at: OffsetDateTime,
+}
+
+impl QuotaExceeded {
+    /// Message, which tells the user when the limit allows to retry
+    pub fn message(&self) -> String {
+        let wait = self.retry_at - OffsetDateTime::now_utc();
+
+        quota_exceeded_message(self.description, wait.unsigned_abs())
+    }
+}
+
+/// Limits of the users, which counters are kept in the database.
+/// Handlers of long operations get it from context to check limits of steals and added stickers.
+#[derive(Clone, FromContext)]
+#[context(key = "quotas")]
+pub struct Quotas {
+    pool: Arc<dyn QuotaEventsPool>,
+    limits: Arc<[Limit]>,
+}
+
+impl Quotas {
+    pub fn new(pool: impl QuotaEventsPool, config: &QuotasConfig) -> Self {
+        Self {
+            pool: Arc::new(pool),
+            limits: limits(config),
+        }
+    }
+
+    /// Record usage of the user, if it doesn't exceed limits of the user.
+    /// Otherwise nothing is recorded and the limit, which is exceeded the longest, is returned.
+    pub async fn take(
+        &self,
+        user_id: i64,
+        usage: &[(QuotaKind, u32)],
+    ) -> Result<Option<QuotaExceeded>, sqlx::Error> {
+        if !self.is_limited(usage) {
+            return Ok(None);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let mut exceeded_limit = None;
+
+        self.pool
+            .record_if(user_id, &events_usage(usage), &mut |events| {
+                exceeded_limit = exceeded(&self.limits, events, usage, now);
+
+                exceeded_limit.is_none()
+            })
+            .await?;
+
+        Ok(exceeded_limit)
+    }
+
+    /// Give back usage of the operation, which is failed, so it doesn't count in limits
+    pub async fn refund(
+        &self,
+        user_id: i64,
+        usage: &[(QuotaKind, u32)],
+    ) -> Result<(), sqlx::Error> {
+        if !self.is_limited(usage) {
+            return Ok(());
+        }
+
+        self.pool.refund(user_id, &events_usage(usage)).await
+    }
+
+    /// Remove expired events of all users every hour
+    pub fn spawn_cleanup(&self) {
+        let pool = Arc::clone(&self.pool);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HOUR);
+
+            loop {
+                interval.tick().await;
+
+                match pool.remove_expired().await {
+                    Ok(removed) => debug!(removed, "Expired quota events are removed"),
+                    Err(err) => {
+                        error!(?err, "An error occurded while remove expired quota events:")
+                    }
+                }
+            }
+        });
+    }
+
+    /// Usage without limits isn't recorded
+    fn is_limited(&self, usage: &[(QuotaKind, u32)]) -> bool {
+        usage
+            .iter()
+            .any(|(kind, _)| self.limits.iter().any(|limit| limit.kind == *kind))
+    }
+}
+
+/// Usage as events of `quota_events` table without empty ones
+fn events_usage(usage: &[(QuotaKind, u32)]) -> Vec<(&'static str, i64)> {
+    usage
+        .iter()
+        .filter(|(_, amount)| *amount > 0)
+        .map(|(kind, amount)| (kind.as_str(), i64::from(*amount)))
+        .collect()
+}
+
+/// Limits from config without disabled ones
+fn limits(config: &QuotasConfig) -> Arc<[Limit]> {
+    let limits = [
+        Limit {
+            kind: QuotaKind::Message,
+            max: config.messages_per_minute,
+            window: MINUTE,
+            description: "messages per minute",
+        },
+        Limit {
+            kind: QuotaKind::Steal,
+            max: config.steals_per_hour,
+            window: HOUR,
+            description: "stolen sticker packs per hour",
+        },
+        Limit {
+            kind: QuotaKind::Steal,
+            max: config.steals_per_day,
+            window: DAY,
+            description: "stolen sticker packs per day",
+        },
+        Limit {
+            kind: QuotaKind::Sticker,
+            max: config.stickers_per_day,
+            window: DAY,
+            description: "added stickers per day",
+        },
+    ];
+
+    limits.into_iter().filter(|limit| limit.max > 0).collect()
+}
+
+fn exceeded(
+    limits: &[Limit],
+    events: &[QuotaEvent],
+    usage: &[(QuotaKind, u32)],
+    now: OffsetDateTime,
+) -> Option<QuotaExceeded> {
+    usage
+        .iter()
+        .flat_map(|(kind, amount)| {
+            limits
+                .iter()
+                .filter(move |limit| limit.kind == *kind)
+                .filter_map(move |limit| {
+                    limit
+                        .retry_at(events, *amount, now)
+                        .map(|retry_at| QuotaExceeded {
+                            description: limit.description,
+                            retry_at,
+                        })
+                })
+        })
+        .max_by_key(|exceeded| exceeded.retry_at)
+}
+
+/// Limit messages of the users and put [`Quotas`] into context
+pub struct QuotaMiddleware {
+    quotas: Quotas,
+    /// Users, who are already told about exceeded limit of messages, and time of the retry
+    notified: Mutex<HashMap<i64, OffsetDateTime>>,
+}
+
+impl QuotaMiddleware {
+    pub fn new(quotas: Quotas) -> Self {
+        Self {
+            quotas,
+            notified: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl OuterMiddleware for QuotaMiddleware {
+    async fn call(&self, request: Request) -> Result<MiddlewareResponse, EventErrorKind> {
+        let _timer = METRICS
+            .middleware_duration
+            .with_label_values(&["quotas"])
+            .start_timer();
+
+        request
+            .context
+            .insert("quotas", Box::new(self.quotas.clone()));
+
+        let Some(user_id) = request.update.from_id() else {
+            return Ok((request, EventReturn::default()));
+        };
+
+        let exceeded = self
+            .quotas
+            .take(user_id, &[(QuotaKind::Message, 1)])
+            .await
+            .map_err(MiddlewareError::new)?;
+
+        let Some(exceeded) = exceeded else {
+            return Ok((request, EventReturn::default()));
+        };
+
+        debug!(
+            user_id,
+            limit = exceeded.description,
+            "Update of the user is ignored:"
+        );
+
+        let is_notified = {
+            let mut notified = self.notified.lock().await;
+            let now = OffsetDateTime::now_utc();
+
+            notified.retain(|_, retry_at| *retry_at > now);
+            notified.insert(user_id, exceeded.retry_at).is_some()
+        };
+
+        // tell only once, otherwise every ignored message gets answer
+        if !is_notified {
+            if let Some(chat_id) = request.update.chat_id() {
+                if let Err(err) = request
+                    .bot
+                    .send_limited(SendMessage::new(chat_id, exceeded.message()))
+                    .await
+                {
+                    error!(
+                        ?err,
+                        "An error occurded while notify user about exceeded limit:"
+                    );
+                }
+            }
+        }
+
+        Ok((request, EventReturn::Cancel))
+    }
+}
+
+#[test]
+fn quotas_exceeded_test() {
+    let config = QuotasConfig {
+        messages_per_minute: 0,
+        steals_per_hour: 2,
+        steals_per_day: 3,
+        stickers_per_day: 100,
+    };
+    let limits = limits(&config);
+    let now = OffsetDateTime::now_utc();
+
+    let event = |kind: QuotaKind, amount: i64, ago: Duration| QuotaEvent {
+        kind: kind.as_str().to_owned(),
+        amount,
+        created: now - ago,
+    };
+
+    // disabled limit of messages isn't checked
+    assert_eq!(limits.len(), 3);
+
+    let events = [
+        event(QuotaKind::Steal, 1, MINUTE * 50),
+        event(QuotaKind::Steal, 1, MINUTE * 10),
+        event(QuotaKind::Sticker, 60, HOUR * 3),
+    ];
+
+    assert_eq!(
+        exceeded(&limits, &events[1..], &[(QuotaKind::Steal, 1)], now),
+        None
+    );
+    assert_eq!(
+        exceeded(&limits, &events, &[(QuotaKind::Steal, 1)], now),
+        Some(QuotaExceeded {
+            description: "stolen sticker packs per hour",
+            retry_at: now + MINUTE * 10,
+        })
+    );
+
+    // 60 + 50 stickers are more than 100, so wait until the first 60 leave the day
+    assert_eq!(
+        exceeded(
+            &limits,
+            &events[2..],
+            &[(QuotaKind::Steal, 1), (QuotaKind::Sticker, 50)],
+            now
+        ),
+        Some(QuotaExceeded {
+            description: "added stickers per day",
+            retry_at: now + HOUR * 21,
+        })
+    );
+
+    // sticker pack larger than the limit is allowed, when nothing is used
+    assert_eq!(
+        exceeded(&limits, &[], &[(QuotaKind::Sticker, 120)], now),
+        None
+    );
+
+    // old events don't count
+    let events = [
+        event(QuotaKind::Steal, 1, DAY * 2),
+        event(QuotaKind::Steal, 1, HOUR * 2),
+        event(QuotaKind::Steal, 1, HOUR * 5),
+        event(QuotaKind::Steal, 1, HOUR * 7),
+    ];
+    assert_eq!(
+        exceeded(&limits, &events, &[(QuotaKind::Steal, 1)], now),
+        Some(QuotaExceeded {
+            description: "stolen sticker packs per day",
+            retry_at: now + HOUR * 17,
+        })
+    );
+}
@@ -0,0 +1,109 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use telers::{methods::GetUpdates, types::Update, Bot};
+use tracing::error;
+
+use crate::shutdown::shutdown_signal;
+
+/// Time in seconds, during which Telegram waits for new updates before answer to `getUpdates`
+pub const POLLING_TIMEOUT: i64 = 10;
+
+/// Maximum delay before the next `getUpdates` after errors
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Time of the last successful `getUpdates` request
+#[derive(Debug, Clone, Default)]
+pub struct PollingStatus {
+    last_success: Arc<Mutex<Option<Instant>>>,
+}
+
+impl PollingStatus {
+    pub fn succeeded(&self) {
+        *self
+            .last_success
+            .lock()
+            .expect("polling status lock poisoned") = Some(Instant::now());
+    }
+
+    /// Return `None`, if `getUpdates` didn't succeed yet
+    pub fn last_success(&self) -> Option<Instant> {
+        *self
+            .last_success
+            .lock()
+            .expect("polling status lock poisoned")
+    }
+}
+
+/// Receive updates using long polling and pass them into `feed` until SIGINT or SIGTERM is received.
+/// Received updates are confirmed before return, so they aren't handled twice after restart.
+/// If `getUpdates` fails, it's repeated with exponential backoff.
+pub async fn run_polling<Feed, Fut>(
+    bot: &Bot,
+    allowed_updates: Vec<String>,
+    status: PollingStatus,
+    feed: Feed,
+) where
+    Feed: Fn(Update) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut offset = None;
+    let mut backoff = Duration::from_secs(1);
+
+    // created once, so signal received while updates are processed isn't lost
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        let mut get_updates = GetUpdates::new()
+            .timeout(POLLING_TIMEOUT)
+            .allowed_updates(allowed_updates.clone());
+        if let Some(offset) = offset {
+            get_updates = get_updates.offset(offset);
+        }
+
+        let result = tokio::select! {
+            result = bot.send(get_updates) => result,
+            () = &mut shutdown => break,
+        };
+
+        match result {
+            Ok(updates) => {
+                status.succeeded();
+                backoff = Duration::from_secs(1);
+
+                for update in updates.into_iter() {
+                    offset = Some(update.update_id + 1);
+
+                    tokio::spawn(feed(update));
+                }
+            }
+            Err(err) => {
+                error!(?err, ?backoff, "An error occurded while get updates:");
+
+                tokio::select! {
+                    () = tokio::time::sleep(backoff) => {},
+                    () = &mut shutdown => break,
+                };
+
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+
+    confirm_updates(bot, offset).await;
+}
+
+/// Confirm the last received updates, otherwise Telegram sends them again after restart
+async fn confirm_updates(bot: &Bot, offset: Option<i64>) {
+    let Some(offset) = offset else {
+        return;
+    };
+
+    if let Err(err) = bot.send(GetUpdates::new().offset(offset).timeout(0)).await {
+        error!(?err, "An error occurded while confirm received updates:");
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        LazyLock, Mutex,
+    },
+    time::Duration,
+};
+
+use telers::{methods::SendMessage, Bot};
+use tokio::{sync::Notify, time::Instant};
+use tracing::{error, info};
+
+use crate::{bot_api::BotApi as _, texts::interrupted_operation_message};
+
+/// Time, during which cancelled operations can stop at the next sticker
+pub const CHECKPOINT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Long operations of the running bot, see [`Operations`]
+pub static OPERATIONS: LazyLock<Operations> = LazyLock::new(Operations::default);
+
+/// Wait for SIGINT (Ctrl+C) or SIGTERM (sent on stop of the container)
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            error!(?err, "An error occurded while wait for SIGINT:");
+
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                error!(?err, "An error occurded while wait for SIGTERM:");
+
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+}
+
+/// Operations, which shouldn't be killed halfway on shutdown (steals and adding of stickers).
+/// Shutdown waits for them, and if they don't finish in time, cancels them, so they stop
+/// at the next sticker (see [`Operations::is_cancelled`]).
+#[derive(Debug, Default)]
+pub struct Operations {
+    next_id: AtomicU64,
+    /// Chat of the user by id of the operation
+    running: Mutex<HashMap<u64, i64>>,
+    /// Operations are not started anymore, see [`Operations::stop`]
+    stopped: AtomicBool,
+    cancelled: AtomicBool,
+    finished: Notify,
+}
+
+impl Operations {
+    /// Register operation started by the user in the `chat_id`.
+    /// The operation is finished, when the returned guard is dropped.
+    /// Return `None`, if shutdown is already started, so the operation shouldn't be started.
+    pub fn start(&self, chat_id: i64) -> Option<OperationGuard<'_>> {
+        let mut running = self.running.lock().expect("operations lock poisoned");
+
+        // checked under the lock, so shutdown doesn't miss the operation started at the same time
+        if self.stopped.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        running.insert(id, chat_id);
+
+        Some(OperationGuard {
+            operations: self,
+            id,
+        })
+    }
+
+    /// Don't start new operations, because they can be started by updates,
+    /// which are still processed after shutdown begins
+    pub fn stop(&self) {
+        let _running = self.running.lock().expect("operations lock poisoned");
+
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn running(&self) -> usize {
+        self.running.lock().expect("operations lock poisoned").len()
+    }
+
+    /// Return `true`, if operations should stop as soon as possible
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Wait until every operation is finished. Return `false`, if `timeout` elapsed earlier.
+    pub async fn wait(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let finished = self.finished.notified();
+            tokio::pin!(finished);
+            // subscribe before check, otherwise notification between them is lost
+            finished.as_mut().enable();
+
+            if self.running() == 0 {
+                return true;
+            }
+
+            if tokio::time::timeout_at(deadline, finished).await.is_err() {
+                return false;
+            }
+        }
+    }
+
+    /// Ask running operations to stop and return chats of their users
+    pub fn cancel(&self) -> Vec<i64> {
+        self.cancelled.store(true, Ordering::Relaxed);
+
+        self.running
+            .lock()
+            .expect("operations lock poisoned")
+            .values()
+            .copied()
+            .collect()
+    }
+}
+
+/// Stop starting of new operations and wait for running ones during `deadline`.
+/// If some of them are still running, cancel them and tell their users, that operation was interrupted.
+pub async fn finish_operations(bot: &Bot, operations: &Operations, deadline: Duration) {
+    operations.stop();
+
+    let running = operations.running();
+    if running == 0 {
+        return;
+    }
+
+    info!(running, ?deadline, "Waiting for running operations");
+
+    if operations.wait(deadline).await {
+        info!("Running operations are finished");
+
+        return;
+    }
+
+    let chats = operations.cancel();
+
+    info!(cancelled = chats.len(), "Running operations are cancelled");
+
+    if !operations.wait(CHECKPOINT_TIMEOUT).await {
+        error!(
+            running = operations.running(),
+            "Cancelled operations didn't stop in time"
+        );
+    }
+
+    for chat_id in chats {
+        if let Err(err) = bot
+            .send_limited(SendMessage::new(chat_id, interrupted_operation_message()))
+            .await
+        {
+            error!(
+                ?err,
+                chat_id, "An error occurded while notify user about interrupted operation:"
+            );
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OperationGuard<'a> {
+    operations: &'a Operations,
+    id: u64,
+}
+
+impl Drop for OperationGuard<'_> {
+    fn drop(&mut self) {
+        let mut running = self
+            .operations
+            .running
+            .lock()
+            .expect("operations lock poisoned");
+
+        running.remove(&self.id);
+
+        if running.is_empty() {
+            self.operations.finished.notify_waiters();
+        }
+    }
+}
+
+#[tokio::test]
+async fn operations_test() {
+    let operations: &'static Operations = Box::leak(Box::default());
+
+    assert!(operations.wait(Duration::from_millis(10)).await);
+
+    let first = operations.start(1).unwrap();
+    let second = operations.start(2).unwrap();
+
+    assert!(!operations.wait(Duration::from_millis(10)).await);
+
+    let mut cancelled = operations.cancel();
+    cancelled.sort_unstable();
+    assert_eq!(cancelled, [1, 2]);
+    assert!(operations.is_cancelled());
+
+    drop(first);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        drop(second);
+    });
+
+    assert!(operations.wait(Duration::from_secs(5)).await);
+    assert_eq!(operations.running(), 0);
+
+    operations.stop();
+
+    assert!(operations.start(3).is_none());
+    assert_eq!(operations.running(), 0);
+}
+
+#[tokio::test]
+async fn finish_operations_test() {
+    use crate::fake_bot_api::FakeBotApi;
+
+    let api = FakeBotApi::start().await;
+    let operations: &'static Operations = Box::leak(Box::default());
+
+    // finished before deadline
+    let guard = operations.start(1).unwrap();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        drop(guard);
+    });
+    finish_operations(&api.bot(), operations, Duration::from_secs(5)).await;
+
+    assert!(!operations.is_cancelled());
+    assert!(api.sent_texts().is_empty());
+    // operation of the update, which is processed after shutdown begins, isn't started
+    assert!(operations.start(2).is_none());
+
+    // stops at the next sticker after cancel
+    let operations: &'static Operations = Box::leak(Box::default());
+    let guard = operations.start(2).unwrap();
+    tokio::spawn(async move {
+        while !operations.is_cancelled() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        drop(guard);
+    });
+    finish_operations(&api.bot(), operations, Duration::from_millis(10)).await;
+
+    assert_eq!(operations.running(), 0);
+    assert_eq!(api.sent_texts(), [interrupted_operation_message()]);
+    assert_eq!(api.calls_of("sendMessage")[0]["chat_id"], 2);
+}
@@ -1,4 +1,4 @@
-use std::{io, time::Duration};
+use std::{future::Future, io, time::Duration};
 
 use grammers_client::{Client, Config, FixedReconnect, InitParams, SignInError};
 use grammers_session::Session;
@@ -8,17 +8,67 @@ use grammers_tl_types::{
     types::{self, InputStickerSetShortName},
 };
 
-use tracing::error;
+use tracing::{error, warn, Instrument as _};
+
+use crate::{metrics::METRICS, telemetry::invoke_span};
 
 mod constants;
-mod errors;
+pub mod errors;
 use constants::SESSION_FILE;
+use errors::OwnerError;
 
 static RECONNECT_POLICY: FixedReconnect = FixedReconnect {
     attempts: 3,
     delay: Duration::from_millis(100),
 };
 
+/// Retries of the requests to get owner of the sticker set, which are shared by all handlers
+pub static OWNER_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    attempts: 6,
+    delay: Duration::from_millis(100),
+    timeout: Duration::from_secs(10),
+};
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    /// Delay between failed attempts
+    pub delay: Duration,
+    /// Time limit of all attempts
+    pub timeout: Duration,
+}
+
+impl RetryPolicy {
+    /// Call `request` until it succeeds, attempts run out or time is out.
+    /// Errors, which are not fixed by retry (like missing sticker set), are returned at once.
+    pub async fn run<T, F, Fut>(&self, mut request: F) -> Result<T, OwnerError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, OwnerError>>,
+    {
+        let attempts = async {
+            let mut attempt = 1;
+
+            loop {
+                match request().await {
+                    Err(err) if err.is_retryable() && attempt < self.attempts => {
+                        warn!(?err, attempt, "An error occurded while request Telegram:");
+
+                        attempt += 1;
+
+                        tokio::time::sleep(self.delay).await;
+                    }
+                    result => return result,
+                }
+            }
+        };
+
+        tokio::time::timeout(self.timeout, attempts)
+            .await
+            .unwrap_or(Err(OwnerError::Timeout))
+    }
+}
+
 pub async fn client_connect(api_id: i32, api_hash: String) -> Result<Client, errors::Error> {
     Ok(Client::connect(Config {
         session: Session::load_file_or_create(SESSION_FILE)?,
@@ -32,6 +82,11 @@ pub async fn client_connect(api_id: i32, api_hash: String) -> Result<Client, err
     .await?)
 }
 
+/// Save session of the client, so authorization isn't lost after restart
+pub fn client_save_session(client: &Client) -> io::Result<()> {
+    client.session().save_to_file(SESSION_FILE)
+}
+
 pub async fn client_authorize(
     client: &Client,
     phone: &str,
@@ -74,10 +129,17 @@ pub async fn client_authorize(
     Ok(())
 }
 
-pub async fn get_sticker_set_user_id(
-    set_name: &str,
-    client: &Client,
-) -> Result<i64, errors::Error> {
+/// Get owner of the sticker set with retries of [`OWNER_RETRY_POLICY`]
+pub async fn resolve_sticker_set_owner(set_name: &str, client: &Client) -> Result<i64, OwnerError> {
+    OWNER_RETRY_POLICY
+        .run(|| get_sticker_set_user_id(set_name, client))
+        .await
+}
+
+/// Get owner of the sticker set in one request, use [`resolve_sticker_set_owner`] to retry it
+pub async fn get_sticker_set_user_id(set_name: &str, client: &Client) -> Result<i64, OwnerError> {
+    let _timer = METRICS.get_sticker_set_user_id_duration.start_timer();
+
     let set_id = match client
         .invoke(&GetStickerSet {
             stickerset: InputStickerSet::ShortName(InputStickerSetShortName {
@@ -85,19 +147,126 @@ pub async fn get_sticker_set_user_id(
             }),
             hash: 0,
         })
+        .instrument(invoke_span("messages.getStickerSet"))
         .await?
     {
         enums::messages::StickerSet::Set(types::messages::StickerSet {
             set: enums::StickerSet::Set(types::StickerSet { id, .. }),
             ..
         }) => id,
-        _ => todo!(),
+        // we dont pass hash, so sticker set is returned if it exists
+        enums::messages::StickerSet::NotModified => return Err(OwnerError::NotFound),
     };
 
-    let mut user_id = set_id >> 32;
-    if set_id >> 24 & 0xff == 1 {
-        user_id += 0x100000000
+    Ok(sticker_set_owner_id(set_id))
+}
+
+/// Decode ID of the owner from ID of the sticker set: the upper 32 bits of the set ID are the owner ID.
+/// IDs of the users don't fit into 32 bits anymore, so owners with larger IDs are marked by `1` in the
+/// next byte of the set ID (bits 24-31), and their ID is larger by `0x100000000`.
+pub const fn sticker_set_owner_id(set_id: i64) -> i64 {
+    let mut owner_id = set_id >> 32;
+
+    if (set_id >> 24) & 0xff == 1 {
+        owner_id += 0x100000000;
     }
 
-    Ok(user_id)
+    owner_id
+}
+
+/// Return keywords of each sticker in the sticker set (in the same order as stickers in the set).
+/// Bot API doesn't return keywords of stickers, so we get them using client application.
+pub async fn get_sticker_set_keywords(
+    set_name: &str,
+    client: &Client,
+) -> Result<Vec<Vec<String>>, errors::Error> {
+    let (documents, keywords) = match client
+        .invoke(&GetStickerSet {
+            stickerset: InputStickerSet::ShortName(InputStickerSetShortName {
+                short_name: set_name.to_owned(),
+            }),
+            hash: 0,
+        })
+        .instrument(invoke_span("messages.getStickerSet"))
+        .await?
+    {
+        enums::messages::StickerSet::Set(types::messages::StickerSet {
+            documents,
+            keywords,
+            ..
+        }) => (documents, keywords),
+        // we dont pass hash, so sticker set cant be not modified
+        enums::messages::StickerSet::NotModified => return Ok(Vec::new()),
+    };
+
+    Ok(documents
+        .into_iter()
+        .map(|document| {
+            let document_id = match document {
+                enums::Document::Document(types::Document { id, .. }) => id,
+                enums::Document::Empty(types::DocumentEmpty { id }) => id,
+            };
+
+            keywords
+                .iter()
+                .filter_map(|keyword| match keyword {
+                    enums::StickerKeyword::Keyword(keyword)
+                        if keyword.document_id == document_id =>
+                    {
+                        Some(keyword.keyword.clone())
+                    }
+                    _ => None,
+                })
+                .flatten()
+                .collect()
+        })
+        .collect())
+}
+
+#[test]
+fn sticker_set_owner_id_test() {
+    assert_eq!(sticker_set_owner_id(123_456_789 << 32), 123_456_789);
+    assert_eq!(
+        sticker_set_owner_id((123_456_789 << 32) | 0xabcdef),
+        123_456_789
+    );
+    // marker of the owner ID larger than 32 bits
+    assert_eq!(
+        sticker_set_owner_id((1_234 << 32) | (1 << 24) | 0xabcdef),
+        0x100000000 + 1_234
+    );
+    // other values of the byte are not marker
+    assert_eq!(sticker_set_owner_id((1_234 << 32) | (2 << 24)), 1_234);
+}
+
+#[tokio::test]
+async fn retry_policy_test() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    let policy = RetryPolicy {
+        attempts: 3,
+        delay: Duration::ZERO,
+        timeout: Duration::from_millis(100),
+    };
+    let calls = AtomicU32::new(0);
+
+    // missing sticker set isn't retried
+    let result: Result<i64, _> = policy
+        .run(|| async {
+            calls.fetch_add(1, Ordering::Relaxed);
+
+            Err(OwnerError::NotFound)
+        })
+        .await;
+    assert!(matches!(result, Err(OwnerError::NotFound)));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+    let result: Result<i64, _> = policy
+        .run(|| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            Ok(1)
+        })
+        .await;
+    assert!(matches!(result, Err(OwnerError::Timeout)));
 }
@@ -2,6 +2,8 @@ use grammers_client::{
     client::bots::{AuthorizationError, InvocationError},
     SignInError,
 };
+use std::time::Duration;
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -20,3 +22,35 @@ pub enum Error {
     #[error(transparent)]
     Std(#[from] std::io::Error),
 }
+
+/// Errors of getting owner of the sticker set
+#[derive(Error, Debug)]
+pub enum OwnerError {
+    #[error("Sticker set not found")]
+    NotFound,
+    #[error("Too many requests, retry after {0:?}")]
+    FloodWait(Duration),
+    #[error("Too long time to get owner of the sticker set")]
+    Timeout,
+    #[error(transparent)]
+    InvocationError(InvocationError),
+}
+
+impl OwnerError {
+    /// Other errors are not fixed by retry at once
+    pub const fn is_retryable(&self) -> bool {
+        matches!(self, Self::InvocationError(_))
+    }
+}
+
+impl From<InvocationError> for OwnerError {
+    fn from(err: InvocationError) -> Self {
+        match err {
+            InvocationError::Rpc(rpc) if rpc.name == "FLOOD_WAIT" => {
+                Self::FloodWait(Duration::from_secs(rpc.value.unwrap_or_default().into()))
+            }
+            err if err.is("STICKERSET_INVALID") => Self::NotFound,
+            err => Self::InvocationError(err),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use opentelemetry::{trace::TraceError, trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig as _;
+use opentelemetry_sdk::{
+    runtime,
+    trace::{Config, Sampler, Tracer, TracerProvider},
+    Resource,
+};
+use tracing::{info_span, Span, Subscriber};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::TelemetryConfig;
+
+/// Name of the tracer, which creates spans of the bot
+const TRACER_NAME: &str = "steal_stickers_bot";
+
+/// Create provider, which exports spans in batches to OpenTelemetry collector using OTLP over gRPC.
+/// Note, that it should be created inside Tokio runtime.
+pub fn otlp_tracer_provider(config: &TelemetryConfig) -> Result<TracerProvider, TraceError> {
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(config.endpoint.as_str()),
+        )
+        .with_trace_config(
+            Config::default()
+                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                    config.sample_ratio,
+                ))))
+                .with_resource(Resource::new([KeyValue::new(
+                    "service.name",
+                    config.service_name.clone(),
+                )])),
+        )
+        .install_batch(runtime::Tokio)
+}
+
+/// Layer, which passes spans of `tracing` into the provider
+pub fn otel_layer<S>(provider: &TracerProvider) -> OpenTelemetryLayer<S, Tracer>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(TRACER_NAME))
+}
+
+/// Span of the request of client application, `request` is name of the MTProto function
+pub fn invoke_span(request: &'static str) -> Span {
+    info_span!("client_invoke", otel.kind = "client", request)
+}
+
+#[test]
+fn otel_layer_test() {
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+    use tracing_subscriber::layer::SubscriberExt as _;
+
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+
+    let subscriber = tracing_subscriber::registry().with(otel_layer(&provider));
+
+    tracing::subscriber::with_default(subscriber, || {
+        let _update = info_span!("update", update_id = 1).entered();
+        let _query = info_span!("db_query", db.system = "sqlite").entered();
+    });
+
+    provider.force_flush();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let names: Vec<&str> = spans.iter().map(|span| span.name.as_ref()).collect();
+    assert_eq!(names, ["db_query", "update"]);
+
+    // query is a child of the update
+    assert_eq!(spans[0].parent_span_id, spans[1].span_context.span_id());
+    assert!(spans[0]
+        .attributes
+        .contains(&KeyValue::new("db.system", "sqlite")));
+}
@@ -0,0 +1,172 @@
+use std::{future::Future, sync::Arc};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router as HttpRouter,
+};
+use telers::{
+    errors::session::ErrorKind,
+    methods::{DeleteWebhook, SetWebhook},
+    types::Update,
+    Bot,
+};
+use tokio::net::TcpListener;
+use tracing::{debug, error};
+
+use crate::{config::WebhookConfig, shutdown::shutdown_signal};
+
+/// Header, in which Telegram sends secret token specified in `setWebhook`
+pub const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error(transparent)]
+    Telegram(#[from] ErrorKind),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Clone)]
+struct WebhookState<Feed> {
+    secret_token: Arc<str>,
+    feed: Feed,
+}
+
+/// Create HTTP router, which accepts updates on the `path` and passes them into `feed`.
+/// Requests without valid secret token are rejected.
+pub fn webhook_router<Feed, Fut>(path: &str, secret_token: &str, feed: Feed) -> HttpRouter
+where
+    Feed: Fn(Update) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    HttpRouter::new()
+        .route(path, post(handle_update::<Feed, Fut>))
+        .with_state(WebhookState {
+            secret_token: secret_token.into(),
+            feed,
+        })
+}
+
+async fn handle_update<Feed, Fut>(
+    State(state): State<WebhookState<Feed>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode
+where
+    Feed: Fn(Update) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let secret_token = headers
+        .get(SECRET_TOKEN_HEADER)
+        .and_then(|secret_token| secret_token.to_str().ok());
+
+    if secret_token != Some(state.secret_token.as_ref()) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let update: Update = match serde_json::from_slice(&body) {
+        Ok(update) => update,
+        Err(err) => {
+            error!(?err, "An error occurded while parse update:");
+
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+
+    // answer to Telegram immediately, otherwise it resends update after timeout
+    tokio::spawn((state.feed)(update));
+
+    StatusCode::OK
+}
+
+/// Set webhook, serve updates until SIGINT or SIGTERM is received and delete webhook after that
+pub async fn run_webhook<Feed, Fut>(
+    bot: &Bot,
+    config: &WebhookConfig,
+    allowed_updates: Vec<String>,
+    feed: Feed,
+) -> Result<(), WebhookError>
+where
+    Feed: Fn(Update) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind(&config.address).await?;
+
+    bot.send(
+        SetWebhook::new(config.url.as_str())
+            .secret_token(config.secret_token.as_str())
+            .allowed_updates(allowed_updates),
+    )
+    .await?;
+
+    debug!(address = %config.address, url = %config.url, "Webhook is set");
+
+    let result = axum::serve(
+        listener,
+        webhook_router(&config.path, &config.secret_token, feed),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await;
+
+    bot.send(DeleteWebhook::new()).await?;
+
+    debug!("Webhook is deleted");
+
+    result.map_err(Into::into)
+}
+
+#[tokio::test]
+async fn webhook_router_test() {
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let router = webhook_router("/webhook", "secret", move |update: Update| {
+        let sender = sender.clone();
+
+        async move {
+            sender.send(update.update_id).unwrap();
+        }
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = reqwest::Client::new();
+    let update = json!({ "update_id": 1, "message": {
+        "message_id": 1,
+        "date": 0,
+        "chat": { "id": 1, "type": "private", "first_name": "Test" },
+        "text": "/start",
+    }})
+    .to_string();
+
+    let response = client.post(&url).body(update.clone()).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = client
+        .post(&url)
+        .header(SECRET_TOKEN_HEADER, "wrong")
+        .body(update.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = client
+        .post(&url)
+        .header(SECRET_TOKEN_HEADER, "secret")
+        .body(update)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(receiver.recv().await, Some(1));
+}